@@ -7,14 +7,15 @@ use tauri::Wry;
 use tauri_plugin_zubridge::{plugin, ZubridgeOptions, STATE_UPDATE_EVENT};
 
 use crate::modes::{
-    custom::CustomStore, redux::ReduxStore, zustand_basic::BasicStore,
-    zustand_handlers::HandlersStore, zustand_reducers::ReducersStore, ZubridgeMode,
+    custom::CustomStore, redux, zustand_basic::BasicStore, zustand_handlers::HandlersStore,
+    zustand_reducers, ZubridgeMode,
 };
 
 /// Build the Zubridge plugin for the given mode.
 pub fn build_plugin(mode: ZubridgeMode) -> TauriPlugin<Wry> {
     let options = ZubridgeOptions {
         event_name: STATE_UPDATE_EVENT.to_string(),
+        ..ZubridgeOptions::default()
     };
     println!(
         "[Bridge] Building Zubridge plugin for mode={} event_name={}",
@@ -24,8 +25,8 @@ pub fn build_plugin(mode: ZubridgeMode) -> TauriPlugin<Wry> {
     match mode {
         ZubridgeMode::ZustandBasic => plugin(BasicStore::new(), options),
         ZubridgeMode::ZustandHandlers => plugin(HandlersStore::new(), options),
-        ZubridgeMode::ZustandReducers => plugin(ReducersStore::new(), options),
-        ZubridgeMode::Redux => plugin(ReduxStore::new(), options),
+        ZubridgeMode::ZustandReducers => plugin(zustand_reducers::create_store(), options),
+        ZubridgeMode::Redux => plugin(redux::create_store(), options),
         ZubridgeMode::Custom => plugin(CustomStore::new(), options),
     }
 }
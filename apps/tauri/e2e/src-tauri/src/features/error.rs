@@ -1,8 +1,11 @@
 use std::fmt;
 
-/// Errors a state-manager can raise while applying an action. Returned as a
-/// JSON `{ success: false, error: ".." }` envelope so the renderer's
-/// `TauriCommandError` reporter has something useful to show.
+use tauri_plugin_zubridge::ZubridgeError;
+
+/// Errors a state-manager can raise while applying an action. Surfaced as
+/// `ZubridgeError::ActionProcessing` so the plugin rejects the dispatch
+/// without touching state and the renderer's `TauriCommandError` reporter
+/// has something useful to show.
 #[derive(Debug, Clone)]
 pub enum ActionError {
     /// Action `type` field was missing or wasn't a string.
@@ -37,8 +40,14 @@ impl fmt::Display for ActionError {
 
 impl std::error::Error for ActionError {}
 
+impl From<ActionError> for ZubridgeError {
+    fn from(error: ActionError) -> Self {
+        ZubridgeError::ActionProcessing(error.to_string())
+    }
+}
+
 /// The single action type the renderer fires when exercising error paths in
-/// the e2e tests. Callers should bubble this up through the StateManager so
+/// the e2e tests. Callers should bubble this up through the TryStateManager so
 /// the renderer's `TauriCommandError` surface is exercised.
 pub fn trigger_main_process_error() -> ActionError {
     ActionError::TriggeredMainProcessError
@@ -6,7 +6,7 @@
 use std::sync::{Arc, Mutex};
 
 use serde_json::Value;
-use tauri_plugin_zubridge::StateManager;
+use tauri_plugin_zubridge::{TryStateManager, ZubridgeError};
 use tokio::sync::broadcast;
 
 use crate::features::{
@@ -15,7 +15,7 @@ use crate::features::{
     state::BaseState,
     theme::Theme,
 };
-use crate::store::{error_envelope, filler_for, AppAction};
+use crate::store::{filler_for, AppAction};
 
 const LOG_TAG: &str = "[Custom]";
 const STATE_CHANGE_CAPACITY: usize = 32;
@@ -89,22 +89,16 @@ impl Default for CustomStore {
     }
 }
 
-impl StateManager for CustomStore {
+impl TryStateManager for CustomStore {
     fn get_initial_state(&self) -> Value {
         serde_json::to_value(self.snapshot()).unwrap_or(Value::Null)
     }
 
-    fn dispatch_action(&mut self, action: Value) -> Value {
-        match AppAction::parse(&action) {
-            Ok(parsed) => {
-                println!("{} Processing {:?}", LOG_TAG, parsed);
-                match self.apply(parsed) {
-                    Ok(new_state) => serde_json::to_value(new_state).unwrap_or(Value::Null),
-                    Err(error) => error_envelope(&self.snapshot(), &error),
-                }
-            }
-            Err(error) => error_envelope(&self.snapshot(), &error),
-        }
+    fn try_dispatch_action(&mut self, action: Value) -> Result<Value, ZubridgeError> {
+        let parsed = AppAction::parse(&action)?;
+        println!("{} Processing {:?}", LOG_TAG, parsed);
+        let new_state = self.apply(parsed)?;
+        Ok(serde_json::to_value(new_state).unwrap_or(Value::Null))
     }
 }
 
@@ -116,7 +110,9 @@ mod tests {
     #[test]
     fn dispatch_increment_works() {
         let mut store = CustomStore::new();
-        let result = store.dispatch_action(json!({ "type": "COUNTER:INCREMENT" }));
+        let result = store
+            .try_dispatch_action(json!({ "type": "COUNTER:INCREMENT" }))
+            .unwrap();
         assert_eq!(result.get("counter").and_then(Value::as_i64), Some(1));
     }
 
@@ -124,7 +120,9 @@ mod tests {
     fn subscribe_receives_state_after_dispatch() {
         let mut store = CustomStore::new();
         let mut rx = store.subscribe();
-        store.dispatch_action(json!({ "type": "COUNTER:INCREMENT" }));
+        store
+            .try_dispatch_action(json!({ "type": "COUNTER:INCREMENT" }))
+            .unwrap();
         // Receiver runs synchronously here because we're using broadcast.
         let received = rx.try_recv().unwrap();
         assert_eq!(received.counter, 1);
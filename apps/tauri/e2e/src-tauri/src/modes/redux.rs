@@ -1,20 +1,13 @@
 //! Mirrors `apps/electron/e2e/src/modes/redux` - the JS version uses Redux
-//! Toolkit slices combined into a `rootReducer`. Here we model the same
-//! pattern: a single `root_reducer` walks the action through each slice
-//! reducer, returning a brand-new state on every dispatch.
-
-use std::sync::Mutex;
+//! Toolkit slices combined into a `rootReducer`. Here each slice reducer is
+//! registered on a `SliceStateManager`, the `combineReducers` counterpart,
+//! which also reports the slices an action changed.
 
 use serde_json::Value;
-use tauri_plugin_zubridge::StateManager;
+use tauri_plugin_zubridge::SliceStateManager;
 
-use crate::features::{
-    counter,
-    error::{trigger_main_process_error, ActionError},
-    state::BaseState,
-    theme::Theme,
-};
-use crate::store::{error_envelope, filler_for, AppAction};
+use crate::features::{counter, state::BaseState, theme::Theme};
+use crate::store::{filler_for, typed_slice, AppAction};
 
 const LOG_TAG: &str = "[Redux]";
 
@@ -51,103 +44,63 @@ fn filler_reducer(state: Option<Value>, action: &AppAction) -> Option<Value> {
     }
 }
 
-fn root_reducer(state: BaseState, action: &AppAction) -> BaseState {
-    if matches!(action, AppAction::StateReset) {
-        return BaseState::initial();
-    }
-    BaseState {
-        counter: counter_reducer(state.counter, action),
-        theme: theme_reducer(state.theme, action),
-        filler: filler_reducer(state.filler, action),
-    }
-}
-
-pub struct ReduxStore {
-    state: Mutex<BaseState>,
-}
-
-impl ReduxStore {
-    pub fn new() -> Self {
-        Self {
-            state: Mutex::new(BaseState::initial()),
-        }
-    }
-
-    fn snapshot(&self) -> BaseState {
-        self.state
-            .lock()
-            .expect("ReduxStore mutex should not be poisoned")
-            .clone()
-    }
-
-    fn apply(&self, action: AppAction) -> Result<BaseState, ActionError> {
-        if matches!(action, AppAction::ErrorTriggerMainProcessError) {
-            return Err(trigger_main_process_error());
-        }
-        let mut guard = self
-            .state
-            .lock()
-            .expect("ReduxStore mutex should not be poisoned");
-        println!("{} Dispatching {:?}", LOG_TAG, action);
-        let next = root_reducer(guard.clone(), &action);
-        *guard = next.clone();
-        Ok(next)
-    }
-}
-
-impl Default for ReduxStore {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl StateManager for ReduxStore {
-    fn get_initial_state(&self) -> Value {
-        serde_json::to_value(self.snapshot()).unwrap_or(Value::Null)
-    }
-
-    fn dispatch_action(&mut self, action: Value) -> Value {
-        match AppAction::parse(&action) {
-            Ok(parsed) => match self.apply(parsed) {
-                Ok(new_state) => serde_json::to_value(new_state).unwrap_or(Value::Null),
-                Err(error) => error_envelope(&self.snapshot(), &error),
-            },
-            Err(error) => error_envelope(&self.snapshot(), &error),
-        }
-    }
+/// Build the store: one slice per `BaseState` field.
+pub fn create_store() -> SliceStateManager {
+    println!("{} Combining counter, theme and filler slices", LOG_TAG);
+    let initial = BaseState::initial();
+    SliceStateManager::builder()
+        .try_slice("counter", typed_slice(initial.counter, counter_reducer))
+        .try_slice("theme", typed_slice(initial.theme, theme_reducer))
+        .try_slice("filler", typed_slice(initial.filler, filler_reducer))
+        .build()
+        .expect("Redux slices should have distinct keys and accept the init action")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
+    use tauri_plugin_zubridge::{TryStateManager, ZubridgeError};
 
     #[test]
-    fn root_reducer_returns_initial_on_state_reset() {
-        let state = BaseState {
-            counter: 99,
-            theme: Theme::Light,
-            filler: None,
-        };
-        let result = root_reducer(state, &AppAction::StateReset);
-        assert_eq!(result.counter, 0);
-        assert_eq!(result.theme, Theme::Dark);
+    fn state_reset_returns_every_slice_to_initial() {
+        let mut store = create_store();
+        store
+            .try_dispatch_action(json!({ "type": "COUNTER:SET", "payload": 99 }))
+            .unwrap();
+        store
+            .try_dispatch_action(json!({ "type": "THEME:TOGGLE" }))
+            .unwrap();
+        let result = store
+            .try_dispatch_action(json!({ "type": "STATE:RESET" }))
+            .unwrap();
+        assert_eq!(result.get("counter").and_then(Value::as_i64), Some(0));
+        assert_eq!(result.get("theme").and_then(Value::as_str), Some("dark"));
+        assert_eq!(store.last_changed_keys(), ["counter", "theme"]);
     }
 
     #[test]
     fn dispatch_double_doubles_counter() {
-        let mut store = ReduxStore::new();
-        store.dispatch_action(json!({ "type": "COUNTER:SET", "payload": 5 }));
-        let result = store.dispatch_action(json!({ "type": "COUNTER:DOUBLE" }));
+        let mut store = create_store();
+        store
+            .try_dispatch_action(json!({ "type": "COUNTER:SET", "payload": 5 }))
+            .unwrap();
+        let result = store
+            .try_dispatch_action(json!({ "type": "COUNTER:DOUBLE" }))
+            .unwrap();
         assert_eq!(result.get("counter").and_then(Value::as_i64), Some(10));
+        assert_eq!(store.last_changed_keys(), ["counter"]);
     }
 
     #[test]
     fn intentional_error_does_not_mutate_state() {
-        let mut store = ReduxStore::new();
-        store.dispatch_action(json!({ "type": "COUNTER:SET", "payload": 7 }));
-        let result = store.dispatch_action(json!({ "type": "ERROR:TRIGGER_MAIN_PROCESS_ERROR" }));
-        assert_eq!(result.get("success").and_then(Value::as_bool), Some(false));
+        let mut store = create_store();
+        store
+            .try_dispatch_action(json!({ "type": "COUNTER:SET", "payload": 7 }))
+            .unwrap();
+        let result =
+            store.try_dispatch_action(json!({ "type": "ERROR:TRIGGER_MAIN_PROCESS_ERROR" }));
+        assert!(matches!(result, Err(ZubridgeError::ActionProcessing(_))));
         // Counter still 7
         let snap = store.get_initial_state();
         assert_eq!(snap.get("counter").and_then(Value::as_i64), Some(7));
@@ -6,10 +6,10 @@
 use std::sync::Mutex;
 
 use serde_json::Value;
-use tauri_plugin_zubridge::StateManager;
+use tauri_plugin_zubridge::{TryStateManager, ZubridgeError};
 
 use crate::features::{counter, state::BaseState, theme::Theme};
-use crate::store::{error_envelope, filler_for, AppAction};
+use crate::store::{filler_for, AppAction};
 
 const LOG_TAG: &str = "[Basic]";
 
@@ -92,19 +92,15 @@ impl Default for BasicStore {
     }
 }
 
-impl StateManager for BasicStore {
+impl TryStateManager for BasicStore {
     fn get_initial_state(&self) -> Value {
         serde_json::to_value(self.snapshot()).unwrap_or(Value::Null)
     }
 
-    fn dispatch_action(&mut self, action: Value) -> Value {
-        match AppAction::parse(&action) {
-            Ok(parsed) => match self.apply(parsed) {
-                Ok(new_state) => serde_json::to_value(new_state).unwrap_or(Value::Null),
-                Err(error) => error_envelope(&self.snapshot(), &error),
-            },
-            Err(error) => error_envelope(&self.snapshot(), &error),
-        }
+    fn try_dispatch_action(&mut self, action: Value) -> Result<Value, ZubridgeError> {
+        let parsed = AppAction::parse(&action)?;
+        let new_state = self.apply(parsed)?;
+        Ok(serde_json::to_value(new_state).unwrap_or(Value::Null))
     }
 }
 
@@ -116,26 +112,28 @@ mod tests {
     #[test]
     fn dispatch_increment_increments_state() {
         let mut store = BasicStore::new();
-        let result = store.dispatch_action(json!({ "type": "COUNTER:INCREMENT" }));
+        let result = store
+            .try_dispatch_action(json!({ "type": "COUNTER:INCREMENT" }))
+            .unwrap();
         assert_eq!(result.get("counter").and_then(Value::as_i64), Some(1));
     }
 
     #[test]
-    fn dispatch_unknown_returns_error_envelope() {
+    fn dispatch_unknown_returns_action_error() {
         let mut store = BasicStore::new();
-        let result = store.dispatch_action(json!({ "type": "MYSTERY" }));
-        assert_eq!(result.get("success").and_then(Value::as_bool), Some(false));
-        assert!(result
-            .get("error")
-            .and_then(Value::as_str)
-            .unwrap_or("")
-            .contains("Unknown action"));
+        let error = store
+            .try_dispatch_action(json!({ "type": "MYSTERY" }))
+            .unwrap_err();
+        assert!(matches!(error, ZubridgeError::ActionProcessing(_)));
+        assert!(error.to_string().contains("Unknown action"));
     }
 
     #[test]
     fn theme_toggle_flips_state() {
         let mut store = BasicStore::new();
-        let after_toggle = store.dispatch_action(json!({ "type": "THEME:TOGGLE" }));
+        let after_toggle = store
+            .try_dispatch_action(json!({ "type": "THEME:TOGGLE" }))
+            .unwrap();
         assert_eq!(
             after_toggle.get("theme").and_then(Value::as_str),
             Some("light")
@@ -1,20 +1,14 @@
 //! Mirrors `apps/electron/e2e/src/modes/zustand-reducers` - the JS version
 //! breaks state by slice (counter, theme, ...) and assigns a pure reducer
 //! per slice. Here we keep the same shape: each slice has a private
-//! `reduce_*` function and the `StateManager` impl wires them together.
-
-use std::sync::Mutex;
+//! `reduce_*` function, registered per top-level key on a
+//! `SliceStateManager`.
 
 use serde_json::Value;
-use tauri_plugin_zubridge::StateManager;
+use tauri_plugin_zubridge::SliceStateManager;
 
-use crate::features::{
-    counter,
-    error::{trigger_main_process_error, ActionError},
-    state::BaseState,
-    theme::Theme,
-};
-use crate::store::{error_envelope, filler_for, AppAction};
+use crate::features::{counter, state::BaseState, theme::Theme};
+use crate::store::{filler_for, typed_slice, AppAction};
 
 const LOG_TAG: &str = "[Reducers]";
 
@@ -51,73 +45,23 @@ fn reduce_filler(filler: Option<Value>, action: &AppAction) -> Option<Value> {
     }
 }
 
-pub struct ReducersStore {
-    state: Mutex<BaseState>,
-}
-
-impl ReducersStore {
-    pub fn new() -> Self {
-        Self {
-            state: Mutex::new(BaseState::initial()),
-        }
-    }
-
-    fn snapshot(&self) -> BaseState {
-        self.state
-            .lock()
-            .expect("ReducersStore mutex should not be poisoned")
-            .clone()
-    }
-
-    fn apply(&self, action: AppAction) -> Result<BaseState, ActionError> {
-        if matches!(action, AppAction::ErrorTriggerMainProcessError) {
-            return Err(trigger_main_process_error());
-        }
-        let mut guard = self
-            .state
-            .lock()
-            .expect("ReducersStore mutex should not be poisoned");
-
-        if matches!(action, AppAction::StateReset) {
-            println!("{} Resetting state", LOG_TAG);
-            *guard = BaseState::initial();
-            return Ok(guard.clone());
-        }
-
-        println!("{} Reducing action {:?}", LOG_TAG, action);
-        guard.counter = reduce_counter(guard.counter, &action);
-        guard.theme = reduce_theme(guard.theme, &action);
-        guard.filler = reduce_filler(guard.filler.take(), &action);
-        Ok(guard.clone())
-    }
-}
-
-impl Default for ReducersStore {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
-impl StateManager for ReducersStore {
-    fn get_initial_state(&self) -> Value {
-        serde_json::to_value(self.snapshot()).unwrap_or(Value::Null)
-    }
-
-    fn dispatch_action(&mut self, action: Value) -> Value {
-        match AppAction::parse(&action) {
-            Ok(parsed) => match self.apply(parsed) {
-                Ok(new_state) => serde_json::to_value(new_state).unwrap_or(Value::Null),
-                Err(error) => error_envelope(&self.snapshot(), &error),
-            },
-            Err(error) => error_envelope(&self.snapshot(), &error),
-        }
-    }
+/// Build the store from the per-slice reducers.
+pub fn create_store() -> SliceStateManager {
+    println!("{} Registering slice reducers", LOG_TAG);
+    let initial = BaseState::initial();
+    SliceStateManager::builder()
+        .try_slice("counter", typed_slice(initial.counter, reduce_counter))
+        .try_slice("theme", typed_slice(initial.theme, reduce_theme))
+        .try_slice("filler", typed_slice(initial.filler, reduce_filler))
+        .build()
+        .expect("Reducer slices should have distinct keys and accept the init action")
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
+    use tauri_plugin_zubridge::TryStateManager;
 
     #[test]
     fn counter_reducer_handles_increment_and_double() {
@@ -132,8 +76,11 @@ mod tests {
 
     #[test]
     fn dispatch_returns_new_state_with_increment() {
-        let mut store = ReducersStore::new();
-        let result = store.dispatch_action(json!({ "type": "COUNTER:INCREMENT" }));
+        let mut store = create_store();
+        let result = store
+            .try_dispatch_action(json!({ "type": "COUNTER:INCREMENT" }))
+            .unwrap();
         assert_eq!(result.get("counter").and_then(Value::as_i64), Some(1));
+        assert_eq!(store.last_changed_keys(), ["counter"]);
     }
 }
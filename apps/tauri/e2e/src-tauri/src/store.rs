@@ -1,11 +1,14 @@
 //! Shared store plumbing - extracts the legacy `{ type, payload }` action
-//! shape that `StateManager::dispatch_action` receives and exposes a
+//! shape that `TryStateManager::try_dispatch_action` receives and exposes a
 //! mode-agnostic `AppAction` enum the modes can match on.
 
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use serde_json::Value;
+use tauri_plugin_zubridge::{ZubridgeError, SLICE_INIT_ACTION_TYPE};
 
-use crate::features::error::ActionError;
-use crate::features::state::{generate_filler, BaseState, FillerVariant};
+use crate::features::error::{trigger_main_process_error, ActionError};
+use crate::features::state::{generate_filler, FillerVariant};
 
 /// Action labels mirrored from the Electron e2e fixture so the same renderer
 /// code drives the Tauri backend.
@@ -89,17 +92,42 @@ impl AppAction {
     }
 }
 
-/// Common JSON envelope used when a mode wants to surface a soft error
-/// without poisoning the stored state.
-pub fn error_envelope(state: &BaseState, error: &ActionError) -> Value {
-    let mut response = serde_json::Map::new();
-    response.insert(
-        "state".to_string(),
-        serde_json::to_value(state).unwrap_or(Value::Null),
-    );
-    response.insert("success".to_string(), Value::Bool(false));
-    response.insert("error".to_string(), Value::String(error.to_string()));
-    Value::Object(response)
+/// Parse the action a `SliceStateManager` reducer receives. `None` for the
+/// slice init action, which every reducer answers with its initial value.
+/// The intentional error action is rejected here, so every slice fails it and
+/// the dispatch leaves the state untouched.
+pub fn parse_slice_action(action: &Value) -> Result<Option<AppAction>, ActionError> {
+    if action.get("type").and_then(Value::as_str) == Some(SLICE_INIT_ACTION_TYPE) {
+        return Ok(None);
+    }
+    match AppAction::parse(action)? {
+        AppAction::ErrorTriggerMainProcessError => Err(trigger_main_process_error()),
+        parsed => Ok(Some(parsed)),
+    }
+}
+
+/// Lift a typed slice reducer onto the JSON slice value a
+/// `SliceStateManager` passes. The slice starts at `initial` and returns to
+/// it on `STATE:RESET`, so reducers only handle their own actions.
+pub fn typed_slice<T, F>(
+    initial: T,
+    reducer: F,
+) -> impl Fn(&Value, &Value) -> Result<Value, ZubridgeError> + Send + Sync + 'static
+where
+    T: Serialize + DeserializeOwned + Clone + Send + Sync + 'static,
+    F: Fn(T, &AppAction) -> T + Send + Sync + 'static,
+{
+    move |state, action| {
+        let next = match parse_slice_action(action)? {
+            None | Some(AppAction::StateReset) => initial.clone(),
+            Some(action) => {
+                let current =
+                    T::deserialize(state).map_err(|e| ZubridgeError::StateError(e.to_string()))?;
+                reducer(current, &action)
+            }
+        };
+        serde_json::to_value(next).map_err(|e| ZubridgeError::Serialization(e.to_string()))
+    }
 }
 
 /// Re-export `generate_filler` so modes can build filler state without
@@ -137,6 +165,17 @@ mod tests {
         assert!(matches!(result, Err(ActionError::UnknownAction(_))));
     }
 
+    #[test]
+    fn slice_init_action_parses_to_none() {
+        let action = json!({ "type": SLICE_INIT_ACTION_TYPE, "payload": null });
+        assert!(matches!(parse_slice_action(&action), Ok(None)));
+        let error = json!({ "type": "ERROR:TRIGGER_MAIN_PROCESS_ERROR" });
+        assert!(matches!(
+            parse_slice_action(&error),
+            Err(ActionError::TriggeredMainProcessError)
+        ));
+    }
+
     #[test]
     fn slow_variants_alias_canonical_actions() {
         let action = json!({ "type": "COUNTER:DOUBLE:SLOW" });
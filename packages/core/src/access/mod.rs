@@ -0,0 +1,486 @@
+//! Key-based access control for dispatched actions and thunks.
+//!
+//! The host grants each webview label a set of state keys
+//! ([`AccessControl::with_label_keys`], or [`AccessControl::with_default_keys`]
+//! for labels without their own grant), and an action may only touch keys its
+//! dispatching webview was granted. Grants are host configuration: unlike
+//! subscriptions, a webview cannot change them. The keys an action affects
+//! are the union of:
+//!
+//! 1. the host-registered mapping for its action type
+//!    ([`AccessControl::with_action_keys`]),
+//! 2. the action's own `keys`,
+//! 3. the `keys` of the thunk it was dispatched from.
+//!
+//! Keys declared by the webview can only widen the check, never replace the
+//! host's mapping. An action type with no mapping is denied unless
+//! [`AccessControl::allow_unmapped`] is set. Labels without any grant are
+//! unrestricted. An action may only name a thunk registered by its own
+//! webview.
+//! Mirrors `packages/electron/src/renderer/actionValidator.ts`, enforced on
+//! the host side instead of in the renderer.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::error::{Result, ZubridgeError};
+use crate::models::ZubridgeAction;
+use crate::orchestration::HOST_SOURCE_LABEL;
+use crate::subscription::StatePath;
+use crate::thunk::ThunkRecord;
+
+/// Key that grants access to every state key.
+pub const WILDCARD_KEY: &str = "*";
+
+// ── BypassPolicy ──────────────────────────────────────────────────────────────
+
+/// Which webviews may skip access control by setting
+/// `bypass_access_control`. A bypass request from any other webview is
+/// ignored and the action's keys are checked as usual.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum BypassPolicy {
+    /// No webview may bypass access control.
+    #[default]
+    Deny,
+    /// Every webview may bypass access control.
+    AllowAll,
+    /// Only the listed webview labels may bypass access control.
+    AllowLabels(HashSet<String>),
+}
+
+impl BypassPolicy {
+    pub fn permits(&self, label: &str) -> bool {
+        match self {
+            Self::Deny => false,
+            Self::AllowAll => true,
+            Self::AllowLabels(labels) => labels.contains(label),
+        }
+    }
+}
+
+// ── AccessControl ─────────────────────────────────────────────────────────────
+
+/// Host configuration for key-based access control. Carried on
+/// [`ZubridgeOptions`](crate::models::ZubridgeOptions).
+#[derive(Debug, Clone, Default)]
+pub struct AccessControl {
+    /// State keys affected by each action type.
+    pub action_keys: HashMap<String, Vec<String>>,
+    /// State keys each webview label may affect.
+    pub label_keys: HashMap<String, Vec<String>>,
+    /// State keys for labels without an entry in `label_keys`. `None` leaves
+    /// those labels unrestricted.
+    pub default_keys: Option<Vec<String>>,
+    /// Who may honour `bypass_access_control`.
+    pub bypass: BypassPolicy,
+    /// Trust the declared keys of action types with no entry in
+    /// `action_keys`, rather than rejecting them.
+    pub allow_unmapped: bool,
+}
+
+impl AccessControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare the state keys `action_type` affects.
+    pub fn with_action_keys<I, K>(mut self, action_type: impl Into<String>, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.action_keys.insert(
+            action_type.into(),
+            keys.into_iter().map(Into::into).collect(),
+        );
+        self
+    }
+
+    /// Grant the webview `label` the state keys `keys`. A key covers its
+    /// nested paths, and `*` covers every key.
+    pub fn with_label_keys<I, K>(mut self, label: impl Into<String>, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.label_keys
+            .insert(label.into(), keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Grant `keys` to every webview without a
+    /// [`with_label_keys`](Self::with_label_keys) entry.
+    pub fn with_default_keys<I, K>(mut self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        self.default_keys = Some(keys.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_bypass(mut self, bypass: BypassPolicy) -> Self {
+        self.bypass = bypass;
+        self
+    }
+
+    /// Check action types with no [`with_action_keys`](Self::with_action_keys)
+    /// mapping against the keys they declare, instead of rejecting them.
+    pub fn with_allow_unmapped(mut self, allow: bool) -> Self {
+        self.allow_unmapped = allow;
+        self
+    }
+
+    /// The keys granted to `label`, or `None` if it is unrestricted.
+    pub fn granted_keys(&self, label: &str) -> Option<&[String]> {
+        self.label_keys
+            .get(label)
+            .or(self.default_keys.as_ref())
+            .map(Vec::as_slice)
+    }
+
+    /// Check that `label` may dispatch `action`. `thunk` is the record of the
+    /// thunk named by `action.thunk_parent_id`, if any; it must have been
+    /// registered by `label`.
+    ///
+    /// Actions dispatched by the host itself ([`HOST_SOURCE_LABEL`]) are
+    /// always allowed.
+    pub fn check_action(
+        &self,
+        label: &str,
+        action: &ZubridgeAction,
+        thunk: Option<&ThunkRecord>,
+    ) -> Result<()> {
+        if label == HOST_SOURCE_LABEL {
+            return Ok(());
+        }
+        check_thunk_owner(label, thunk)?;
+        let bypass = action.bypass_access_control == Some(true)
+            || thunk.is_some_and(|t| t.bypass_access_control);
+        if self.bypass_permitted(label, bypass) {
+            return Ok(());
+        }
+        let Some(granted) = self.granted_keys(label) else {
+            return Ok(());
+        };
+        let mapped = self.action_keys.get(&action.action_type);
+        if mapped.is_none() && !self.allow_unmapped {
+            return Err(ZubridgeError::AccessDenied {
+                source_label: label.to_string(),
+                keys: Vec::new(),
+                message: format!("action type {} has no registered keys", action.action_type),
+            });
+        }
+        let keys: Vec<String> = mapped
+            .into_iter()
+            .chain(action.keys.as_ref())
+            .chain(thunk.and_then(|t| t.keys.as_ref()))
+            .flatten()
+            .cloned()
+            .collect();
+        check_keys(label, granted, &keys)
+    }
+
+    /// Check that `label` may undo or redo a history entry, where `keys` are
+    /// the top-level keys the step would restore (see
+    /// [`UndoHistory::affected_keys`](crate::history::UndoHistory::affected_keys)).
+    /// Used in place of [`check_action`](Self::check_action) for history
+    /// actions, whose keys depend on the entry rather than the action type.
+    pub fn check_history(
+        &self,
+        label: &str,
+        thunk: Option<&ThunkRecord>,
+        keys: &[String],
+    ) -> Result<()> {
+        if label == HOST_SOURCE_LABEL {
+            return Ok(());
+        }
+        check_thunk_owner(label, thunk)?;
+        match self.granted_keys(label) {
+            Some(granted) => check_keys(label, granted, keys),
+            None => Ok(()),
+        }
+    }
+
+    /// Check that `label` may register a thunk declaring `keys`.
+    pub fn check_thunk(
+        &self,
+        label: &str,
+        keys: Option<&[String]>,
+        bypass_access_control: bool,
+    ) -> Result<()> {
+        if label == HOST_SOURCE_LABEL || self.bypass_permitted(label, bypass_access_control) {
+            return Ok(());
+        }
+        match (self.granted_keys(label), keys) {
+            (Some(granted), Some(keys)) => check_keys(label, granted, keys),
+            _ => Ok(()),
+        }
+    }
+
+    fn bypass_permitted(&self, label: &str, requested: bool) -> bool {
+        if !requested {
+            return false;
+        }
+        let permitted = self.bypass.permits(label);
+        if !permitted {
+            log::debug!("zubridge: ignoring access-control bypass requested by {label}");
+        }
+        permitted
+    }
+}
+
+/// Reject an action from `label` that names a thunk registered by another
+/// webview.
+fn check_thunk_owner(label: &str, thunk: Option<&ThunkRecord>) -> Result<()> {
+    match thunk {
+        Some(thunk) if thunk.source_label != label => Err(ZubridgeError::AccessDenied {
+            source_label: label.to_string(),
+            keys: Vec::new(),
+            message: format!(
+                "thunk {} was registered by {}",
+                thunk.thunk_id, thunk.source_label
+            ),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Reject `keys` that fall outside the keys `granted` to `label`. A key is
+/// covered by a grant of itself or of any ancestor path (`user` covers
+/// `user.profile` and `user/profile`).
+fn check_keys(label: &str, granted: &[String], keys: &[String]) -> Result<()> {
+    if granted.iter().any(|key| key == WILDCARD_KEY) {
+        return Ok(());
+    }
+    let denied: Vec<String> = keys
+        .iter()
+        .filter(|key| !granted.iter().any(|grant| is_covered(key, grant)))
+        .cloned()
+        .collect();
+    if denied.is_empty() {
+        Ok(())
+    } else {
+        Err(ZubridgeError::AccessDenied {
+            source_label: label.to_string(),
+            message: format!("not granted {}", denied.join(", ")),
+            keys: denied,
+        })
+    }
+}
+
+fn is_covered(key: &str, grant: &str) -> bool {
+    StatePath::parse(key).starts_with(&StatePath::parse(grant))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::thunk::ThunkManager;
+
+    fn action(action_type: &str, keys: Option<&[&str]>) -> ZubridgeAction {
+        ZubridgeAction {
+            id: None,
+            action_type: action_type.to_string(),
+            payload: None,
+            source_label: None,
+            thunk_parent_id: None,
+            immediate: None,
+            keys: keys.map(|keys| keys.iter().map(|k| k.to_string()).collect()),
+            bypass_access_control: None,
+            starts_thunk: None,
+            ends_thunk: None,
+        }
+    }
+
+    /// `overlay` may only touch `theme`; `COUNTER:*` and `THEME:SET` are
+    /// mapped to their slices.
+    fn overlay_acl() -> AccessControl {
+        AccessControl::new()
+            .with_label_keys("overlay", ["theme"])
+            .with_action_keys("COUNTER:SET", ["counter"])
+            .with_action_keys("COUNTER:INCREMENT", ["counter"])
+            .with_action_keys("THEME:SET", ["theme"])
+    }
+
+    #[test]
+    fn keys_outside_the_grant_are_denied() {
+        let acl = overlay_acl();
+        let err = acl
+            .check_action("overlay", &action("COUNTER:SET", None), None)
+            .unwrap_err();
+        match err {
+            ZubridgeError::AccessDenied {
+                source_label, keys, ..
+            } => {
+                assert_eq!(source_label, "overlay");
+                assert_eq!(keys, vec!["counter".to_string()]);
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        acl.check_action("overlay", &action("THEME:SET", None), None)
+            .unwrap();
+    }
+
+    #[test]
+    fn ungranted_wildcard_and_host_labels_are_unrestricted() {
+        let acl = overlay_acl().with_label_keys("devtools", ["*"]);
+        let counter = action("COUNTER:SET", None);
+        acl.check_action("main", &counter, None).unwrap();
+        acl.check_action("main", &action("UNMAPPED", None), None)
+            .unwrap();
+        acl.check_action("devtools", &counter, None).unwrap();
+        acl.check_action(HOST_SOURCE_LABEL, &counter, None).unwrap();
+    }
+
+    #[test]
+    fn default_keys_apply_to_labels_without_a_grant() {
+        let acl = overlay_acl().with_default_keys(["counter"]);
+        acl.check_action("main", &action("COUNTER:SET", None), None)
+            .unwrap();
+        assert!(acl
+            .check_action("main", &action("THEME:SET", None), None)
+            .is_err());
+        assert!(acl
+            .check_action("overlay", &action("COUNTER:SET", None), None)
+            .is_err());
+    }
+
+    #[test]
+    fn nested_keys_are_covered_by_their_ancestors() {
+        let acl = AccessControl::new()
+            .with_label_keys("main", ["user"])
+            .with_allow_unmapped(true);
+        acl.check_action("main", &action("X", Some(&["user.profile"])), None)
+            .unwrap();
+        assert!(acl
+            .check_action("main", &action("X", Some(&["username"])), None)
+            .is_err());
+    }
+
+    #[test]
+    fn declared_keys_cannot_replace_the_mapping() {
+        let acl = overlay_acl();
+        for keys in [&[][..], &["theme"][..]] {
+            let err = acl
+                .check_action("overlay", &action("COUNTER:SET", Some(keys)), None)
+                .unwrap_err();
+            match err {
+                ZubridgeError::AccessDenied { keys, .. } => {
+                    assert_eq!(keys, vec!["counter".to_string()]);
+                }
+                other => panic!("unexpected error: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn unmapped_types_are_denied_unless_allowed() {
+        let acl = overlay_acl();
+        for keys in [None, Some(&["theme"][..])] {
+            let err = acl
+                .check_action("overlay", &action("UNMAPPED", keys), None)
+                .unwrap_err();
+            match err {
+                ZubridgeError::AccessDenied { keys, message, .. } => {
+                    assert!(keys.is_empty());
+                    assert_eq!(message, "action type UNMAPPED has no registered keys");
+                }
+                other => panic!("unexpected error: {other:?}"),
+            }
+        }
+        acl.check_action(HOST_SOURCE_LABEL, &action("UNMAPPED", None), None)
+            .unwrap();
+
+        let acl = overlay_acl().with_allow_unmapped(true);
+        acl.check_action("overlay", &action("UNMAPPED", Some(&["theme"])), None)
+            .unwrap();
+        assert!(acl
+            .check_action("overlay", &action("UNMAPPED", Some(&["counter"])), None)
+            .is_err());
+    }
+
+    #[test]
+    fn bypass_requires_host_permission() {
+        let bypassing = ZubridgeAction {
+            bypass_access_control: Some(true),
+            ..action("COUNTER:SET", None)
+        };
+
+        assert!(overlay_acl()
+            .check_action("overlay", &bypassing, None)
+            .is_err());
+
+        let labels = BypassPolicy::AllowLabels(HashSet::from(["overlay".to_string()]));
+        overlay_acl()
+            .with_bypass(labels)
+            .check_action("overlay", &bypassing, None)
+            .unwrap();
+    }
+
+    #[test]
+    fn thunk_keys_apply_to_its_actions() {
+        let acl = overlay_acl();
+        let mut thunks = ThunkManager::new();
+        thunks
+            .register(
+                "t1".into(),
+                None,
+                "overlay".into(),
+                Some(vec!["counter".into()]),
+                false,
+                false,
+            )
+            .unwrap();
+        let thunk = thunks.get("t1");
+        assert!(acl
+            .check_action("overlay", &action("THEME:SET", None), thunk)
+            .is_err());
+        assert!(acl
+            .check_thunk("overlay", Some(&["counter".to_string()]), false)
+            .is_err());
+        acl.check_thunk("overlay", Some(&["theme".to_string()]), false)
+            .unwrap();
+    }
+
+    #[test]
+    fn history_steps_are_limited_to_granted_keys() {
+        let acl = overlay_acl();
+        let keys = |keys: &[&str]| keys.iter().map(|k| k.to_string()).collect::<Vec<_>>();
+        acl.check_history("overlay", None, &keys(&["theme"]))
+            .unwrap();
+        acl.check_history("overlay", None, &[]).unwrap();
+        assert!(acl
+            .check_history("overlay", None, &keys(&["counter", "theme"]))
+            .is_err());
+        acl.check_history(HOST_SOURCE_LABEL, None, &keys(&["counter"]))
+            .unwrap();
+    }
+
+    #[test]
+    fn actions_may_not_join_another_webviews_thunk() {
+        let acl = AccessControl::new();
+        let mut thunks = ThunkManager::new();
+        thunks
+            .register("t1".into(), None, "main".into(), None, true, false)
+            .unwrap();
+        let thunk = thunks.get("t1");
+        let err = acl
+            .check_action("overlay", &action("ANY", None), thunk)
+            .unwrap_err();
+        match err {
+            ZubridgeError::AccessDenied {
+                source_label,
+                message,
+                ..
+            } => {
+                assert_eq!(source_label, "overlay");
+                assert_eq!(message, "thunk t1 was registered by main");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        acl.check_action("main", &action("ANY", None), thunk)
+            .unwrap();
+    }
+}
@@ -95,6 +95,8 @@ pub struct ActionScheduler {
     max_queue_size: usize,
     dropped_count: usize,
     needs_sort: bool,
+    /// Action evicted by the most recent `enqueue` call to make room, if any.
+    last_evicted: Option<QueuedAction>,
 }
 
 impl Default for ActionScheduler {
@@ -110,6 +112,7 @@ impl ActionScheduler {
             max_queue_size: 1000,
             dropped_count: 0,
             needs_sort: false,
+            last_evicted: None,
         }
     }
 
@@ -127,6 +130,7 @@ impl ActionScheduler {
         source_label: String,
         ctx: &SchedulerContext,
     ) -> EnqueueResult {
+        self.last_evicted = None;
         if action.id.is_none() {
             action.id = Some(uuid::Uuid::new_v4().to_string());
         }
@@ -200,6 +204,16 @@ impl ActionScheduler {
         self.queue.len()
     }
 
+    /// Take the action that the most recent [`enqueue`](Self::enqueue) call
+    /// evicted to make room for a higher-priority one.
+    ///
+    /// Only the latest call is remembered — callers that track queued actions
+    /// (e.g. to resolve a waiting IPC request) should check this after every
+    /// enqueue that returns `Queued`.
+    pub fn take_evicted(&mut self) -> Option<QueuedAction> {
+        self.last_evicted.take()
+    }
+
     pub fn dropped_count(&self) -> usize {
         self.dropped_count
     }
@@ -230,7 +244,7 @@ impl ActionScheduler {
 
         match droppable_pos {
             Some(pos) => {
-                self.queue.remove(pos);
+                self.last_evicted = Some(self.queue.remove(pos));
                 self.dropped_count += 1;
                 OverflowDecision::AcceptNew
             }
@@ -246,7 +260,7 @@ impl ActionScheduler {
                         .min_by_key(|(_, q)| q.received_at)
                         .map(|(i, _)| i)
                         .unwrap_or(0);
-                    self.queue.remove(oldest_pos);
+                    self.last_evicted = Some(self.queue.remove(oldest_pos));
                     self.dropped_count += 1;
                     OverflowDecision::AcceptNew
                 }
@@ -507,6 +521,22 @@ mod tests {
         assert_eq!(sched.dropped_count(), 1);
     }
 
+    #[test]
+    fn overflow_reports_evicted_action() {
+        let mut sched = ActionScheduler::with_max_queue_size(ActionScheduler::new(), 1);
+        let ctx = active_thunk_ctx("t1");
+
+        let first = action("A");
+        let first_id = first.id.clone();
+        sched.enqueue(first, "main".into(), &ctx);
+        assert!(sched.take_evicted().is_none());
+
+        sched.enqueue(action("B"), "main".into(), &ctx);
+        let evicted = sched.take_evicted().expect("A should have been evicted");
+        assert_eq!(evicted.action.id, first_id);
+        assert!(sched.take_evicted().is_none(), "take_evicted clears the slot");
+    }
+
     #[test]
     fn overflow_rejects_new_low_priority_when_no_droppable_exists() {
         let mut sched = ActionScheduler::with_max_queue_size(ActionScheduler::new(), 2);
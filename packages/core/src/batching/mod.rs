@@ -8,11 +8,19 @@
 //! intervals (e.g. every 16 ms) and [`ActionBatcher::take_batch`] when
 //! a flush is needed. The `BATCH_DISPATCH` / `BATCH_ACK` payload shapes
 //! are preserved for IPC compatibility with the TypeScript renderer.
+//!
+//! [`BatchingDispatcher`] wraps a batcher with a timer thread and a sink that
+//! applies each flushed batch, for hosts that dispatch many actions in quick
+//! succession and want one state update per window rather than per action.
 
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
 
+use crate::error::ZubridgeError;
 use crate::models::ZubridgeAction;
 use crate::action::{PRIORITY_IMMEDIATE, PRIORITY_NORMAL, PRIORITY_THUNK};
 
@@ -272,7 +280,7 @@ impl ActionBatcher {
             return;
         }
         let mut pending = std::mem::take(&mut self.pending_batch_items);
-        pending.extend(self.queue.drain(..));
+        pending.append(&mut self.queue);
         self.queue = pending;
         self.is_flushing = false;
         self.active_batch_id = None;
@@ -343,6 +351,200 @@ pub fn calculate_priority(action: &ZubridgeAction) -> i32 {
     PRIORITY_NORMAL
 }
 
+// ── BatchingDispatcher ────────────────────────────────────────────────────────
+
+/// Applies one flushed batch — typically as a single coalesced dispatch with
+/// one state broadcast — and reports each action's outcome.
+///
+/// Set the ack's batch-level `error` only when none of the batch's actions
+/// were applied: the batch is then requeued and retried on the next flush.
+pub type BatchSink = Box<dyn Fn(BatchPayload) -> BatchAckPayload + Send + Sync>;
+
+struct DispatcherShared {
+    batcher: Mutex<ActionBatcher>,
+    sink: BatchSink,
+}
+
+impl DispatcherShared {
+    fn batcher(&self) -> crate::Result<MutexGuard<'_, ActionBatcher>> {
+        self.batcher
+            .lock()
+            .map_err(|e| ZubridgeError::StateError(e.to_string()))
+    }
+
+    /// Hand `batch` to the sink, then settle it with the batcher. The batcher
+    /// lock is not held while the sink runs, so actions can keep arriving;
+    /// they wait for the next flush.
+    fn send(&self, batch: BatchPayload) -> crate::Result<()> {
+        let ack = (self.sink)(batch);
+        for result in ack.results.iter().filter(|r| !r.success) {
+            log::warn!(
+                "zubridge: batched action {} failed: {}",
+                result.action_id,
+                result.error.as_deref().unwrap_or("unknown error")
+            );
+        }
+        self.batcher()?.complete_batch(&ack);
+        Ok(())
+    }
+
+    fn tick(&self) -> crate::Result<bool> {
+        let batch = self.batcher()?.maybe_flush();
+        match batch {
+            Some(batch) => self.send(batch).map(|()| true),
+            None => Ok(false),
+        }
+    }
+
+    fn flush(&self) -> crate::Result<()> {
+        loop {
+            let batch = {
+                let mut batcher = self.batcher()?;
+                if batcher.is_flushing || batcher.queue.is_empty() {
+                    return Ok(());
+                }
+                batcher.take_batch_internal()
+            };
+            self.send(batch)?;
+        }
+    }
+}
+
+/// Coalesces actions dispatched in quick succession into batches handed to a
+/// [`BatchSink`].
+///
+/// Actions wait for the next window; one whose priority reaches
+/// [`BatchingConfig::priority_flush_threshold`] (e.g. an `immediate` action)
+/// flushes the queue straight away, together with everything queued before
+/// it. Only one batch is in flight at a time. Dropping the dispatcher stops
+/// its timer and flushes whatever is still queued.
+pub struct BatchingDispatcher {
+    shared: Arc<DispatcherShared>,
+    /// Dropping the sender wakes and stops the timer thread.
+    stop: Option<mpsc::Sender<()>>,
+    timer: Option<JoinHandle<()>>,
+}
+
+impl BatchingDispatcher {
+    /// Create a dispatcher with a timer thread that flushes every
+    /// `config.window_ms`.
+    pub fn spawn<F>(config: BatchingConfig, sink: F) -> Self
+    where
+        F: Fn(BatchPayload) -> BatchAckPayload + Send + Sync + 'static,
+    {
+        let window = Duration::from_millis(config.window_ms.max(1));
+        let mut dispatcher = Self::manual(config, sink);
+        let shared = dispatcher.shared.clone();
+        let (stop, stopped) = mpsc::channel::<()>();
+        let timer = std::thread::Builder::new()
+            .name("zubridge-batcher".to_string())
+            .spawn(move || {
+                // Runs until the dispatcher drops `stop`.
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(window) {
+                    if let Err(err) = shared.tick() {
+                        log::warn!("zubridge: batch flush failed: {err}");
+                    }
+                }
+            });
+        match timer {
+            Ok(timer) => {
+                dispatcher.stop = Some(stop);
+                dispatcher.timer = Some(timer);
+            }
+            Err(err) => log::error!("zubridge: failed to start batch timer: {err}"),
+        }
+        dispatcher
+    }
+
+    /// Create a dispatcher without a timer. The embedding runtime must call
+    /// [`tick`](Self::tick) every `window_ms`.
+    pub fn manual<F>(config: BatchingConfig, sink: F) -> Self
+    where
+        F: Fn(BatchPayload) -> BatchAckPayload + Send + Sync + 'static,
+    {
+        Self {
+            shared: Arc::new(DispatcherShared {
+                batcher: Mutex::new(ActionBatcher::new(config)),
+                sink: Box::new(sink),
+            }),
+            stop: None,
+            timer: None,
+        }
+    }
+
+    /// Queue `action` at the priority given by [`calculate_priority`].
+    /// Returns the action's id, assigning one if it has none.
+    pub fn dispatch(&self, action: ZubridgeAction) -> crate::Result<String> {
+        let priority = calculate_priority(&action);
+        self.dispatch_with_priority(action, priority)
+    }
+
+    /// Queue `action` at `priority`, flushing straight away if it reaches the
+    /// configured threshold. Fails with [`ZubridgeError::QueueOverflow`] once
+    /// the batcher's hard queue limit is reached.
+    pub fn dispatch_with_priority(
+        &self,
+        mut action: ZubridgeAction,
+        priority: i32,
+    ) -> crate::Result<String> {
+        let action_id = action
+            .id
+            .get_or_insert_with(|| uuid::Uuid::new_v4().to_string())
+            .clone();
+        let parent_id = action.thunk_parent_id.clone();
+        let batch = {
+            let mut batcher = self.shared.batcher()?;
+            batcher.enqueue(action, priority, parent_id).map_err(|_| {
+                ZubridgeError::QueueOverflow {
+                    queue_size: batcher.queue.len(),
+                    max_size: batcher.hard_queue_limit,
+                }
+            })?
+        };
+        if let Some(batch) = batch {
+            self.shared.send(batch)?;
+        }
+        Ok(action_id)
+    }
+
+    /// Flush the queue if the window has elapsed. Returns whether a batch
+    /// was sent. Called by the timer thread; call it directly only on a
+    /// dispatcher built with [`manual`](Self::manual).
+    pub fn tick(&self) -> crate::Result<bool> {
+        self.shared.tick()
+    }
+
+    /// Send everything queued now, regardless of the window, unless a batch
+    /// is already in flight.
+    pub fn flush(&self) -> crate::Result<()> {
+        self.shared.flush()
+    }
+
+    pub fn stats(&self) -> crate::Result<BatchStats> {
+        Ok(self.shared.batcher()?.stats())
+    }
+}
+
+impl Drop for BatchingDispatcher {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(timer) = self.timer.take() {
+            let _ = timer.join();
+        }
+        if let Err(err) = self.shared.flush() {
+            log::warn!("zubridge: final batch flush failed: {err}");
+        }
+    }
+}
+
+impl std::fmt::Debug for BatchingDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BatchingDispatcher")
+            .field("timer", &self.timer.is_some())
+            .finish()
+    }
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -435,12 +637,9 @@ mod tests {
         // Now is_flushing=true so auto-flush won't fire; fill queue normally.
         let mut rejected = 0usize;
         for i in 0..=hard_limit {
-            match b.enqueue(action(&format!("X{i}")), PRIORITY_THUNK, None) {
-                Err(_) => {
-                    rejected += 1;
-                    break;
-                }
-                Ok(_) => {}
+            if b.enqueue(action(&format!("X{i}")), PRIORITY_THUNK, None).is_err() {
+                rejected += 1;
+                break;
             }
         }
         assert_eq!(rejected, 1, "should reject exactly once at hard limit");
@@ -521,4 +720,104 @@ mod tests {
         let result = b.enqueue(action("B"), PRIORITY_THUNK, None);
         assert!(result.is_err());
     }
+
+    type Sent = Arc<Mutex<Vec<Vec<String>>>>;
+
+    /// A dispatcher whose sink records each batch's action types and acks
+    /// every action.
+    fn recording(config: BatchingConfig) -> (BatchingDispatcher, Sent) {
+        let sent: Sent = Arc::default();
+        let log = sent.clone();
+        let dispatcher = BatchingDispatcher::manual(config, move |batch: BatchPayload| {
+            log.lock().unwrap().push(
+                batch
+                    .actions
+                    .iter()
+                    .map(|entry| entry.action.action_type.clone())
+                    .collect(),
+            );
+            BatchAckPayload {
+                batch_id: batch.batch_id,
+                results: batch
+                    .actions
+                    .iter()
+                    .map(|entry| BatchActionResult {
+                        action_id: entry.id.clone(),
+                        success: true,
+                        error: None,
+                    })
+                    .collect(),
+                error: None,
+            }
+        });
+        (dispatcher, sent)
+    }
+
+    #[test]
+    fn dispatcher_coalesces_actions_until_the_window_flushes() {
+        let (dispatcher, sent) = recording(BatchingConfig {
+            window_ms: 0,
+            ..BatchingConfig::default()
+        });
+        for t in ["A", "B", "C"] {
+            dispatcher.dispatch(action(t)).unwrap();
+        }
+        assert!(sent.lock().unwrap().is_empty());
+        assert!(dispatcher.tick().unwrap());
+        assert!(!dispatcher.tick().unwrap());
+        assert_eq!(*sent.lock().unwrap(), vec![vec!["A", "B", "C"]]);
+    }
+
+    #[test]
+    fn dispatcher_flushes_immediately_at_the_priority_threshold() {
+        let (dispatcher, sent) = recording(BatchingConfig::default());
+        dispatcher.dispatch(action("A")).unwrap();
+        dispatcher
+            .dispatch(ZubridgeAction {
+                immediate: Some(true),
+                ..action("URGENT")
+            })
+            .unwrap();
+        assert_eq!(*sent.lock().unwrap(), vec![vec!["URGENT", "A"]]);
+    }
+
+    #[test]
+    fn dispatcher_flushes_remaining_actions_on_drop() {
+        let (dispatcher, sent) = recording(BatchingConfig {
+            window_ms: 60_000,
+            ..BatchingConfig::default()
+        });
+        dispatcher.dispatch(action("A")).unwrap();
+        drop(dispatcher);
+        assert_eq!(*sent.lock().unwrap(), vec![vec!["A"]]);
+    }
+
+    #[test]
+    fn spawned_dispatcher_flushes_on_its_timer() {
+        let sent: Sent = Arc::default();
+        let log = sent.clone();
+        let (done, flushed) = mpsc::channel();
+        let done = Mutex::new(done);
+        let dispatcher = BatchingDispatcher::spawn(
+            BatchingConfig {
+                window_ms: 5,
+                ..BatchingConfig::default()
+            },
+            move |batch: BatchPayload| {
+                log.lock()
+                    .unwrap()
+                    .push(vec![batch.actions.len().to_string()]);
+                let _ = done.lock().unwrap().send(());
+                BatchAckPayload {
+                    batch_id: batch.batch_id,
+                    results: Vec::new(),
+                    error: None,
+                }
+            },
+        );
+        dispatcher.dispatch(action("A")).unwrap();
+        dispatcher.dispatch(action("B")).unwrap();
+        flushed.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(*sent.lock().unwrap(), vec![vec!["2"]]);
+    }
 }
@@ -0,0 +1,634 @@
+//! Runtime-agnostic state-update broadcasting.
+//!
+//! A [`Broadcaster`] turns each new state into a [`StateUpdatePayload`] per
+//! target and hands it to an [`EventEmitter`]. It filters the state to each
+//! target's subscription, diffs it against the last state the target was
+//! sent, numbers updates per target, applies the update throttle and
+//! backpressure, and tracks acknowledgements. Platform wrappers supply the
+//! emitter and the targets allowed to read state.
+//!
+//! A target's baseline only advances when the emitter reports the update
+//! delivered, so after a failed emit its next update is diffed against the
+//! last state it actually received. Failures are counted per target (see
+//! [`Broadcaster::emit_failures`]).
+//!
+//! Wrappers should hold the broadcaster behind one lock from reading a new
+//! state until it has been broadcast. Otherwise two concurrent dispatches
+//! can broadcast out of order, and the older state becomes every target's
+//! baseline.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::deltas::{DeltaCalculator, DeltaResult};
+use crate::emit::EventEmitter;
+use crate::error::{Result, ZubridgeError};
+use crate::models::{JsonValue, StateUpdatePayload, UpdateSource};
+use crate::snapshot::Snapshot;
+use crate::subscription::{StatePath, SubscriptionManager};
+use crate::throttle::{ThrottleDecision, UpdateThrottle};
+use crate::thunk::{Admission, SlowConsumerEvent, StateUpdateTracker};
+
+/// Per-target monotonic sequence counter for state-update events.
+#[derive(Debug, Default)]
+pub struct SequenceTracker {
+    by_label: HashMap<String, u64>,
+}
+
+impl SequenceTracker {
+    pub fn next(&mut self, label: &str) -> u64 {
+        let seq = self.peek(label);
+        self.advance(label, seq);
+        seq
+    }
+
+    /// The sequence number the next update to `label` will carry, without
+    /// consuming it.
+    pub fn peek(&self, label: &str) -> u64 {
+        self.by_label.get(label).copied().unwrap_or(0) + 1
+    }
+
+    /// Records `seq` as the last sequence number delivered to `label`.
+    pub fn advance(&mut self, label: &str, seq: u64) {
+        self.by_label.insert(label.to_string(), seq);
+    }
+
+    pub fn forget(&mut self, label: &str) {
+        self.by_label.remove(label);
+    }
+}
+
+/// Called with every backpressure transition.
+pub type SlowConsumerHandler = Box<dyn Fn(&SlowConsumerEvent) + Send + Sync>;
+
+/// Failed emits to one target.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EmitFailures {
+    /// Failures since the last successful emit.
+    pub consecutive: u64,
+    /// Failures since the target was first seen.
+    pub total: u64,
+    /// Message of the most recent failure.
+    pub last_error: Option<String>,
+}
+
+// ── Broadcaster ───────────────────────────────────────────────────────────────
+
+/// Sends state updates to targets through `E`. See the module docs.
+pub struct Broadcaster<E: EventEmitter> {
+    emitter: E,
+    event_name: String,
+    deltas: DeltaCalculator,
+    updates: StateUpdateTracker,
+    sequences: SequenceTracker,
+    throttle: Option<UpdateThrottle>,
+    on_slow_consumer: Option<SlowConsumerHandler>,
+    failures: HashMap<String, EmitFailures>,
+}
+
+impl<E: EventEmitter> Broadcaster<E> {
+    /// A broadcaster emitting state updates as `event_name`.
+    pub fn new(emitter: E, event_name: impl Into<String>) -> Self {
+        Self {
+            emitter,
+            event_name: event_name.into(),
+            deltas: DeltaCalculator::new(),
+            updates: StateUpdateTracker::new(),
+            sequences: SequenceTracker::default(),
+            throttle: None,
+            on_slow_consumer: None,
+            failures: HashMap::new(),
+        }
+    }
+
+    pub fn emitter(&self) -> &E {
+        &self.emitter
+    }
+
+    pub fn set_emitter(&mut self, emitter: E) {
+        self.emitter = emitter;
+    }
+
+    pub fn event_name(&self) -> &str {
+        &self.event_name
+    }
+
+    pub fn set_event_name(&mut self, event_name: impl Into<String>) {
+        self.event_name = event_name.into();
+    }
+
+    pub fn deltas(&self) -> &DeltaCalculator {
+        &self.deltas
+    }
+
+    pub fn deltas_mut(&mut self) -> &mut DeltaCalculator {
+        &mut self.deltas
+    }
+
+    pub fn updates(&self) -> &StateUpdateTracker {
+        &self.updates
+    }
+
+    pub fn updates_mut(&mut self) -> &mut StateUpdateTracker {
+        &mut self.updates
+    }
+
+    /// Emit failures per target that has had any.
+    pub fn emit_failures(&self) -> &HashMap<String, EmitFailures> {
+        &self.failures
+    }
+
+    /// Targets whose last `threshold` or more emits all failed, sorted.
+    pub fn unreachable_targets(&self, threshold: u64) -> Vec<String> {
+        let mut labels: Vec<String> = self
+            .failures
+            .iter()
+            .filter(|(_, failures)| failures.consecutive >= threshold)
+            .map(|(label, _)| label.clone())
+            .collect();
+        labels.sort();
+        labels
+    }
+
+    /// Throttle each target to one update per `window`, or stop throttling.
+    /// Deferred updates are sent by [`flush_throttled`](Self::flush_throttled).
+    pub fn set_throttle(&mut self, window: Option<Duration>) {
+        self.throttle = window.map(UpdateThrottle::new);
+    }
+
+    /// Report backpressure transitions to `handler`, e.g. to notify the host.
+    pub fn on_slow_consumer(
+        &mut self,
+        handler: impl Fn(&SlowConsumerEvent) + Send + Sync + 'static,
+    ) {
+        self.on_slow_consumer = Some(Box::new(handler));
+    }
+
+    /// Send each of `targets` its update for `state`, if its view changed.
+    ///
+    /// `changed_keys` are the top-level keys changed since the previous
+    /// broadcast, when known. Only those keys are compared, and targets
+    /// subscribed to none of them are skipped. `None` diffs the full state
+    /// for every target.
+    ///
+    /// A failed emit doesn't stop the others; the first failure is returned.
+    pub fn broadcast(
+        &mut self,
+        subscriptions: &SubscriptionManager,
+        targets: &[String],
+        state: JsonValue,
+        changed_keys: Option<&[String]>,
+        source: Option<UpdateSource>,
+    ) -> Result<()> {
+        // Share unchanged top-level values with the previous broadcast, so
+        // baselines holding them are compared by pointer below.
+        let snapshot = match changed_keys {
+            Some(keys) => self.deltas.share_changed(state, keys),
+            None => self.deltas.share(state),
+        };
+        // Targets with identical subscriptions get the same filtered
+        // snapshot, and so record one shared baseline.
+        let mut scoped_by_keys: HashMap<Vec<String>, Snapshot> = HashMap::new();
+        let mut result = Ok(());
+
+        for label in targets {
+            let scoped = scoped_by_keys
+                .entry(subscriptions.keys_for(label))
+                .or_insert_with(|| subscriptions.filter_snapshot(label, &snapshot))
+                .clone();
+            let touched = changed_keys.map_or(true, |keys| subscriptions.intersects(label, keys));
+
+            // A change outside the target's subscription leaves an
+            // up-to-date replica as it is. A target that missed an earlier
+            // update still gets diffed so it catches up.
+            if !touched && self.deltas.is_current(label) {
+                self.deltas.confirm(label);
+                continue;
+            }
+
+            // A throttled target inside its window waits for the flush,
+            // which diffs against the same baseline and so merges every
+            // deferred change into one update.
+            let offered = source.clone().filter(|_| touched);
+            let source = match self.offer_throttled(label, offered) {
+                Some(ThrottleDecision::Deferred) => continue,
+                Some(ThrottleDecision::EmitNow(merged)) => merged,
+                None => source.clone(),
+            };
+
+            let nested_paths = subscriptions.nested_paths_for(label, &scoped);
+            let emitted =
+                self.emit_update(label, scoped, nested_paths.as_deref(), changed_keys, source);
+            result = result.and(emitted);
+        }
+        result
+    }
+
+    /// Send the updates the throttle deferred for every target whose window
+    /// has elapsed, each diffed against the latest broadcast state. Targets
+    /// not in `targets` are skipped. A no-op when throttling is off. Returns
+    /// the first failed emit, as [`broadcast`](Self::broadcast) does.
+    pub fn flush_throttled(
+        &mut self,
+        subscriptions: &SubscriptionManager,
+        targets: &[String],
+    ) -> Result<()> {
+        let due = match self.throttle.as_mut() {
+            Some(throttle) => throttle.due(Instant::now()),
+            None => return Ok(()),
+        };
+        let snapshot = self.deltas.latest().clone();
+        let mut result = Ok(());
+        for (label, source) in due {
+            if !targets.contains(&label) {
+                continue;
+            }
+            let scoped = subscriptions.filter_snapshot(&label, &snapshot);
+            let nested_paths = subscriptions.nested_paths_for(&label, &scoped);
+            let emitted = self.emit_update(&label, scoped, nested_paths.as_deref(), None, source);
+            result = result.and(emitted);
+        }
+        result
+    }
+
+    /// True if the throttle is holding back any update.
+    pub fn has_throttled(&self) -> bool {
+        self.throttle
+            .as_ref()
+            .is_some_and(UpdateThrottle::has_pending)
+    }
+
+    /// Forget `label`'s baseline and send it the latest broadcast state in
+    /// full.
+    pub fn resync(&mut self, subscriptions: &SubscriptionManager, label: &str) -> Result<()> {
+        self.deltas.forget(label);
+        let scoped = subscriptions.filter_snapshot(label, self.deltas.latest());
+        let nested_paths = subscriptions.nested_paths_for(label, &scoped);
+        self.emit_update(label, scoped, nested_paths.as_deref(), None, None)
+    }
+
+    /// Record that `label` applied update `update_id`. A target this
+    /// unstalls is resynced.
+    pub fn ack(
+        &mut self,
+        subscriptions: &SubscriptionManager,
+        label: &str,
+        update_id: &str,
+    ) -> Result<()> {
+        self.updates.ack(label, update_id);
+        match self.updates.resume(label) {
+            Some(event) => {
+                self.report_slow_consumer(&event);
+                self.resync(subscriptions, label)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Treat updates unacked for longer than `timeout` as lost, and resync
+    /// every target that lost one. Returns the first failed resync.
+    pub fn sweep_expired_acks(
+        &mut self,
+        subscriptions: &SubscriptionManager,
+        timeout: Duration,
+    ) -> Result<()> {
+        let expired = self.updates.sweep_expired(Instant::now(), timeout);
+        for label in &expired {
+            if let Some(event) = self.updates.resume(label) {
+                self.report_slow_consumer(&event);
+            }
+        }
+        let mut result = Ok(());
+        for label in expired {
+            log::debug!("zubridge: state update to {label} unacked after {timeout:?}; resyncing");
+            result = result.and(self.resync(subscriptions, &label));
+        }
+        result
+    }
+
+    /// Forget `label`'s pending acks and baseline, so its next update is the
+    /// full state. For targets that just fetched the state themselves.
+    pub fn reset_label(&mut self, label: &str) {
+        self.updates.drop_label(label);
+        self.deltas.forget(label);
+    }
+
+    /// Drop everything tracked for a target that has gone away.
+    pub fn forget(&mut self, label: &str) {
+        self.deltas.forget(label);
+        self.updates.forget_label(label);
+        self.sequences.forget(label);
+        self.failures.remove(label);
+        if let Some(throttle) = self.throttle.as_mut() {
+            throttle.forget(label);
+        }
+    }
+
+    /// Offer an update for `label` to the throttle. `None` when throttling
+    /// is off.
+    fn offer_throttled(
+        &mut self,
+        label: &str,
+        source: Option<UpdateSource>,
+    ) -> Option<ThrottleDecision> {
+        self.throttle
+            .as_mut()
+            .map(|throttle| throttle.offer(label, source, Instant::now()))
+    }
+
+    /// Diff `scoped` against `label`'s baseline and emit the result, if
+    /// anything changed. `nested_paths` and `changed_keys` are as in
+    /// [`broadcast`](Self::broadcast).
+    fn emit_update(
+        &mut self,
+        label: &str,
+        scoped: Snapshot,
+        nested_paths: Option<&[StatePath]>,
+        changed_keys: Option<&[String]>,
+        source: Option<UpdateSource>,
+    ) -> Result<()> {
+        // A target with too many unacked updates gets nothing until it acks
+        // again, then resyncs with the full state.
+        match self.updates.admit(label) {
+            Admission::Send => {}
+            Admission::Hold => return Ok(()),
+            Admission::Stall(event) => {
+                log::debug!("zubridge: {label} stalled on unacked state updates");
+                self.report_slow_consumer(&event);
+                return Ok(());
+            }
+        }
+
+        // Nested subscriptions diff per subscribed path, so the delta carries
+        // `settings.theme` rather than all of `settings`. With known changed
+        // keys, a baseline that matched the previous state can only differ
+        // in those keys.
+        let outcome = match (nested_paths, changed_keys) {
+            (Some(paths), _) => self.deltas.compute_paths(label, &scoped, paths),
+            (None, Some(keys)) if self.deltas.is_current(label) => {
+                self.deltas.compute_keys(label, &scoped, keys)
+            }
+            (None, _) => self.deltas.compute(label, &scoped),
+        };
+        let (delta, full_state) = match outcome {
+            DeltaResult::Unchanged => {
+                self.deltas.confirm(label);
+                return Ok(());
+            }
+            DeltaResult::FullState => (None, Some(scoped.to_value())),
+            DeltaResult::Delta(delta) => (Some(delta), None),
+        };
+
+        let payload = StateUpdatePayload {
+            seq: self.sequences.peek(label),
+            update_id: uuid::Uuid::new_v4().to_string(),
+            delta,
+            full_state,
+            source,
+        };
+        let value = serde_json::to_value(&payload)
+            .map_err(|e| ZubridgeError::Serialization(e.to_string()))?;
+
+        // Record the new baseline and sequence number only after the emit
+        // succeeded. Recording them first would advance the baseline past a
+        // state the target never received, and every later delta would
+        // silently diverge from its replica; a consumed seq would show up
+        // as a gap the target never closes.
+        if let Err(err) = self.emitter.emit(label, &self.event_name, &value) {
+            let failures = self.failures.entry(label.to_string()).or_default();
+            failures.consecutive += 1;
+            failures.total += 1;
+            failures.last_error = Some(err.to_string());
+            return Err(err);
+        }
+        if let Some(failures) = self.failures.get_mut(label) {
+            failures.consecutive = 0;
+        }
+        self.sequences.advance(label, payload.seq);
+        self.deltas.record(label, scoped);
+        self.updates.record_pending(label, &payload.update_id);
+        Ok(())
+    }
+
+    fn report_slow_consumer(&self, event: &SlowConsumerEvent) {
+        if let Some(handler) = &self.on_slow_consumer {
+            handler(event);
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    /// Records every emitted event in memory.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        sent: Arc<Mutex<Vec<(String, JsonValue)>>>,
+        /// Targets whose emits fail.
+        unreachable: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl EventEmitter for Recorder {
+        fn emit(&self, target: &str, _event: &str, payload: &JsonValue) -> Result<()> {
+            if self.unreachable.lock().unwrap().iter().any(|t| t == target) {
+                return Err(ZubridgeError::EmitError(format!("{target} is gone")));
+            }
+            self.sent
+                .lock()
+                .unwrap()
+                .push((target.to_string(), payload.clone()));
+            Ok(())
+        }
+    }
+
+    impl Recorder {
+        fn take(&self) -> Vec<(String, JsonValue)> {
+            std::mem::take(&mut *self.sent.lock().unwrap())
+        }
+
+        fn set_unreachable(&self, targets: &[&str]) {
+            *self.unreachable.lock().unwrap() = targets.iter().map(|t| t.to_string()).collect();
+        }
+    }
+
+    fn broadcaster() -> (Broadcaster<Recorder>, Recorder) {
+        let recorder = Recorder::default();
+        (Broadcaster::new(recorder.clone(), "state-update"), recorder)
+    }
+
+    fn targets(labels: &[&str]) -> Vec<String> {
+        labels.iter().map(|label| label.to_string()).collect()
+    }
+
+    #[test]
+    fn sends_full_state_then_filtered_deltas() {
+        let (mut broadcaster, recorder) = broadcaster();
+        let mut subs = SubscriptionManager::new();
+        subs.subscribe("settings", &["theme".to_string()]);
+        let targets = targets(&["main", "settings"]);
+
+        broadcaster
+            .broadcast(
+                &subs,
+                &targets,
+                json!({ "count": 0, "theme": "dark" }),
+                None,
+                None,
+            )
+            .unwrap();
+        let sent = recorder.take();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(
+            sent[0].1["full_state"],
+            json!({ "count": 0, "theme": "dark" })
+        );
+        assert_eq!(sent[1].1["full_state"], json!({ "theme": "dark" }));
+
+        let keys = vec!["count".to_string()];
+        broadcaster
+            .broadcast(
+                &subs,
+                &targets,
+                json!({ "count": 1, "theme": "dark" }),
+                Some(&keys),
+                None,
+            )
+            .unwrap();
+        let sent = recorder.take();
+        assert_eq!(sent.len(), 1, "settings is not subscribed to count");
+        assert_eq!(sent[0].0, "main");
+        assert_eq!(sent[0].1["seq"], json!(2));
+        assert_eq!(sent[0].1["delta"]["changed"], json!({ "count": 1 }));
+    }
+
+    #[test]
+    fn stalled_targets_resume_with_a_resync_once_they_ack() {
+        let (mut broadcaster, recorder) = broadcaster();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let reported = events.clone();
+        broadcaster.on_slow_consumer(move |event| reported.lock().unwrap().push(event.clone()));
+        broadcaster.updates_mut().set_max_in_flight(Some(1));
+        let subs = SubscriptionManager::new();
+        let targets = targets(&["main"]);
+
+        broadcaster
+            .broadcast(&subs, &targets, json!({ "count": 0 }), None, None)
+            .unwrap();
+        broadcaster
+            .broadcast(&subs, &targets, json!({ "count": 1 }), None, None)
+            .unwrap();
+        broadcaster
+            .broadcast(&subs, &targets, json!({ "count": 2 }), None, None)
+            .unwrap();
+        let sent = recorder.take();
+        assert_eq!(sent.len(), 1);
+        assert!(broadcaster.updates().is_stalled("main"));
+
+        let update_id = sent[0].1["update_id"].as_str().unwrap().to_string();
+        broadcaster.ack(&subs, "main", &update_id).unwrap();
+        let sent = recorder.take();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].1["full_state"], json!({ "count": 2 }));
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                SlowConsumerEvent::Stalled {
+                    label: "main".to_string(),
+                    in_flight: 1,
+                },
+                SlowConsumerEvent::Resumed {
+                    label: "main".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn throttled_updates_are_merged_on_flush() {
+        let (mut broadcaster, recorder) = broadcaster();
+        broadcaster.set_throttle(Some(Duration::from_millis(100)));
+        let subs = SubscriptionManager::new();
+        let targets = targets(&["main"]);
+
+        broadcaster
+            .broadcast(&subs, &targets, json!({ "a": 0, "b": 0 }), None, None)
+            .unwrap();
+        broadcaster
+            .broadcast(&subs, &targets, json!({ "a": 1, "b": 0 }), None, None)
+            .unwrap();
+        broadcaster
+            .broadcast(&subs, &targets, json!({ "a": 1, "b": 1 }), None, None)
+            .unwrap();
+        assert_eq!(
+            recorder.take().len(),
+            1,
+            "the first update opens the window"
+        );
+        assert!(broadcaster.has_throttled());
+
+        broadcaster.flush_throttled(&subs, &targets).unwrap();
+        assert!(recorder.take().is_empty(), "the window is still open");
+
+        std::thread::sleep(Duration::from_millis(150));
+        broadcaster.flush_throttled(&subs, &targets).unwrap();
+        let sent = recorder.take();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].1["seq"], json!(2));
+        assert_eq!(sent[0].1["delta"]["changed"], json!({ "a": 1, "b": 1 }));
+        assert!(!broadcaster.has_throttled());
+    }
+
+    #[test]
+    fn failed_emits_keep_the_baseline_and_are_counted() {
+        let (mut broadcaster, recorder) = broadcaster();
+        let subs = SubscriptionManager::new();
+        let targets = targets(&["main", "other"]);
+        recorder.set_unreachable(&["other"]);
+
+        for count in 0..2 {
+            let result =
+                broadcaster.broadcast(&subs, &targets, json!({ "count": count }), None, None);
+            assert!(matches!(result, Err(ZubridgeError::EmitError(_))));
+        }
+        assert_eq!(recorder.take().len(), 2, "main is still updated");
+        assert!(broadcaster.deltas().baseline("other").is_none());
+        assert_eq!(broadcaster.updates().pending_count("other"), 0);
+        assert_eq!(
+            broadcaster.unreachable_targets(2),
+            vec!["other".to_string()]
+        );
+
+        recorder.set_unreachable(&[]);
+        broadcaster
+            .broadcast(&subs, &targets, json!({ "count": 2 }), None, None)
+            .unwrap();
+        let sent = recorder.take();
+        assert_eq!(sent[0].1["delta"]["changed"], json!({ "count": 2 }));
+        assert_eq!(sent[1].0, "other");
+        assert_eq!(sent[1].1["full_state"], json!({ "count": 2 }));
+        assert_eq!(sent[1].1["seq"], json!(1), "failed emits consume no seq");
+        assert_eq!(
+            broadcaster.emit_failures()["other"],
+            EmitFailures {
+                consecutive: 0,
+                total: 2,
+                last_error: Some("emit error: other is gone".to_string()),
+            }
+        );
+        assert!(broadcaster.unreachable_targets(1).is_empty());
+
+        broadcaster
+            .broadcast(&subs, &targets, json!({ "count": 3 }), None, None)
+            .unwrap();
+        let sent = recorder.take();
+        assert_eq!(sent[0].1["seq"], json!(4));
+        assert_eq!(sent[1].1["seq"], json!(2));
+    }
+}
@@ -1,15 +1,38 @@
 use std::collections::HashMap;
 
-use crate::models::{JsonValue, StateDelta};
+use serde::Serialize;
+
+use crate::models::{DeltaMode, JsonValue, StateDelta};
+use crate::snapshot::Snapshot;
+use crate::subscription::StatePath;
 
 /// Tracks the last state sent to each webview and computes deltas.
 ///
 /// State is assumed to be a JSON object at the top level (the convention used
 /// by Zubridge state managers). For non-object states, the delta calculator
 /// always emits a full-state payload.
+///
+/// Baselines are stored as [`Snapshot`]s. When the states recorded for
+/// several webviews come from the same snapshot (see [`share`](Self::share)
+/// and [`SubscriptionManager::filter_snapshot`](crate::subscription::SubscriptionManager::filter_snapshot)),
+/// they share their values rather than each holding a copy, and diffing a
+/// key whose value is still the shared one is a pointer comparison.
 #[derive(Debug, Default)]
 pub struct DeltaCalculator {
-    last_by_label: HashMap<String, JsonValue>,
+    last_by_label: HashMap<String, Baseline>,
+    /// The most recent state passed to [`share`](Self::share).
+    latest: Snapshot,
+    /// Number of [`share`](Self::share) calls so far.
+    generation: u64,
+    mode: DeltaMode,
+}
+
+/// The last state sent to a webview, and the [`share`](DeltaCalculator::share)
+/// generation it was last known to match.
+#[derive(Debug)]
+struct Baseline {
+    state: Snapshot,
+    generation: u64,
 }
 
 /// Outcome of a delta computation. Three cases instead of an `Option<StateDelta>`
@@ -29,48 +52,267 @@ pub enum DeltaResult {
     Delta(StateDelta),
 }
 
+/// A state [`DeltaCalculator`] can diff against a baseline: a plain
+/// [`JsonValue`] or a [`Snapshot`].
+pub trait DiffState: Serialize {
+    /// Top-level member names, or `None` for a non-object state.
+    fn member_names(&self) -> Option<Vec<&String>>;
+    /// The top-level member `key`.
+    fn member(&self, key: &str) -> Option<&JsonValue>;
+    /// The value at `path`.
+    fn at(&self, path: &StatePath) -> Option<&JsonValue>;
+}
+
+impl DiffState for JsonValue {
+    fn member_names(&self) -> Option<Vec<&String>> {
+        Some(self.as_object()?.keys().collect())
+    }
+
+    fn member(&self, key: &str) -> Option<&JsonValue> {
+        self.as_object()?.get(key)
+    }
+
+    fn at(&self, path: &StatePath) -> Option<&JsonValue> {
+        path.get(self)
+    }
+}
+
+impl DiffState for Snapshot {
+    fn member_names(&self) -> Option<Vec<&String>> {
+        Some(self.entries()?.keys().collect())
+    }
+
+    fn member(&self, key: &str) -> Option<&JsonValue> {
+        self.get(key).map(|value| &**value)
+    }
+
+    fn at(&self, path: &StatePath) -> Option<&JsonValue> {
+        self.get_path(path)
+    }
+}
+
 impl DeltaCalculator {
     pub fn new() -> Self {
         Self::default()
     }
 
+    pub fn with_mode(mode: DeltaMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    pub fn mode(&self) -> DeltaMode {
+        self.mode
+    }
+
+    /// Switch the encoding used by subsequent computations. Existing
+    /// baselines stay valid: both modes diff against the same recorded state.
+    pub fn set_mode(&mut self, mode: DeltaMode) {
+        self.mode = mode;
+    }
+
+    /// Wrap a new full state as a [`Snapshot`] sharing every unchanged
+    /// top-level value with the previous one, so baselines recorded from
+    /// either can be compared by pointer. Each top-level key is compared
+    /// once here rather than once per webview.
+    pub fn share(&mut self, state: JsonValue) -> Snapshot {
+        self.generation += 1;
+        self.latest = Snapshot::share(state, &self.latest);
+        self.latest.clone()
+    }
+
+    /// The most recently shared state.
+    pub fn latest(&self) -> &Snapshot {
+        &self.latest
+    }
+
+    /// Like [`share`](Self::share), for a state that differs from the
+    /// previously shared one only in `changed` (e.g. as reported by
+    /// [`TryStateManager::changed_keys`](crate::models::TryStateManager::changed_keys)).
+    /// Other keys reuse the previous values without being compared.
+    pub fn share_changed(&mut self, state: JsonValue, changed: &[String]) -> Snapshot {
+        self.generation += 1;
+        self.latest = Snapshot::share_except(state, &self.latest, changed);
+        self.latest.clone()
+    }
+
+    /// True if `label`'s baseline matched the state of the previous
+    /// [`share`](Self::share) — it was recorded or [`confirm`](Self::confirm)ed
+    /// since — so a change the webview can't see leaves it up to date.
+    /// False for a label with no baseline, or one that missed an update
+    /// (e.g. because its emit failed).
+    pub fn is_current(&self, label: &str) -> bool {
+        self.last_by_label
+            .get(label)
+            .is_some_and(|baseline| baseline.generation + 1 >= self.generation)
+    }
+
+    /// Mark `label`'s baseline as matching the latest shared state without
+    /// replacing it — for a webview whose scoped state was
+    /// [`Unchanged`](DeltaResult::Unchanged) or untouched by the change.
+    pub fn confirm(&mut self, label: &str) {
+        if let Some(baseline) = self.last_by_label.get_mut(label) {
+            baseline.generation = self.generation;
+        }
+    }
+
     /// Compute the delta for `label`. See [`DeltaResult`] for the three cases.
-    pub fn compute(&self, label: &str, new_state: &JsonValue) -> DeltaResult {
-        let Some(prev) = self.last_by_label.get(label) else {
+    pub fn compute<S: DiffState + ?Sized>(&self, label: &str, new_state: &S) -> DeltaResult {
+        let Some(prev) = self.baseline(label) else {
+            return DeltaResult::FullState;
+        };
+        let (Some(prev_keys), Some(next_keys)) = (prev.member_names(), new_state.member_names())
+        else {
+            return DeltaResult::FullState;
+        };
+
+        let mut delta = StateDelta::default();
+        for key in next_keys {
+            self.diff_entry(
+                key.clone(),
+                prev.member(key),
+                new_state.member(key),
+                &mut delta,
+            );
+        }
+        for key in prev_keys {
+            if new_state.member(key).is_none() {
+                delta.removed.push(key.clone());
+            }
+        }
+        self.finish(delta)
+    }
+
+    /// Compute the delta for `label` when only `keys` can have changed since
+    /// the baseline — e.g. the set reported by
+    /// [`SliceStateManager::last_changed_keys`](crate::slice::SliceStateManager::last_changed_keys).
+    ///
+    /// Top-level keys outside `keys` are assumed equal to the baseline and are
+    /// neither compared nor included in the delta. Keys absent from both the
+    /// baseline and `new_state` (e.g. filtered out by the webview's
+    /// subscription) are ignored.
+    pub fn compute_keys<S: DiffState + ?Sized>(
+        &self,
+        label: &str,
+        new_state: &S,
+        keys: &[String],
+    ) -> DeltaResult {
+        let Some(prev) = self.baseline(label) else {
             return DeltaResult::FullState;
         };
-        let (JsonValue::Object(prev_map), JsonValue::Object(next_map)) = (prev, new_state) else {
+        if prev.member_names().is_none() || new_state.member_names().is_none() {
+            return DeltaResult::FullState;
+        }
+
+        let mut delta = StateDelta::default();
+        for key in keys {
+            self.diff_entry(
+                key.clone(),
+                prev.member(key),
+                new_state.member(key),
+                &mut delta,
+            );
+        }
+        self.finish(delta)
+    }
+
+    /// Compute the delta for `label` at the granularity of its subscribed
+    /// `paths` — e.g. those reported by
+    /// [`SubscriptionManager::nested_paths_for`](crate::subscription::SubscriptionManager::nested_paths_for).
+    ///
+    /// `changed` and `removed` are keyed by each path's dotted form
+    /// (`settings.theme`), which the renderer merges into its replica at that
+    /// depth. Falls back to [`DeltaResult::FullState`] when a path has no
+    /// dotted form (a segment containing `.`).
+    pub fn compute_paths<S: DiffState + ?Sized>(
+        &self,
+        label: &str,
+        new_state: &S,
+        paths: &[StatePath],
+    ) -> DeltaResult {
+        let Some(prev) = self.baseline(label) else {
             return DeltaResult::FullState;
         };
+        if prev.member_names().is_none() || new_state.member_names().is_none() {
+            return DeltaResult::FullState;
+        }
 
-        let mut changed = serde_json::Map::new();
-        let mut removed = Vec::new();
+        let mut delta = StateDelta::default();
+        for path in paths {
+            let Some(key) = path.to_dotted() else {
+                return DeltaResult::FullState;
+            };
+            self.diff_entry(key, prev.at(path), new_state.at(path), &mut delta);
+        }
+        self.finish(delta)
+    }
 
-        for (key, value) in next_map {
-            match prev_map.get(key) {
-                Some(prev_value) if prev_value == value => {}
-                _ => {
-                    changed.insert(key.clone(), value.clone());
+    /// Record the difference between `prev` and `next` at `key` in `delta`.
+    fn diff_entry(
+        &self,
+        key: String,
+        prev: Option<&JsonValue>,
+        next: Option<&JsonValue>,
+        delta: &mut StateDelta,
+    ) {
+        match (prev, next) {
+            // Values shared through a snapshot are equal without a deep
+            // comparison.
+            (Some(prev_value), Some(value))
+                if std::ptr::eq(prev_value, value) || prev_value == value => {}
+            (Some(prev_value), Some(value)) if self.mode == DeltaMode::Deep => {
+                let mut deep = StateDelta::default();
+                diff_deep(key.clone(), prev_value, value, &mut deep);
+                // A path-keyed delta touching most of a value can outweigh the
+                // value itself; send it whole then. Sizing `value` stops once
+                // it passes the delta, so the check costs O(delta), not
+                // O(value).
+                let deep_len = json_len(&deep.changed) + json_len(&deep.removed);
+                let budget = deep_len.saturating_sub(json_len(&key) + 1);
+                if json_len_capped(value, budget) > budget {
+                    delta.changed.extend(deep.changed);
+                    delta.removed.extend(deep.removed);
+                } else {
+                    delta.changed.insert(key, value.clone());
                 }
             }
-        }
-        for key in prev_map.keys() {
-            if !next_map.contains_key(key) {
-                removed.push(key.clone());
+            (_, Some(value)) => {
+                delta.changed.insert(key, value.clone());
             }
+            (Some(_), None) => delta.removed.push(key),
+            (None, None) => {}
         }
+    }
 
-        if changed.is_empty() && removed.is_empty() {
+    fn finish(&self, delta: StateDelta) -> DeltaResult {
+        if delta.is_no_op() {
             return DeltaResult::Unchanged;
         }
-
-        DeltaResult::Delta(StateDelta { changed, removed })
+        DeltaResult::Delta(delta)
     }
 
     /// Record `state` as the last state sent to `label`. Always called after a
     /// state-update event has been emitted to that webview.
-    pub fn record(&mut self, label: &str, state: JsonValue) {
-        self.last_by_label.insert(label.to_string(), state);
+    ///
+    /// Recording clones of one [`Snapshot`] for several webviews stores a
+    /// single shared baseline.
+    pub fn record(&mut self, label: &str, state: impl Into<Snapshot>) {
+        self.last_by_label.insert(
+            label.to_string(),
+            Baseline {
+                state: state.into(),
+                generation: self.generation,
+            },
+        );
+    }
+
+    /// The baseline recorded for `label`, if any.
+    pub fn baseline(&self, label: &str) -> Option<&Snapshot> {
+        self.last_by_label
+            .get(label)
+            .map(|baseline| &baseline.state)
     }
 
     /// Clear stored history for `label` (e.g. on webview close or resync).
@@ -79,6 +321,93 @@ impl DeltaCalculator {
     }
 }
 
+/// Recurse into `prev` and `next` (known to differ) below the dotted `path`,
+/// recording the smallest changed paths.
+///
+/// Objects are diffed per member and arrays per index, with the elements an
+/// array gained recorded as new indices. Anything else — scalars, type
+/// changes, arrays that shrank, and objects with a member name the dotted
+/// encoding can't express — is replaced whole at `path`. A shrunk array is
+/// replaced rather than patched because the renderer cannot remove an array
+/// element by path.
+fn diff_deep(path: String, prev: &JsonValue, next: &JsonValue, delta: &mut StateDelta) {
+    match (prev, next) {
+        (JsonValue::Object(prev_map), JsonValue::Object(next_map))
+            if prev_map
+                .keys()
+                .chain(next_map.keys())
+                .all(|k| is_path_segment(k)) =>
+        {
+            for (key, value) in next_map {
+                let child = format!("{path}.{key}");
+                match prev_map.get(key) {
+                    Some(prev_value) if prev_value == value => {}
+                    Some(prev_value) => diff_deep(child, prev_value, value, delta),
+                    None => {
+                        delta.changed.insert(child, value.clone());
+                    }
+                }
+            }
+            for key in prev_map.keys() {
+                if !next_map.contains_key(key) {
+                    delta.removed.push(format!("{path}.{key}"));
+                }
+            }
+        }
+        (JsonValue::Array(prev_items), JsonValue::Array(next_items))
+            if prev_items.len() <= next_items.len() =>
+        {
+            for (index, (prev_value, value)) in prev_items.iter().zip(next_items).enumerate() {
+                if prev_value != value {
+                    diff_deep(format!("{path}.{index}"), prev_value, value, delta);
+                }
+            }
+            for (index, value) in next_items.iter().enumerate().skip(prev_items.len()) {
+                delta
+                    .changed
+                    .insert(format!("{path}.{index}"), value.clone());
+            }
+        }
+        _ => {
+            delta.changed.insert(path, next.clone());
+        }
+    }
+}
+
+fn is_path_segment(key: &str) -> bool {
+    !key.is_empty() && !key.contains('.')
+}
+
+/// Serialized length of `value` in bytes, without allocating the output.
+fn json_len<T: Serialize + ?Sized>(value: &T) -> usize {
+    json_len_capped(value, usize::MAX)
+}
+
+/// Like [`json_len`], but stops serializing once the length passes `cap` and
+/// returns some length above it.
+fn json_len_capped<T: Serialize + ?Sized>(value: &T, cap: usize) -> usize {
+    struct Counter {
+        len: usize,
+        cap: usize,
+    }
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.len = self.len.saturating_add(buf.len());
+            if self.len > self.cap {
+                return Err(std::io::ErrorKind::WriteZero.into());
+            }
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let mut counter = Counter { len: 0, cap };
+    // The only possible error is the counter refusing input past `cap`.
+    let _ = serde_json::to_writer(&mut counter, value);
+    counter.len
+}
+
 impl StateDelta {
     /// True when the delta has no changes and no removals.
     pub fn is_no_op(&self) -> bool {
@@ -139,6 +468,245 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn compute_keys_only_diffs_listed_keys() {
+        let mut calc = DeltaCalculator::new();
+        calc.record("main", serde_json::json!({ "a": 1, "b": 2, "c": 3 }));
+        // `c` changed too, but the caller vouches that only `a` and `b` could
+        // have, so `c` is never compared.
+        let next = serde_json::json!({ "a": 5, "b": 2, "c": 4 });
+        let DeltaResult::Delta(delta) = calc.compute_keys("main", &next, &["a".into(), "b".into()])
+        else {
+            panic!("expected Delta variant");
+        };
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(delta.changed.get("a").unwrap(), &serde_json::json!(5));
+        assert!(delta.removed.is_empty());
+    }
+
+    #[test]
+    fn compute_keys_reports_removed_and_skips_filtered_keys() {
+        let mut calc = DeltaCalculator::new();
+        calc.record("main", serde_json::json!({ "a": 1, "b": 2 }));
+        let next = serde_json::json!({ "a": 1 });
+        let DeltaResult::Delta(delta) =
+            calc.compute_keys("main", &next, &["b".into(), "hidden".into()])
+        else {
+            panic!("expected Delta variant");
+        };
+        assert!(delta.changed.is_empty());
+        assert_eq!(delta.removed, vec!["b".to_string()]);
+    }
+
+    #[test]
+    fn compute_keys_with_no_keys_is_unchanged() {
+        let mut calc = DeltaCalculator::new();
+        calc.record("main", serde_json::json!({ "a": 1 }));
+        assert!(matches!(
+            calc.compute_keys("main", &serde_json::json!({ "a": 2 }), &[]),
+            DeltaResult::Unchanged
+        ));
+        assert!(matches!(
+            calc.compute_keys("other", &serde_json::json!({ "a": 2 }), &[]),
+            DeltaResult::FullState
+        ));
+    }
+
+    #[test]
+    fn compute_paths_keys_changes_by_dotted_path() {
+        let mut calc = DeltaCalculator::new();
+        calc.record(
+            "main",
+            serde_json::json!({ "settings": { "theme": "dark" }, "items": { "1": "b" } }),
+        );
+        let next = serde_json::json!({ "settings": { "theme": "light" }, "items": { "1": "b" } });
+        let paths = crate::subscription::normalize_paths(&next, ["settings.theme", "items/1"]);
+        let DeltaResult::Delta(delta) = calc.compute_paths("main", &next, &paths) else {
+            panic!("expected Delta variant");
+        };
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(
+            delta.changed.get("settings.theme").unwrap(),
+            &serde_json::json!("light")
+        );
+
+        let gone = serde_json::json!({ "settings": { "theme": "dark" } });
+        let DeltaResult::Delta(delta) = calc.compute_paths("main", &gone, &paths) else {
+            panic!("expected Delta variant");
+        };
+        assert_eq!(delta.removed, vec!["items.1".to_string()]);
+    }
+
+    #[test]
+    fn compute_paths_falls_back_to_full_state_for_undotted_paths() {
+        let mut calc = DeltaCalculator::new();
+        calc.record("main", serde_json::json!({ "a.b": 1 }));
+        let paths = crate::subscription::normalize_paths(&JsonValue::Null, ["/a.b"]);
+        assert!(matches!(
+            calc.compute_paths("main", &serde_json::json!({ "a.b": 2 }), &paths),
+            DeltaResult::FullState
+        ));
+    }
+
+    #[test]
+    fn deep_mode_reports_changed_leaves_by_path() {
+        let mut calc = DeltaCalculator::with_mode(DeltaMode::Deep);
+        let items: Vec<_> = (0..100)
+            .map(|i| serde_json::json!({ "id": i, "done": false }))
+            .collect();
+        let prev = serde_json::json!({ "items": items, "user": { "name": "Ada", "age": 36 } });
+        calc.record("main", prev.clone());
+
+        let mut next = prev;
+        next["items"][42]["done"] = serde_json::json!(true);
+        next["user"].as_object_mut().unwrap().remove("age");
+        let DeltaResult::Delta(delta) = calc.compute("main", &next) else {
+            panic!("expected Delta variant");
+        };
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(
+            delta.changed.get("items.42.done").unwrap(),
+            &serde_json::json!(true)
+        );
+        assert_eq!(delta.removed, vec!["user.age".to_string()]);
+    }
+
+    #[test]
+    fn deep_mode_replaces_shrunk_arrays_and_dotted_members_whole() {
+        let mut calc = DeltaCalculator::with_mode(DeltaMode::Deep);
+        let long: Vec<_> = (0..20).collect();
+        let prev = serde_json::json!({
+            "list": long,
+            "map": { "a.b": 1, "c": 2 }
+        });
+        calc.record("main", prev);
+        let next = serde_json::json!({
+            "list": long[..19],
+            "map": { "a.b": 1, "c": 3 }
+        });
+        let DeltaResult::Delta(delta) = calc.compute("main", &next) else {
+            panic!("expected Delta variant");
+        };
+        assert_eq!(delta.changed.get("list").unwrap(), &next["list"]);
+        assert_eq!(delta.changed.get("map").unwrap(), &next["map"]);
+    }
+
+    #[test]
+    fn deep_mode_sends_appended_items_by_index() {
+        let mut calc = DeltaCalculator::with_mode(DeltaMode::Deep);
+        let items: Vec<_> = (0..10_000)
+            .map(|i| serde_json::json!({ "id": i, "done": false }))
+            .collect();
+        let prev = serde_json::json!({ "items": items });
+        calc.record("main", prev.clone());
+
+        let mut next = prev;
+        let item = serde_json::json!({ "id": 10_000, "done": false });
+        next["items"].as_array_mut().unwrap().push(item.clone());
+        next["items"][7]["done"] = serde_json::json!(true);
+        let DeltaResult::Delta(delta) = calc.compute("main", &next) else {
+            panic!("expected Delta variant");
+        };
+        assert_eq!(delta.changed.len(), 2);
+        assert_eq!(delta.changed.get("items.10000").unwrap(), &item);
+        assert_eq!(
+            delta.changed.get("items.7.done").unwrap(),
+            &serde_json::json!(true)
+        );
+        assert!(delta.removed.is_empty());
+    }
+
+    #[test]
+    fn deep_mode_sends_a_value_whole_when_its_paths_outweigh_it() {
+        let mut calc = DeltaCalculator::with_mode(DeltaMode::Deep);
+        calc.record(
+            "main",
+            serde_json::json!({ "a": { "x": 1, "y": 2 }, "b": { "z": 1 } }),
+        );
+        // Every leaf of `a` changed: its path-keyed entries repeat the
+        // structure and outweigh the value.
+        let next = serde_json::json!({ "a": { "x": 10, "y": 20 }, "b": { "z": 1 } });
+        let DeltaResult::Delta(delta) = calc.compute("main", &next) else {
+            panic!("expected Delta variant");
+        };
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(delta.changed.get("a").unwrap(), &next["a"]);
+    }
+
+    #[test]
+    fn json_len_capped_stops_past_the_cap() {
+        let value = serde_json::json!({ "list": (0..1000).collect::<Vec<_>>() });
+        assert_eq!(json_len_capped(&value, usize::MAX), json_len(&value));
+        let capped = json_len_capped(&value, 16);
+        assert!(capped > 16 && capped < json_len(&value));
+    }
+
+    #[test]
+    fn top_level_mode_ignores_the_size_heuristic() {
+        let mut calc = DeltaCalculator::new();
+        calc.record("main", serde_json::json!({ "a": { "x": 1, "y": 2 } }));
+        let next = serde_json::json!({ "a": { "x": 10, "y": 20 } });
+        assert!(matches!(calc.compute("main", &next), DeltaResult::Delta(_)));
+    }
+
+    #[test]
+    fn shared_snapshots_diff_and_share_baselines() {
+        let mut calc = DeltaCalculator::new();
+        let first = calc.share(serde_json::json!({ "a": [1, 2], "b": 1 }));
+        calc.record("main", first.clone());
+        calc.record("settings", first);
+        assert!(Snapshot::ptr_eq(
+            calc.baseline("main").unwrap(),
+            calc.baseline("settings").unwrap()
+        ));
+
+        let next = calc.share(serde_json::json!({ "a": [1, 2], "b": 2 }));
+        assert!(std::sync::Arc::ptr_eq(
+            calc.baseline("main").unwrap().get("a").unwrap(),
+            next.get("a").unwrap()
+        ));
+        let DeltaResult::Delta(delta) = calc.compute("main", &next) else {
+            panic!("expected Delta variant");
+        };
+        assert_eq!(delta.changed.len(), 1);
+        assert_eq!(delta.changed.get("b").unwrap(), &serde_json::json!(2));
+    }
+
+    #[test]
+    fn baselines_fall_behind_when_a_shared_state_is_not_recorded() {
+        let mut calc = DeltaCalculator::new();
+        let first = calc.share(serde_json::json!({ "a": 1, "b": 1 }));
+        calc.record("main", first.clone());
+        calc.record("settings", first);
+
+        let second = calc.share_changed(serde_json::json!({ "a": 2, "b": 1 }), &["a".into()]);
+        assert!(calc.is_current("main"));
+        calc.record("main", second);
+        // `settings` neither received nor confirmed the second state.
+        calc.share_changed(serde_json::json!({ "a": 2, "b": 2 }), &["b".into()]);
+        assert!(calc.is_current("main"));
+        assert!(!calc.is_current("settings"));
+        assert!(!calc.is_current("unknown"));
+
+        calc.confirm("settings");
+        assert!(calc.is_current("settings"));
+    }
+
+    #[test]
+    fn snapshot_and_plain_states_diff_alike() {
+        let mut calc = DeltaCalculator::new();
+        calc.record("main", serde_json::json!({ "a": 1, "b": 2 }));
+        let next = serde_json::json!({ "a": 1, "c": 3 });
+        let (DeltaResult::Delta(plain), DeltaResult::Delta(shared)) = (
+            calc.compute("main", &next),
+            calc.compute("main", &Snapshot::from_value(next.clone())),
+        ) else {
+            panic!("expected Delta variants");
+        };
+        assert_eq!(plain.changed, shared.changed);
+        assert_eq!(plain.removed, shared.removed);
+    }
+
     #[test]
     fn is_no_op_distinguishes_empty_from_changed() {
         let empty = StateDelta::default();
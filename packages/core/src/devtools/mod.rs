@@ -0,0 +1,217 @@
+//! Time-travel debugging.
+//!
+//! A [`DevtoolsSession`] attached to the
+//! [`ActionQueueManager`](crate::orchestration::ActionQueueManager) keeps a
+//! bounded ring of every committed action and the state it produced.
+//! [`jump_to`](DevtoolsSession::jump_to) selects an entry whose state
+//! platform wrappers broadcast in place of the live state; until
+//! [`resume`](DevtoolsSession::resume), the queue rejects new dispatches
+//! and holds queued actions, so the live state cannot move underneath the
+//! view. The state manager itself is never rewound.
+//!
+//! With the `devtools` feature, [`remote::RemoteDevtools`] streams the
+//! session to the standalone Redux DevTools app.
+
+#[cfg(feature = "devtools")]
+pub mod remote;
+
+use std::collections::VecDeque;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::error::{Result, ZubridgeError};
+use crate::models::{JsonValue, ZubridgeAction};
+
+/// One committed action and the state after it.
+#[derive(Debug, Clone, Serialize)]
+pub struct DevtoolsEntry {
+    /// Position in the session, counting from 0. Indices keep increasing as
+    /// old entries fall out of the ring.
+    pub index: u64,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub source_label: String,
+    pub action: ZubridgeAction,
+    pub state: JsonValue,
+}
+
+/// Notified of every entry a [`DevtoolsSession`] records, e.g. to stream
+/// them to an external debugger. Called with the action queue locked, so
+/// implementations should hand the entry off rather than block.
+pub trait DevtoolsObserver: Send {
+    fn recorded(&mut self, entry: &DevtoolsEntry);
+}
+
+/// Ring of recent (action, state) pairs. See the module docs.
+pub struct DevtoolsSession {
+    capacity: usize,
+    entries: VecDeque<DevtoolsEntry>,
+    next_index: u64,
+    jumped_to: Option<u64>,
+    observers: Vec<Box<dyn DevtoolsObserver>>,
+}
+
+impl std::fmt::Debug for DevtoolsSession {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DevtoolsSession")
+            .field("capacity", &self.capacity)
+            .field("entries", &self.entries.len())
+            .field("next_index", &self.next_index)
+            .field("jumped_to", &self.jumped_to)
+            .field("observers", &self.observers.len())
+            .finish()
+    }
+}
+
+impl DevtoolsSession {
+    /// A session keeping the latest `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity.min(1024)),
+            next_index: 0,
+            jumped_to: None,
+            observers: Vec::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn add_observer(&mut self, observer: Box<dyn DevtoolsObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Append a committed action, dropping the oldest entry when full, and
+    /// notify observers.
+    pub fn record(&mut self, action: &ZubridgeAction, source_label: &str, state: &JsonValue) {
+        let entry = DevtoolsEntry {
+            index: self.next_index,
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|since| since.as_millis() as u64)
+                .unwrap_or(0),
+            source_label: source_label.to_string(),
+            action: action.clone(),
+            state: state.clone(),
+        };
+        self.next_index += 1;
+        for observer in &mut self.observers {
+            observer.recorded(&entry);
+        }
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// The retained entries, oldest first.
+    pub fn entries(&self) -> impl Iterator<Item = &DevtoolsEntry> {
+        self.entries.iter()
+    }
+
+    /// Index of the latest recorded entry, if any.
+    pub fn latest_index(&self) -> Option<u64> {
+        self.next_index.checked_sub(1)
+    }
+
+    pub fn get(&self, index: u64) -> Option<&DevtoolsEntry> {
+        let first = self.entries.front()?.index;
+        let offset = usize::try_from(index.checked_sub(first)?).ok()?;
+        self.entries.get(offset)
+    }
+
+    /// View the state after entry `index`, pausing dispatch. Fails with
+    /// [`ZubridgeError::Devtools`] if the entry is not in the ring.
+    pub fn jump_to(&mut self, index: u64) -> Result<JsonValue> {
+        let state = self
+            .get(index)
+            .map(|entry| entry.state.clone())
+            .ok_or_else(|| {
+                ZubridgeError::Devtools(format!("history entry {index} is not retained"))
+            })?;
+        self.jumped_to = Some(index);
+        Ok(state)
+    }
+
+    /// Return to the live state. Returns `false` if the session wasn't
+    /// paused.
+    pub fn resume(&mut self) -> bool {
+        self.jumped_to.take().is_some()
+    }
+
+    /// True while viewing a past entry.
+    pub fn is_paused(&self) -> bool {
+        self.jumped_to.is_some()
+    }
+
+    pub fn jumped_to(&self) -> Option<u64> {
+        self.jumped_to
+    }
+
+    /// The state being viewed while paused.
+    pub fn jumped_state(&self) -> Option<&JsonValue> {
+        self.get(self.jumped_to?).map(|entry| &entry.state)
+    }
+
+    /// `Ok` unless paused, in which case dispatches are refused.
+    pub fn check_dispatch(&self) -> Result<()> {
+        match self.jumped_to {
+            Some(index) => Err(ZubridgeError::Devtools(format!(
+                "dispatch is paused while viewing history entry {index}"
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn action(action_type: &str) -> ZubridgeAction {
+        serde_json::from_value(json!({ "action_type": action_type })).unwrap()
+    }
+
+    #[test]
+    fn ring_keeps_the_latest_entries_with_stable_indices() {
+        let mut session = DevtoolsSession::new(2);
+        for count in 1..=3 {
+            session.record(&action("INC"), "main", &json!({ "count": count }));
+        }
+        let indices: Vec<u64> = session.entries().map(|entry| entry.index).collect();
+        assert_eq!(indices, vec![1, 2]);
+        assert!(session.get(0).is_none());
+        assert_eq!(session.get(2).unwrap().state, json!({ "count": 3 }));
+    }
+
+    #[test]
+    fn jumping_pauses_until_resumed() {
+        let mut session = DevtoolsSession::new(10);
+        session.record(&action("INC"), "main", &json!({ "count": 1 }));
+        session.record(&action("INC"), "main", &json!({ "count": 2 }));
+        assert!(session.check_dispatch().is_ok());
+
+        assert_eq!(session.jump_to(0).unwrap(), json!({ "count": 1 }));
+        assert_eq!(session.jumped_state(), Some(&json!({ "count": 1 })));
+        assert!(matches!(
+            session.check_dispatch(),
+            Err(ZubridgeError::Devtools(_))
+        ));
+        assert!(session.jump_to(7).is_err());
+        assert_eq!(session.jumped_to(), Some(0));
+
+        assert!(session.resume());
+        assert!(!session.resume());
+        assert!(session.check_dispatch().is_ok());
+        assert_eq!(session.jumped_state(), None);
+    }
+}
@@ -0,0 +1,574 @@
+//! Redux DevTools remote bridge.
+//!
+//! [`RemoteDevtools`] connects to the socket server of the standalone Redux
+//! DevTools app (`npx @redux-devtools/cli --open`, on `localhost:8000` by
+//! default) and speaks its remotedev protocol over SocketCluster:
+//!
+//! - On connecting, and whenever the monitor asks (`START`, `UPDATE`), it
+//!   sends the live state as `INIT`.
+//! - Every entry the [`DevtoolsSession`](super::DevtoolsSession) records is
+//!   streamed as an `ACTION` with the state after it.
+//! - An incoming `ACTION` (the DevTools dispatcher) is dispatched through the
+//!   host's normal dispatch path.
+//! - An incoming `DISPATCH` of `JUMP_TO_STATE` or `JUMP_TO_ACTION` jumps the
+//!   session to the matching entry; jumping to the latest action resumes.
+//!   Other monitor actions (commit, sweep, toggle) are not supported.
+//!
+//! The bridge runs on its own thread and reconnects until dropped, so the
+//! DevTools app can be started and stopped at any time.
+
+use std::io::ErrorKind;
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+use tungstenite::{Message, WebSocket};
+
+use super::{DevtoolsEntry, DevtoolsObserver};
+use crate::error::{Result, ZubridgeError};
+use crate::models::{JsonValue, ZubridgeAction};
+
+/// How often the bridge thread checks for new entries and stop requests.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// The operations the bridge performs on the app it debugs.
+pub trait DevtoolsHost: Send + Sync {
+    /// Dispatch an action sent from the DevTools dispatcher.
+    fn dispatch(&self, action: ZubridgeAction) -> Result<()>;
+    /// Show the state after session entry `index`.
+    fn jump_to(&self, index: u64) -> Result<()>;
+    /// Return to the live state.
+    fn resume(&self) -> Result<()>;
+    /// The live state, and the index of the session's latest entry.
+    fn snapshot(&self) -> Result<(JsonValue, Option<u64>)>;
+}
+
+/// Where to find the DevTools socket server.
+#[derive(Debug, Clone)]
+pub struct RemoteDevtoolsConfig {
+    /// Default `localhost`.
+    pub host: String,
+    /// Default 8000, the port `@redux-devtools/cli` listens on.
+    pub port: u16,
+    /// Instance name shown in the DevTools instance selector. Default
+    /// `zubridge`.
+    pub instance_name: String,
+    /// Wait between connection attempts. Default 2 s.
+    pub reconnect_delay: Duration,
+}
+
+impl Default for RemoteDevtoolsConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 8000,
+            instance_name: "zubridge".to_string(),
+            reconnect_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RemoteDevtoolsConfig {
+    pub fn url(&self) -> String {
+        format!(
+            "ws://{}:{}/socketcluster/?transport=websocket",
+            self.host, self.port
+        )
+    }
+}
+
+// ── RemoteDevtools ────────────────────────────────────────────────────────────
+
+/// A running bridge. Dropping it disconnects and stops its thread.
+pub struct RemoteDevtools {
+    entries: Sender<DevtoolsEntry>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl RemoteDevtools {
+    /// Start the bridge thread. Connection failures are retried, not
+    /// returned; this only fails if the thread cannot be spawned.
+    pub fn start(config: RemoteDevtoolsConfig, host: Arc<dyn DevtoolsHost>) -> Result<Self> {
+        let (entries, received) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let thread = std::thread::Builder::new()
+            .name("zubridge-devtools".to_string())
+            .spawn(move || run(&config, &*host, &received, &stopped))
+            .map_err(|e| devtools_error(&e))?;
+        Ok(Self {
+            entries,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// An observer to add to the
+    /// [`DevtoolsSession`](super::DevtoolsSession) whose entries the
+    /// bridge streams.
+    pub fn observer(&self) -> Box<dyn DevtoolsObserver> {
+        Box::new(EntrySender(self.entries.clone()))
+    }
+}
+
+impl Drop for RemoteDevtools {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl std::fmt::Debug for RemoteDevtools {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteDevtools")
+            .field("stopped", &self.stop.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+struct EntrySender(Sender<DevtoolsEntry>);
+
+impl DevtoolsObserver for EntrySender {
+    fn recorded(&mut self, entry: &DevtoolsEntry) {
+        // Fails only once the bridge has stopped.
+        let _ = self.0.send(entry.clone());
+    }
+}
+
+fn run(
+    config: &RemoteDevtoolsConfig,
+    host: &dyn DevtoolsHost,
+    entries: &Receiver<DevtoolsEntry>,
+    stop: &AtomicBool,
+) {
+    while !stop.load(Ordering::Relaxed) {
+        match Connection::open(config) {
+            Ok(mut connection) => {
+                log::info!("zubridge: connected to Redux DevTools at {}", config.url());
+                if let Err(err) = connection.serve(host, entries, stop) {
+                    log::debug!("zubridge: Redux DevTools connection closed: {err}");
+                }
+            }
+            Err(err) => {
+                log::debug!(
+                    "zubridge: Redux DevTools not reachable at {}: {err}",
+                    config.url()
+                )
+            }
+        }
+        // Entries recorded while disconnected are dropped; the next
+        // connection starts from a fresh INIT.
+        let retry_at = Instant::now() + config.reconnect_delay;
+        while !stop.load(Ordering::Relaxed) && Instant::now() < retry_at {
+            if let Err(RecvTimeoutError::Disconnected) = entries.recv_timeout(POLL_INTERVAL) {
+                return;
+            }
+        }
+    }
+}
+
+// ── Connection ────────────────────────────────────────────────────────────────
+
+/// One SocketCluster connection to the DevTools server.
+struct Connection<'a> {
+    config: &'a RemoteDevtoolsConfig,
+    socket: WebSocket<TcpStream>,
+    next_cid: u64,
+    handshake_cid: u64,
+    login_cid: Option<u64>,
+    socket_id: Option<String>,
+    /// Channel the monitor publishes to; set once logged in.
+    channel: Option<String>,
+    /// The session entry whose state was sent as INIT (`None`: the state
+    /// before any entry). The monitor numbers actions from there.
+    init_index: Option<u64>,
+    /// The latest entry streamed since INIT.
+    last_sent: Option<u64>,
+}
+
+impl<'a> Connection<'a> {
+    fn open(config: &'a RemoteDevtoolsConfig) -> Result<Self> {
+        let stream = TcpStream::connect((config.host.as_str(), config.port))
+            .map_err(|e| devtools_error(&e))?;
+        let (socket, _response) =
+            tungstenite::client(config.url(), stream).map_err(|e| devtools_error(&e))?;
+        socket
+            .get_ref()
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|e| devtools_error(&e))?;
+        let mut connection = Self {
+            config,
+            socket,
+            next_cid: 1,
+            handshake_cid: 0,
+            login_cid: None,
+            socket_id: None,
+            channel: None,
+            init_index: None,
+            last_sent: None,
+        };
+        connection.handshake_cid = connection.emit("#handshake", json!({ "authToken": null }))?;
+        Ok(connection)
+    }
+
+    fn serve(
+        &mut self,
+        host: &dyn DevtoolsHost,
+        entries: &Receiver<DevtoolsEntry>,
+        stop: &AtomicBool,
+    ) -> Result<()> {
+        while !stop.load(Ordering::Relaxed) {
+            if self.channel.is_some() {
+                while let Ok(entry) = entries.try_recv() {
+                    self.send_entry(&entry)?;
+                }
+            }
+            match self.socket.read() {
+                Ok(Message::Text(text)) => self.handle_text(&text, host)?,
+                Ok(Message::Close(_)) => return Ok(()),
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(err))
+                    if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(err) => return Err(devtools_error(&err)),
+            }
+        }
+        let _ = self.socket.close(None);
+        Ok(())
+    }
+
+    fn handle_text(&mut self, text: &str, host: &dyn DevtoolsHost) -> Result<()> {
+        // SocketCluster pings: "" (protocol v2) or "#1" (v1).
+        match text {
+            "" => return self.send(String::new()),
+            "#1" => return self.send("#2".to_string()),
+            _ => {}
+        }
+        let Ok(message) = serde_json::from_str::<JsonValue>(text) else {
+            log::debug!("zubridge: ignoring unparseable Redux DevTools message");
+            return Ok(());
+        };
+        if let Some(rid) = message["rid"].as_u64() {
+            return self.handle_response(rid, &message, host);
+        }
+        match message["event"].as_str() {
+            Some("#publish") if message["data"]["channel"].as_str() == self.channel.as_deref() => {
+                self.handle_monitor(&message["data"]["data"], host)
+            }
+            Some(event) if Some(event) == self.channel.as_deref() => {
+                self.handle_monitor(&message["data"], host)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn handle_response(
+        &mut self,
+        rid: u64,
+        message: &JsonValue,
+        host: &dyn DevtoolsHost,
+    ) -> Result<()> {
+        if !message["error"].is_null() {
+            return Err(ZubridgeError::Devtools(format!(
+                "server rejected request {rid}: {}",
+                message["error"]
+            )));
+        }
+        if rid == self.handshake_cid {
+            self.socket_id = message["data"]["id"].as_str().map(str::to_string);
+            self.login_cid = Some(self.emit("login", json!("master"))?);
+        } else if Some(rid) == self.login_cid {
+            let channel = message["data"]
+                .as_str()
+                .ok_or_else(|| ZubridgeError::Devtools("login returned no channel".to_string()))?
+                .to_string();
+            self.emit("#subscribe", json!({ "channel": channel }))?;
+            self.channel = Some(channel);
+            self.send_init(host)?;
+        }
+        Ok(())
+    }
+
+    fn handle_monitor(&mut self, message: &JsonValue, host: &dyn DevtoolsHost) -> Result<()> {
+        let result = match message["type"].as_str() {
+            Some("START") | Some("UPDATE") => return self.send_init(host),
+            Some("ACTION") => match parse_action(&message["action"]) {
+                Some(action) => host.dispatch(action),
+                None => Err(ZubridgeError::Devtools(
+                    "dispatched action has no type".to_string(),
+                )),
+            },
+            Some("DISPATCH") => self.handle_monitor_action(&message["action"], host),
+            _ => Ok(()),
+        };
+        // A rejected request is reported, not fatal to the connection.
+        if let Err(err) = result {
+            log::warn!("zubridge: Redux DevTools request failed: {err}");
+        }
+        Ok(())
+    }
+
+    fn handle_monitor_action(&self, action: &JsonValue, host: &dyn DevtoolsHost) -> Result<()> {
+        match action["type"].as_str() {
+            Some("JUMP_TO_STATE") | Some("JUMP_TO_ACTION") => {
+                let action_id = action["actionId"]
+                    .as_u64()
+                    .or_else(|| action["index"].as_u64())
+                    .ok_or_else(|| ZubridgeError::Devtools("jump without an action id".into()))?;
+                let target = self.entry_index(action_id);
+                if target == self.last_sent.or(self.init_index) {
+                    return host.resume();
+                }
+                match target {
+                    Some(index) => host.jump_to(index),
+                    None => Err(ZubridgeError::Devtools(
+                        "the initial state is not retained".to_string(),
+                    )),
+                }
+            }
+            other => Err(ZubridgeError::Devtools(format!(
+                "unsupported monitor action {}",
+                other.unwrap_or("<missing type>")
+            ))),
+        }
+    }
+
+    /// The session entry the monitor's action `action_id` refers to. The
+    /// monitor numbers the INIT state 0 and each streamed action after it.
+    fn entry_index(&self, action_id: u64) -> Option<u64> {
+        match (action_id, self.init_index) {
+            (0, init_index) => init_index,
+            (n, Some(init_index)) => Some(init_index + n),
+            (n, None) => Some(n - 1),
+        }
+    }
+
+    fn send_init(&mut self, host: &dyn DevtoolsHost) -> Result<()> {
+        let (state, latest) = host.snapshot()?;
+        self.init_index = latest;
+        self.last_sent = None;
+        let action = json!({ "type": "@@INIT", "timestamp": now_ms() });
+        self.log("INIT", &state, &action)
+    }
+
+    fn send_entry(&mut self, entry: &DevtoolsEntry) -> Result<()> {
+        // Entries already covered by the INIT state.
+        if self.init_index.is_some_and(|init| entry.index <= init) {
+            return Ok(());
+        }
+        let action = json!({
+            "type": "PERFORM_ACTION",
+            "action": { "type": entry.action.action_type, "payload": entry.action.payload },
+            "timestamp": entry.timestamp_ms,
+        });
+        self.log("ACTION", &entry.state, &action)?;
+        self.last_sent = Some(entry.index);
+        Ok(())
+    }
+
+    /// Send a remotedev message. `payload` and `action` travel as JSON
+    /// strings, as the monitor expects.
+    fn log(&mut self, message_type: &str, state: &JsonValue, action: &JsonValue) -> Result<()> {
+        let message = json!({
+            "type": message_type,
+            "id": self.socket_id,
+            "name": self.config.instance_name,
+            "instanceId": self.config.instance_name,
+            "payload": state.to_string(),
+            "action": action.to_string(),
+        });
+        let event = if self.socket_id.is_some() {
+            "log"
+        } else {
+            "log-noid"
+        };
+        self.emit(event, message).map(|_| ())
+    }
+
+    /// Emit a SocketCluster event, returning its call id.
+    fn emit(&mut self, event: &str, data: JsonValue) -> Result<u64> {
+        let cid = self.next_cid;
+        self.next_cid += 1;
+        self.send(json!({ "event": event, "data": data, "cid": cid }).to_string())?;
+        Ok(cid)
+    }
+
+    fn send(&mut self, text: String) -> Result<()> {
+        self.socket
+            .send(Message::Text(text))
+            .map_err(|e| devtools_error(&e))
+    }
+}
+
+/// A Redux action from the DevTools dispatcher, sent either as an object or
+/// as its JSON text.
+fn parse_action(action: &JsonValue) -> Option<ZubridgeAction> {
+    let action = match action {
+        JsonValue::String(text) => serde_json::from_str(text).ok()?,
+        other => other.clone(),
+    };
+    serde_json::from_value(json!({
+        "action_type": action["type"].as_str()?,
+        "payload": action.get("payload"),
+    }))
+    .ok()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn devtools_error(err: &impl std::fmt::Display) -> ZubridgeError {
+    ZubridgeError::Devtools(err.to_string())
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devtools::DevtoolsSession;
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+
+    /// Records what the bridge asks of the app.
+    #[derive(Default)]
+    struct RecordingHost {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl RecordingHost {
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+
+        fn push(&self, call: String) -> Result<()> {
+            self.calls.lock().unwrap().push(call);
+            Ok(())
+        }
+    }
+
+    impl DevtoolsHost for RecordingHost {
+        fn dispatch(&self, action: ZubridgeAction) -> Result<()> {
+            self.push(format!(
+                "dispatch {} {:?}",
+                action.action_type, action.payload
+            ))
+        }
+        fn jump_to(&self, index: u64) -> Result<()> {
+            self.push(format!("jump {index}"))
+        }
+        fn resume(&self) -> Result<()> {
+            self.push("resume".to_string())
+        }
+        fn snapshot(&self) -> Result<(JsonValue, Option<u64>)> {
+            Ok((json!({ "count": 0 }), None))
+        }
+    }
+
+    /// Next JSON message from the bridge, answering pings along the way.
+    fn next_message(ws: &mut WebSocket<TcpStream>) -> JsonValue {
+        loop {
+            match ws.read().unwrap() {
+                Message::Text(text) if text == "#2" || text.is_empty() => continue,
+                Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+                _ => continue,
+            }
+        }
+    }
+
+    fn reply(ws: &mut WebSocket<TcpStream>, request: &JsonValue, data: JsonValue) {
+        let response = json!({ "rid": request["cid"], "error": null, "data": data });
+        ws.send(Message::Text(response.to_string())).unwrap();
+    }
+
+    fn publish(ws: &mut WebSocket<TcpStream>, data: JsonValue) {
+        let message = json!({
+            "event": "#publish",
+            "data": { "channel": "respond", "data": data },
+        });
+        ws.send(Message::Text(message.to_string())).unwrap();
+    }
+
+    #[test]
+    fn streams_actions_and_applies_monitor_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = RemoteDevtoolsConfig {
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+            reconnect_delay: Duration::from_millis(10),
+            ..RemoteDevtoolsConfig::default()
+        };
+        let host = Arc::new(RecordingHost::default());
+        let bridge = RemoteDevtools::start(config, host.clone()).unwrap();
+        let mut session = DevtoolsSession::new(10);
+        session.add_observer(bridge.observer());
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut ws = tungstenite::accept(stream).unwrap();
+        let handshake = next_message(&mut ws);
+        assert_eq!(handshake["event"], "#handshake");
+        ws.send(Message::Text("#1".to_string())).unwrap();
+        reply(&mut ws, &handshake, json!({ "id": "socket-1" }));
+        let login = next_message(&mut ws);
+        assert_eq!(
+            (&login["event"], &login["data"]),
+            (&json!("login"), &json!("master"))
+        );
+        reply(&mut ws, &login, json!("respond"));
+        assert_eq!(next_message(&mut ws)["event"], "#subscribe");
+
+        let init = next_message(&mut ws);
+        assert_eq!(init["event"], "log");
+        assert_eq!(init["data"]["type"], "INIT");
+        assert_eq!(init["data"]["id"], "socket-1");
+        assert_eq!(init["data"]["payload"], r#"{"count":0}"#);
+
+        let inc: ZubridgeAction = serde_json::from_value(json!({ "action_type": "INC" })).unwrap();
+        session.record(&inc, "main", &json!({ "count": 1 }));
+        session.record(&inc, "main", &json!({ "count": 2 }));
+        for count in 1..=2 {
+            let streamed = next_message(&mut ws);
+            assert_eq!(streamed["data"]["type"], "ACTION");
+            assert_eq!(
+                streamed["data"]["payload"],
+                format!(r#"{{"count":{count}}}"#)
+            );
+            let action: JsonValue =
+                serde_json::from_str(streamed["data"]["action"].as_str().unwrap()).unwrap();
+            assert_eq!(action["action"]["type"], "INC");
+        }
+
+        publish(
+            &mut ws,
+            json!({ "type": "ACTION", "action": r#"{"type":"ADD","payload":5}"# }),
+        );
+        publish(
+            &mut ws,
+            json!({ "type": "DISPATCH", "action": { "type": "JUMP_TO_STATE", "index": 1, "actionId": 1 } }),
+        );
+        publish(
+            &mut ws,
+            json!({ "type": "DISPATCH", "action": { "type": "JUMP_TO_ACTION", "actionId": 2 } }),
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while host.calls().len() < 3 && Instant::now() < deadline {
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        assert_eq!(
+            host.calls(),
+            vec!["dispatch ADD Some(Number(5))", "jump 0", "resume"]
+        );
+        drop(bridge);
+    }
+}
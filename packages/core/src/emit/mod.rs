@@ -1,3 +1,4 @@
+use crate::error::Result;
 use crate::models::JsonValue;
 
 /// Platform-agnostic event emission trait.
@@ -14,5 +15,9 @@ pub trait EventEmitter: Send + Sync {
     /// - Tauri: webview label
     /// - NAPI: subscriber ID
     /// - Direct Rust: channel name
-    fn emit(&self, target: &str, event: &str, payload: &JsonValue);
+    ///
+    /// Returns [`ZubridgeError::EmitError`](crate::ZubridgeError::EmitError)
+    /// if the event could not be handed to the runtime, so callers can tell
+    /// delivered updates from lost ones.
+    fn emit(&self, target: &str, event: &str, payload: &JsonValue) -> Result<()>;
 }
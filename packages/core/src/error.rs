@@ -6,8 +6,30 @@ pub enum ZubridgeError {
     StateError(String),
     #[error("action processing error: {0}")]
     ActionProcessing(String),
+    #[error(
+        "invalid action {}: {message}",
+        action_type.as_deref().unwrap_or("<missing type>")
+    )]
+    InvalidAction {
+        action_type: Option<String>,
+        message: String,
+    },
+    #[error("no handler registered for action type {action_type}")]
+    UnknownActionType { action_type: String },
+    #[error("access denied for {source_label}: {message}")]
+    AccessDenied {
+        source_label: String,
+        keys: Vec<String>,
+        message: String,
+    },
+    #[error("permission denied for {source_label}: {message}")]
+    PermissionDenied { source_label: String, message: String },
+    #[error("action queue overflow ({queue_size}/{max_size})")]
+    QueueOverflow { queue_size: usize, max_size: usize },
     #[error("state manager missing")]
     StateManagerMissing,
+    #[error("state manager does not support hydrate")]
+    HydrateUnsupported,
     #[error("thunk not found: {thunk_id}")]
     ThunkNotFound { thunk_id: String },
     #[error("thunk registration failed for {thunk_id}: {message}")]
@@ -18,6 +40,12 @@ pub enum ZubridgeError {
     EmitError(String),
     #[error("serialization error: {0}")]
     Serialization(String),
+    #[error("persistence error: {0}")]
+    Persistence(String),
+    #[error("state migration from schema v{from} to v{to} failed: {message}")]
+    Migration { from: u32, to: u32, message: String },
+    #[error("devtools: {0}")]
+    Devtools(String),
 }
 
 pub type Result<T> = std::result::Result<T, ZubridgeError>;
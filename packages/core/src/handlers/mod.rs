@@ -0,0 +1,297 @@
+//! Action handler registry.
+//!
+//! [`HandlerRegistry`] maps action types to handler closures, the Rust
+//! counterpart of a Zustand store whose actions are attached as handler
+//! functions. Handlers are registered against either an exact action type or
+//! a namespace pattern ending in `*`:
+//!
+//! - `COUNTER:SET` matches only `COUNTER:SET`.
+//! - `COUNTER:*` matches every action type starting with `COUNTER:`.
+//! - `*` matches every action type.
+//!
+//! An exact registration always wins; among patterns the longest prefix wins.
+//! An action type with no matching handler is rejected with
+//! [`ZubridgeError::UnknownActionType`].
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::error::{Result, ZubridgeError};
+use crate::models::{JsonValue, TryStateManager};
+
+/// Handler invoked with the state and the action in the legacy
+/// `{ type, payload }` shape.
+pub type ActionHandler<S> = Box<dyn Fn(&mut S, &JsonValue) -> Result<()> + Send + Sync>;
+
+// ── HandlerRegistry ───────────────────────────────────────────────────────────
+
+/// A state manager that routes each action to the handler registered for its
+/// type.
+///
+/// Handlers run against a copy of the state that is only committed when the
+/// handler succeeds, so a failing handler never leaves a half-applied update.
+pub struct HandlerRegistry<S> {
+    state: S,
+    exact: HashMap<String, ActionHandler<S>>,
+    /// `(prefix, handler)` for each `prefix*` pattern, longest prefix first.
+    patterns: Vec<(String, ActionHandler<S>)>,
+}
+
+impl<S> HandlerRegistry<S>
+where
+    S: Serialize + Clone + Send + Sync + 'static,
+{
+    pub fn new(initial_state: S) -> Self {
+        Self {
+            state: initial_state,
+            exact: HashMap::new(),
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Register `handler` for `pattern` (an exact action type, or a prefix
+    /// ending in `*`). Registering the same pattern again replaces the
+    /// earlier handler.
+    pub fn on<F>(mut self, pattern: impl Into<String>, handler: F) -> Self
+    where
+        F: Fn(&mut S, &JsonValue) -> Result<()> + Send + Sync + 'static,
+    {
+        self.register(pattern, handler);
+        self
+    }
+
+    /// Non-consuming form of [`on`](Self::on).
+    pub fn register<F>(&mut self, pattern: impl Into<String>, handler: F)
+    where
+        F: Fn(&mut S, &JsonValue) -> Result<()> + Send + Sync + 'static,
+    {
+        let pattern = pattern.into();
+        let handler: ActionHandler<S> = Box::new(handler);
+        match pattern.strip_suffix('*') {
+            Some(prefix) => {
+                let prefix = prefix.to_string();
+                self.patterns.retain(|(existing, _)| *existing != prefix);
+                let at = self
+                    .patterns
+                    .iter()
+                    .position(|(existing, _)| existing.len() < prefix.len())
+                    .unwrap_or(self.patterns.len());
+                self.patterns.insert(at, (prefix, handler));
+            }
+            None => {
+                self.exact.insert(pattern, handler);
+            }
+        }
+    }
+
+    /// Current state.
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    /// Every registered pattern, sorted: exact action types as registered,
+    /// namespace patterns with their trailing `*`.
+    pub fn registered_action_types(&self) -> Vec<String> {
+        let mut types: Vec<String> = self
+            .exact
+            .keys()
+            .cloned()
+            .chain(self.patterns.iter().map(|(prefix, _)| format!("{prefix}*")))
+            .collect();
+        types.sort();
+        types
+    }
+
+    fn handler_for(&self, action_type: &str) -> Option<&ActionHandler<S>> {
+        self.exact.get(action_type).or_else(|| {
+            self.patterns
+                .iter()
+                .find(|(prefix, _)| action_type.starts_with(prefix.as_str()))
+                .map(|(_, handler)| handler)
+        })
+    }
+
+    fn state_json(&self) -> Result<JsonValue> {
+        serde_json::to_value(&self.state).map_err(|e| ZubridgeError::Serialization(e.to_string()))
+    }
+}
+
+impl<S> TryStateManager for HandlerRegistry<S>
+where
+    S: Serialize + Clone + Send + Sync + 'static,
+{
+    fn get_initial_state(&self) -> JsonValue {
+        self.state_json().unwrap_or_else(|err| {
+            log::error!("zubridge: failed to serialize handler registry state: {err}");
+            JsonValue::Null
+        })
+    }
+
+    fn try_dispatch_action(&mut self, action: JsonValue) -> Result<JsonValue> {
+        let Some(action_type) = action.get("type").and_then(JsonValue::as_str) else {
+            return Err(ZubridgeError::InvalidAction {
+                action_type: None,
+                message: "action is missing a string `type` field".to_string(),
+            });
+        };
+        let handler =
+            self.handler_for(action_type)
+                .ok_or_else(|| ZubridgeError::UnknownActionType {
+                    action_type: action_type.to_string(),
+                })?;
+        let mut next = self.state.clone();
+        handler(&mut next, &action)?;
+        self.state = next;
+        self.state_json()
+    }
+
+    fn action_types(&self) -> Option<Vec<String>> {
+        Some(self.registered_action_types())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, Clone, Default, Serialize)]
+    struct AppState {
+        counter: i64,
+        log: Vec<String>,
+    }
+
+    fn registry() -> HandlerRegistry<AppState> {
+        HandlerRegistry::new(AppState::default())
+            .on("COUNTER:INCREMENT", |state: &mut AppState, _| {
+                state.counter += 1;
+                Ok(())
+            })
+            .on("COUNTER:SET", |state: &mut AppState, action| {
+                state.counter = action["payload"].as_i64().ok_or_else(|| {
+                    ZubridgeError::ActionProcessing("COUNTER:SET expects a number".into())
+                })?;
+                Ok(())
+            })
+            .on("COUNTER:*", |state: &mut AppState, action| {
+                state
+                    .log
+                    .push(format!("counter fallback: {}", action["type"]));
+                Ok(())
+            })
+            .on("*", |state: &mut AppState, action| {
+                state.log.push(format!("catch-all: {}", action["type"]));
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn exact_match_wins_over_patterns() {
+        let mut registry = registry();
+        let state = registry
+            .try_dispatch_action(json!({ "type": "COUNTER:INCREMENT" }))
+            .unwrap();
+        assert_eq!(state["counter"], 1);
+        assert!(registry.state().log.is_empty());
+    }
+
+    #[test]
+    fn longest_namespace_pattern_wins() {
+        let mut registry = registry();
+        registry
+            .try_dispatch_action(json!({ "type": "COUNTER:DOUBLE" }))
+            .unwrap();
+        registry
+            .try_dispatch_action(json!({ "type": "THEME:TOGGLE" }))
+            .unwrap();
+        assert_eq!(
+            registry.state().log,
+            vec![
+                "counter fallback: \"COUNTER:DOUBLE\"".to_string(),
+                "catch-all: \"THEME:TOGGLE\"".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_action_type_is_a_typed_error() {
+        let mut registry =
+            HandlerRegistry::new(AppState::default()).on("COUNTER:*", |_: &mut AppState, _| Ok(()));
+        let err = registry
+            .try_dispatch_action(json!({ "type": "THEME:TOGGLE" }))
+            .unwrap_err();
+        match err {
+            ZubridgeError::UnknownActionType { action_type } => {
+                assert_eq!(action_type, "THEME:TOGGLE")
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn missing_type_is_invalid_action() {
+        let mut registry = registry();
+        let err = registry
+            .try_dispatch_action(json!({ "payload": 1 }))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ZubridgeError::InvalidAction {
+                action_type: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn failing_handler_leaves_state_unchanged() {
+        let mut registry = registry();
+        registry
+            .try_dispatch_action(json!({ "type": "COUNTER:INCREMENT" }))
+            .unwrap();
+        let err = registry
+            .try_dispatch_action(json!({ "type": "COUNTER:SET", "payload": "x" }))
+            .unwrap_err();
+        assert!(matches!(err, ZubridgeError::ActionProcessing(_)));
+        assert_eq!(registry.state().counter, 1);
+    }
+
+    #[test]
+    fn re_registering_a_pattern_replaces_the_handler() {
+        let mut registry = registry();
+        registry.register("COUNTER:*", |state: &mut AppState, _| {
+            state.counter = -1;
+            Ok(())
+        });
+        registry
+            .try_dispatch_action(json!({ "type": "COUNTER:HALVE" }))
+            .unwrap();
+        assert_eq!(registry.state().counter, -1);
+        assert_eq!(
+            registry.registered_action_types(),
+            vec!["*", "COUNTER:*", "COUNTER:INCREMENT", "COUNTER:SET"]
+        );
+    }
+
+    #[test]
+    fn action_types_are_reported_through_the_state_handle() {
+        let handle = crate::state::new_handle(registry());
+        let types = crate::state::action_types(&handle).unwrap().unwrap();
+        assert!(types.contains(&"COUNTER:*".to_string()));
+
+        struct Plain;
+        impl crate::models::StateManager for Plain {
+            fn get_initial_state(&self) -> JsonValue {
+                JsonValue::Null
+            }
+            fn dispatch_action(&mut self, _action: JsonValue) -> JsonValue {
+                JsonValue::Null
+            }
+        }
+        let plain = crate::state::new_handle(Plain);
+        assert!(crate::state::action_types(&plain).unwrap().is_none());
+    }
+}
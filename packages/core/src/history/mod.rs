@@ -0,0 +1,340 @@
+//! Undo/redo history.
+//!
+//! An [`UndoHistory`] attached to the
+//! [`ActionQueueManager`](crate::orchestration::ActionQueueManager) snapshots
+//! the tracked part of the state before each change the queue commits. With
+//! [`HistoryGranularity::RootThunk`] (the default) every action dispatched
+//! from within one root thunk's tree shares a history entry, so the whole
+//! thunk undoes as one unit; actions outside thunks get an entry each.
+//!
+//! Undo and redo are built-in actions: dispatch [`UNDO_ACTION_TYPE`] or
+//! [`REDO_ACTION_TYPE`] from any webview or the host. They always run
+//! immediately, and restore the previous state through
+//! [`TryStateManager::hydrate`](crate::models::TryStateManager::hydrate), so
+//! they fail with [`HydrateUnsupported`](crate::ZubridgeError::HydrateUnsupported)
+//! against a state manager that can't restore.
+//! [`HistoryConfig::keys`] limits history to some top-level keys: untracked
+//! keys are neither snapshotted nor restored, and changes only to them do
+//! not create entries.
+
+use std::collections::VecDeque;
+
+use crate::models::JsonValue;
+use crate::persistence::KeyFilter;
+
+/// Action type that undoes the latest history entry.
+pub const UNDO_ACTION_TYPE: &str = "@@zubridge/UNDO";
+
+/// Action type that re-applies the latest undone history entry.
+pub const REDO_ACTION_TYPE: &str = "@@zubridge/REDO";
+
+/// A built-in history action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryOp {
+    Undo,
+    Redo,
+}
+
+impl HistoryOp {
+    /// The history action `action_type` names, if any.
+    pub fn from_action_type(action_type: &str) -> Option<Self> {
+        match action_type {
+            UNDO_ACTION_TYPE => Some(HistoryOp::Undo),
+            REDO_ACTION_TYPE => Some(HistoryOp::Redo),
+            _ => None,
+        }
+    }
+}
+
+/// What one history entry covers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HistoryGranularity {
+    /// Every committed action.
+    Action,
+    /// Every action outside a thunk, and every root thunk's actions as one.
+    #[default]
+    RootThunk,
+}
+
+/// Options for [`UndoHistory`].
+#[derive(Debug, Clone)]
+pub struct HistoryConfig {
+    /// Entries kept for undo; the oldest are dropped beyond this. Default 100.
+    pub limit: usize,
+    /// Top-level keys history tracks. Default [`KeyFilter::All`].
+    pub keys: KeyFilter,
+    pub granularity: HistoryGranularity,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            limit: 100,
+            keys: KeyFilter::All,
+            granularity: HistoryGranularity::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HistoryEntry {
+    /// The root thunk the entry's actions belonged to, if any.
+    group: Option<String>,
+    /// The tracked state to restore.
+    state: JsonValue,
+}
+
+// ── UndoHistory ───────────────────────────────────────────────────────────────
+
+/// Undo and redo stacks of tracked-state snapshots. See the module docs.
+#[derive(Debug)]
+pub struct UndoHistory {
+    config: HistoryConfig,
+    undo: VecDeque<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    /// The tracked state after the latest recorded change; `None` until a
+    /// baseline is set.
+    current: Option<JsonValue>,
+}
+
+impl UndoHistory {
+    pub fn new(config: HistoryConfig) -> Self {
+        Self {
+            config,
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            current: None,
+        }
+    }
+
+    pub fn config(&self) -> &HistoryConfig {
+        &self.config
+    }
+
+    /// True once a baseline state has been set by [`reset`](Self::reset).
+    pub fn has_baseline(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Drop every entry and treat `state` as the starting point.
+    pub fn reset(&mut self, state: &JsonValue) {
+        self.undo.clear();
+        self.redo.clear();
+        self.current = Some(self.config.keys.apply(state));
+    }
+
+    /// Record that an action from root thunk `group` (if any) produced
+    /// `state`. Creates an entry only if the tracked state changed and the
+    /// latest entry doesn't already cover `group`; clears the redo stack.
+    pub fn record(&mut self, group: Option<&str>, state: &JsonValue) {
+        let tracked = self.config.keys.apply(state);
+        let Some(previous) = self.current.take() else {
+            self.current = Some(tracked);
+            return;
+        };
+        if previous == tracked {
+            self.current = Some(previous);
+            return;
+        }
+        let same_group = self.config.granularity == HistoryGranularity::RootThunk
+            && group.is_some()
+            && self.undo.back().and_then(|entry| entry.group.as_deref()) == group;
+        if !same_group {
+            self.undo.push_back(HistoryEntry {
+                group: group.map(str::to_string),
+                state: previous,
+            });
+            while self.undo.len() > self.config.limit {
+                self.undo.pop_front();
+            }
+        }
+        self.redo.clear();
+        self.current = Some(tracked);
+    }
+
+    /// Step back (or forward) one entry. Returns the partial state to
+    /// [`hydrate`](crate::models::TryStateManager::hydrate) the state manager
+    /// with — tracked keys that did not exist at that point are `null` — or
+    /// `None` if there is nothing to undo (or redo).
+    pub fn apply(&mut self, op: HistoryOp) -> Option<JsonValue> {
+        let current = self.current.as_ref()?;
+        let entry = match op {
+            HistoryOp::Undo => self.undo.pop_back()?,
+            HistoryOp::Redo => self.redo.pop()?,
+        };
+        let restore = match (&entry.state, current) {
+            (JsonValue::Object(target), JsonValue::Object(current)) => {
+                let mut restore = target.clone();
+                for key in current.keys() {
+                    restore.entry(key.clone()).or_insert(JsonValue::Null);
+                }
+                JsonValue::Object(restore)
+            }
+            (target, _) => target.clone(),
+        };
+        let inverse = HistoryEntry {
+            group: entry.group,
+            state: self.current.replace(entry.state).unwrap_or_default(),
+        };
+        match op {
+            HistoryOp::Undo => self.redo.push(inverse),
+            HistoryOp::Redo => self.undo.push_back(inverse),
+        }
+        Some(restore)
+    }
+
+    /// Top-level keys [`apply`](Self::apply) would change for `op`, sorted,
+    /// without applying it. Empty if there is nothing to undo (or redo), or
+    /// if the tracked state is not an object.
+    pub fn affected_keys(&self, op: HistoryOp) -> Vec<String> {
+        let entry = match op {
+            HistoryOp::Undo => self.undo.back(),
+            HistoryOp::Redo => self.redo.last(),
+        };
+        let (Some(entry), Some(current)) = (entry, self.current.as_ref()) else {
+            return Vec::new();
+        };
+        let (JsonValue::Object(target), JsonValue::Object(current)) = (&entry.state, current)
+        else {
+            return Vec::new();
+        };
+        let mut keys: Vec<String> = target
+            .keys()
+            .chain(current.keys())
+            .filter(|key| target.get(*key) != current.get(*key))
+            .cloned()
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Number of entries available to undo and to redo.
+    pub fn depth(&self) -> (usize, usize) {
+        (self.undo.len(), self.redo.len())
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn history(config: HistoryConfig) -> UndoHistory {
+        let mut history = UndoHistory::new(config);
+        history.reset(&json!({ "doc": "", "cursor": 0 }));
+        history
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_stacks() {
+        let mut history = history(HistoryConfig::default());
+        history.record(None, &json!({ "doc": "a", "cursor": 1 }));
+        history.record(None, &json!({ "doc": "ab", "cursor": 2 }));
+        assert_eq!(history.depth(), (2, 0));
+
+        assert_eq!(
+            history.apply(HistoryOp::Undo),
+            Some(json!({ "doc": "a", "cursor": 1 }))
+        );
+        assert_eq!(
+            history.apply(HistoryOp::Undo),
+            Some(json!({ "doc": "", "cursor": 0 }))
+        );
+        assert_eq!(history.apply(HistoryOp::Undo), None);
+        assert_eq!(
+            history.apply(HistoryOp::Redo),
+            Some(json!({ "doc": "a", "cursor": 1 }))
+        );
+        assert_eq!(history.depth(), (1, 1));
+
+        // A new change discards what could be redone.
+        history.record(None, &json!({ "doc": "ax", "cursor": 2 }));
+        assert!(!history.can_redo());
+        assert_eq!(history.apply(HistoryOp::Redo), None);
+    }
+
+    #[test]
+    fn affected_keys_lists_what_a_step_would_change() {
+        let mut history = history(HistoryConfig::default());
+        assert!(history.affected_keys(HistoryOp::Undo).is_empty());
+        history.record(None, &json!({ "doc": "a", "cursor": 0 }));
+        history.record(None, &json!({ "doc": "a", "cursor": 1, "sel": 1 }));
+        assert_eq!(history.affected_keys(HistoryOp::Undo), ["cursor", "sel"]);
+        history.apply(HistoryOp::Undo);
+        assert_eq!(history.affected_keys(HistoryOp::Redo), ["cursor", "sel"]);
+        assert_eq!(history.affected_keys(HistoryOp::Undo), ["doc"]);
+    }
+
+    #[test]
+    fn a_root_thunk_is_one_entry() {
+        let mut history = history(HistoryConfig::default());
+        history.record(None, &json!({ "doc": "a", "cursor": 1 }));
+        history.record(Some("t1"), &json!({ "doc": "ab", "cursor": 1 }));
+        history.record(Some("t1"), &json!({ "doc": "ab", "cursor": 2 }));
+        history.record(Some("t2"), &json!({ "doc": "abc", "cursor": 3 }));
+        assert_eq!(history.depth(), (3, 0));
+        history.apply(HistoryOp::Undo);
+        assert_eq!(
+            history.apply(HistoryOp::Undo),
+            Some(json!({ "doc": "a", "cursor": 1 }))
+        );
+
+        let mut per_action = self::history(HistoryConfig {
+            granularity: HistoryGranularity::Action,
+            ..HistoryConfig::default()
+        });
+        per_action.record(Some("t1"), &json!({ "doc": "a", "cursor": 0 }));
+        per_action.record(Some("t1"), &json!({ "doc": "a", "cursor": 1 }));
+        assert_eq!(per_action.depth(), (2, 0));
+    }
+
+    #[test]
+    fn untracked_keys_are_ignored() {
+        let mut history = history(HistoryConfig {
+            keys: KeyFilter::Allow(vec!["doc".into()]),
+            ..HistoryConfig::default()
+        });
+        history.record(None, &json!({ "doc": "", "cursor": 5 }));
+        assert!(!history.can_undo());
+        history.record(None, &json!({ "doc": "a", "cursor": 6, "draft": true }));
+        assert_eq!(history.apply(HistoryOp::Undo), Some(json!({ "doc": "" })));
+    }
+
+    #[test]
+    fn keys_added_since_the_entry_are_nulled() {
+        let mut history = history(HistoryConfig::default());
+        history.record(None, &json!({ "doc": "", "cursor": 0, "draft": true }));
+        assert_eq!(
+            history.apply(HistoryOp::Undo),
+            Some(json!({ "doc": "", "cursor": 0, "draft": null }))
+        );
+    }
+
+    #[test]
+    fn limit_drops_the_oldest_entries() {
+        let mut history = history(HistoryConfig {
+            limit: 2,
+            ..HistoryConfig::default()
+        });
+        for cursor in 1..=4 {
+            history.record(None, &json!({ "doc": "", "cursor": cursor }));
+        }
+        assert_eq!(history.depth(), (2, 0));
+        history.apply(HistoryOp::Undo);
+        assert_eq!(
+            history.apply(HistoryOp::Undo),
+            Some(json!({ "doc": "", "cursor": 2 }))
+        );
+    }
+}
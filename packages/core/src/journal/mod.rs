@@ -0,0 +1,409 @@
+//! Append-only action journal.
+//!
+//! An [`ActionJournal`] records every action the
+//! [`ActionQueueManager`](crate::orchestration::ActionQueueManager) executes
+//! as one NDJSON line: the action, its source label and thunk parent, when
+//! it ran, and whether the state manager accepted it. Each line is written
+//! with a single `write_all` and flushed, so a crash loses at most the line
+//! being written, and [`replay`] skips such a truncated tail. Once the file
+//! would exceed [`JournalConfig::max_bytes`] it is rotated to `<path>.1`
+//! (shifting older files up to [`JournalConfig::max_files`]).
+//!
+//! [`replay`] feeds a journal back through a fresh state manager to rebuild
+//! the state it recorded — e.g. to reproduce a bug report locally. An undo
+//! or redo is journaled with the state it restored, which replay hydrates
+//! rather than dispatching the history action to a manager that doesn't
+//! know it.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, ZubridgeError};
+use crate::history::HistoryOp;
+use crate::models::{JsonValue, TryStateManager, ZubridgeAction};
+
+/// One executed action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub source_label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thunk_parent_id: Option<String>,
+    pub action: ZubridgeAction,
+    /// The state manager's error, if it rejected the action.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// For an undo or redo, the state passed to the state manager's
+    /// `hydrate`. `None` if there was nothing to undo or redo.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hydrated: Option<JsonValue>,
+}
+
+/// Where and how much to journal.
+#[derive(Debug, Clone)]
+pub struct JournalConfig {
+    /// The active journal file. Relative paths are resolved by the platform
+    /// wrapper (the Tauri plugin uses the app data directory).
+    pub path: PathBuf,
+    /// Rotate once the active file would exceed this many bytes. Default
+    /// 8 MiB.
+    pub max_bytes: u64,
+    /// Rotated files to keep (`<path>.1` is the newest). Default 3.
+    pub max_files: usize,
+}
+
+impl JournalConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: 8 * 1024 * 1024,
+            max_files: 3,
+        }
+    }
+}
+
+// ── ActionJournal ─────────────────────────────────────────────────────────────
+
+/// An open journal. See the module docs.
+#[derive(Debug)]
+pub struct ActionJournal {
+    config: JournalConfig,
+    file: File,
+    len: u64,
+}
+
+impl ActionJournal {
+    /// Open `config.path` for appending, creating it and its directory if
+    /// needed.
+    pub fn open(config: JournalConfig) -> Result<Self> {
+        if let Some(dir) = config.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| journal_error(dir, e))?;
+        }
+        let file = open_append(&config.path)?;
+        let len = file
+            .metadata()
+            .map_err(|e| journal_error(&config.path, e))?
+            .len();
+        Ok(Self { config, file, len })
+    }
+
+    pub fn config(&self) -> &JournalConfig {
+        &self.config
+    }
+
+    /// Record that `action` from `source_label` was executed, with the
+    /// state manager's verdict. `hydrated` is the state an undo or redo
+    /// restored.
+    pub fn record(
+        &mut self,
+        action: &ZubridgeAction,
+        source_label: &str,
+        outcome: std::result::Result<(), &ZubridgeError>,
+        hydrated: Option<&JsonValue>,
+    ) -> Result<()> {
+        let entry = JournalEntry {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|since| since.as_millis() as u64)
+                .unwrap_or(0),
+            source_label: source_label.to_string(),
+            thunk_parent_id: action.thunk_parent_id.clone(),
+            action: action.clone(),
+            error: outcome.err().map(ToString::to_string),
+            hydrated: hydrated.cloned(),
+        };
+        self.append(&entry)
+    }
+
+    /// Append `entry` as one line, rotating first if it would overflow the
+    /// active file.
+    pub fn append(&mut self, entry: &JournalEntry) -> Result<()> {
+        let mut line =
+            serde_json::to_vec(entry).map_err(|e| ZubridgeError::Serialization(e.to_string()))?;
+        line.push(b'\n');
+        if self.len > 0 && self.len + line.len() as u64 > self.config.max_bytes {
+            self.rotate()?;
+        }
+        self.file
+            .write_all(&line)
+            .and_then(|()| self.file.flush())
+            .map_err(|e| journal_error(&self.config.path, e))?;
+        self.len += line.len() as u64;
+        Ok(())
+    }
+
+    /// The journal's files, oldest first: rotated files, then the active
+    /// one. Replaying them in this order rebuilds the full history kept.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = (1..=self.config.max_files)
+            .rev()
+            .map(|n| rotated_path(&self.config.path, n))
+            .filter(|path| path.exists())
+            .collect();
+        files.push(self.config.path.clone());
+        files
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        let path = &self.config.path;
+        if self.config.max_files == 0 {
+            self.file = File::create(path).map_err(|e| journal_error(path, e))?;
+        } else {
+            for n in (1..self.config.max_files).rev() {
+                let from = rotated_path(path, n);
+                if from.exists() {
+                    let to = rotated_path(path, n + 1);
+                    fs::rename(&from, &to).map_err(|e| journal_error(&from, e))?;
+                }
+            }
+            fs::rename(path, rotated_path(path, 1)).map_err(|e| journal_error(path, e))?;
+            self.file = open_append(path)?;
+        }
+        self.len = 0;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| journal_error(path, e))
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{n}"));
+    PathBuf::from(rotated)
+}
+
+fn journal_error(path: &Path, err: std::io::Error) -> ZubridgeError {
+    ZubridgeError::Persistence(format!("journal {}: {err}", path.display()))
+}
+
+// ── Replay ────────────────────────────────────────────────────────────────────
+
+/// Result of a [`replay`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplaySummary {
+    /// Actions dispatched to the state manager.
+    pub applied: usize,
+    /// Entries recorded as rejected, and undos or redos with nothing to
+    /// restore, which left state unchanged and so were not replayed.
+    pub skipped: usize,
+    /// True if the log ended in a partial line (a crash mid-write), which
+    /// was ignored.
+    pub truncated: bool,
+}
+
+/// Dispatch every accepted action in `log` to `manager`, in order. Undos and
+/// redos are replayed by hydrating the state they restored, so `manager`
+/// must support `hydrate` if the journal contains any.
+///
+/// `manager` should start from the state the journal started from (usually
+/// its initial state). Fails if an action recorded as accepted is now
+/// rejected, or if a line other than the last is malformed.
+pub fn replay<R: BufRead, S: TryStateManager + ?Sized>(
+    log: R,
+    manager: &mut S,
+) -> Result<ReplaySummary> {
+    let mut summary = ReplaySummary::default();
+    let mut lines = log.lines().enumerate().peekable();
+    while let Some((index, line)) = lines.next() {
+        let line = line.map_err(|e| ZubridgeError::Persistence(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: JournalEntry = match serde_json::from_str(&line) {
+            Ok(entry) => entry,
+            Err(_) if lines.peek().is_none() => {
+                summary.truncated = true;
+                break;
+            }
+            Err(err) => {
+                return Err(ZubridgeError::Serialization(format!(
+                    "journal line {}: {err}",
+                    index + 1
+                )))
+            }
+        };
+        let is_history = HistoryOp::from_action_type(&entry.action.action_type).is_some();
+        if entry.error.is_some() || (is_history && entry.hydrated.is_none()) {
+            summary.skipped += 1;
+            continue;
+        }
+        let action_id = entry.action.id.clone().unwrap_or_default();
+        let replayed = match entry.hydrated {
+            Some(state) => manager.hydrate(state),
+            None => manager
+                .try_dispatch_action(entry.action.to_legacy_json())
+                .map(|_| ()),
+        };
+        replayed.map_err(|err| {
+            ZubridgeError::ActionProcessing(format!(
+                "replaying action {action_id} (line {}): {err}",
+                index + 1
+            ))
+        })?;
+        summary.applied += 1;
+    }
+    Ok(summary)
+}
+
+/// [`replay`] each of `files` in order — e.g. [`ActionJournal::files`].
+pub fn replay_files<S: TryStateManager + ?Sized>(
+    files: &[PathBuf],
+    manager: &mut S,
+) -> Result<(JsonValue, ReplaySummary)> {
+    let mut total = ReplaySummary::default();
+    for path in files {
+        let file = File::open(path).map_err(|e| journal_error(path, e))?;
+        let summary = replay(BufReader::new(file), manager)?;
+        total.applied += summary.applied;
+        total.skipped += summary.skipped;
+        total.truncated |= summary.truncated;
+    }
+    Ok((manager.get_initial_state(), total))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StateManager;
+    use serde_json::json;
+
+    struct Counter(i64);
+
+    impl TryStateManager for Counter {
+        fn get_initial_state(&self) -> JsonValue {
+            json!({ "counter": self.0 })
+        }
+
+        fn try_dispatch_action(&mut self, action: JsonValue) -> Result<JsonValue> {
+            match action["type"].as_str() {
+                Some("INC") => self.0 += action["payload"].as_i64().unwrap_or(1),
+                _ => return Err(ZubridgeError::ActionProcessing("unknown".into())),
+            }
+            Ok(self.get_initial_state())
+        }
+    }
+
+    fn action(action_type: &str, payload: i64) -> ZubridgeAction {
+        serde_json::from_value(json!({
+            "id": format!("{action_type}-{payload}"),
+            "action_type": action_type,
+            "payload": payload,
+            "thunk_parent_id": "t1",
+        }))
+        .unwrap()
+    }
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("zubridge-journal-{}", uuid::Uuid::new_v4()))
+            .join("actions.ndjson")
+    }
+
+    fn journal_with(actions: &[(&str, i64)], config: JournalConfig) -> ActionJournal {
+        let mut journal = ActionJournal::open(config).unwrap();
+        let mut manager = Counter(0);
+        for (action_type, payload) in actions {
+            let action = action(action_type, *payload);
+            let result = manager.try_dispatch_action(action.to_legacy_json());
+            journal
+                .record(&action, "main", result.as_ref().map(|_| ()), None)
+                .unwrap();
+        }
+        journal
+    }
+
+    #[test]
+    fn replay_rebuilds_the_recorded_state() {
+        let path = temp_path();
+        let journal = journal_with(
+            &[("INC", 2), ("BOGUS", 1), ("INC", 5)],
+            JournalConfig::new(&path),
+        );
+        let text = fs::read_to_string(&path).unwrap();
+        let first: JournalEntry = serde_json::from_str(text.lines().next().unwrap()).unwrap();
+        assert_eq!(first.source_label, "main");
+        assert_eq!(first.thunk_parent_id.as_deref(), Some("t1"));
+
+        let (state, summary) = replay_files(&journal.files(), &mut Counter(0)).unwrap();
+        assert_eq!(state, json!({ "counter": 7 }));
+        assert_eq!(summary.applied, 2);
+        assert_eq!(summary.skipped, 1);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn truncated_tail_is_ignored() {
+        let path = temp_path();
+        journal_with(&[("INC", 1)], JournalConfig::new(&path));
+        let mut text = fs::read_to_string(&path).unwrap();
+        text.push_str("{\"timestamp_ms\":1,\"source");
+        let summary = replay(text.as_bytes(), &mut Counter(0)).unwrap();
+        assert_eq!(summary.applied, 1);
+        assert!(summary.truncated);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn rotation_keeps_history_in_order() {
+        let path = temp_path();
+        let config = JournalConfig {
+            max_bytes: 1,
+            max_files: 5,
+            ..JournalConfig::new(&path)
+        };
+        let journal = journal_with(&[("INC", 1), ("INC", 10), ("INC", 100)], config);
+        let files = journal.files();
+        assert_eq!(files.len(), 3);
+        assert!(files[0].ends_with("actions.ndjson.2"));
+
+        let (state, _) = replay_files(&files, &mut Counter(0)).unwrap();
+        assert_eq!(state, json!({ "counter": 111 }));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn rotation_drops_files_beyond_the_limit() {
+        let path = temp_path();
+        let config = JournalConfig {
+            max_bytes: 1,
+            max_files: 1,
+            ..JournalConfig::new(&path)
+        };
+        let journal = journal_with(&[("INC", 1), ("INC", 10), ("INC", 100)], config);
+        let (state, _) = replay_files(&journal.files(), &mut Counter(0)).unwrap();
+        assert_eq!(state, json!({ "counter": 110 }));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn replay_works_with_plain_state_managers() {
+        struct Echo(usize);
+        impl StateManager for Echo {
+            fn get_initial_state(&self) -> JsonValue {
+                json!(self.0)
+            }
+            fn dispatch_action(&mut self, _action: JsonValue) -> JsonValue {
+                self.0 += 1;
+                json!(self.0)
+            }
+        }
+        let path = temp_path();
+        let journal = journal_with(&[("INC", 1), ("INC", 2)], JournalConfig::new(&path));
+        let (state, _) = replay_files(&journal.files(), &mut Echo(0)).unwrap();
+        assert_eq!(state, json!(2));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -1,16 +1,32 @@
+pub mod access;
 pub mod action;
 pub mod batching;
+pub mod broadcast;
 pub mod deltas;
+pub mod devtools;
 pub mod emit;
 pub mod error;
+pub mod handlers;
+pub mod history;
+pub mod journal;
+pub mod migration;
 pub mod models;
 pub mod orchestration;
+pub mod permission;
+pub mod persistence;
+pub mod slice;
+pub mod snapshot;
 pub mod state;
 pub mod subscription;
+pub mod throttle;
 pub mod thunk;
+pub mod typed;
 
 #[cfg(any(feature = "tauri", feature = "uniffi", feature = "napi"))]
 pub mod wrappers;
 
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
 pub use error::{Result, ZubridgeError};
 pub use models::*;
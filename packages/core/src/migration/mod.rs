@@ -0,0 +1,150 @@
+//! Versioned state-schema migrations.
+//!
+//! A [`MigrationRegistry`] holds one step per schema version. The step
+//! registered for version `n` upgrades a state at version `n - 1` to version
+//! `n`; the highest registered version is the current schema. Persisted
+//! snapshots record the version they were saved at, and
+//! [`PersistedStateManager`](crate::persistence::PersistedStateManager) runs
+//! the missing steps, in order, before hydrating.
+//!
+//! ```
+//! use serde_json::json;
+//! use zubridge_core::migration::MigrationRegistry;
+//!
+//! let migrations = MigrationRegistry::new().step(1, |mut state| {
+//!     // v1 renamed `count` to `counter`.
+//!     if let Some(count) = state.as_object_mut().and_then(|s| s.remove("count")) {
+//!         state["counter"] = count;
+//!     }
+//!     Ok(state)
+//! });
+//! assert_eq!(migrations.current_version(), 1);
+//! assert_eq!(
+//!     migrations.migrate(json!({ "count": 3 }), 0).unwrap(),
+//!     json!({ "counter": 3 })
+//! );
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::error::{Result, ZubridgeError};
+use crate::models::JsonValue;
+
+/// Upgrades a state by one schema version.
+pub type MigrationStep = fn(JsonValue) -> Result<JsonValue>;
+
+/// Ordered migration steps, keyed by the schema version they produce.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationRegistry {
+    steps: BTreeMap<u32, MigrationStep>,
+}
+
+impl MigrationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the step that upgrades a version `version - 1` state to
+    /// `version`. Registering a version twice replaces the earlier step.
+    pub fn step(mut self, version: u32, step: MigrationStep) -> Self {
+        self.steps.insert(version, step);
+        self
+    }
+
+    /// The schema version new snapshots are saved at: the highest registered
+    /// version, or 0 with no steps.
+    pub fn current_version(&self) -> u32 {
+        self.steps.keys().next_back().copied().unwrap_or(0)
+    }
+
+    /// Upgrade `state`, saved at schema version `from`, to the current
+    /// version. Fails with [`ZubridgeError::Migration`] if a step fails or
+    /// `from` is newer than the current version (a snapshot written by a
+    /// later app version).
+    pub fn migrate(&self, state: JsonValue, from: u32) -> Result<JsonValue> {
+        let to = self.current_version();
+        if from > to {
+            return Err(ZubridgeError::Migration {
+                from,
+                to,
+                message: "snapshot is newer than the current schema".to_string(),
+            });
+        }
+        let mut state = state;
+        for (&version, step) in self.steps.range(from + 1..) {
+            state = step(state).map_err(|err| ZubridgeError::Migration {
+                from: version - 1,
+                to: version,
+                message: err.to_string(),
+            })?;
+        }
+        Ok(state)
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rename_count(mut state: JsonValue) -> Result<JsonValue> {
+        if let Some(count) = state.as_object_mut().and_then(|s| s.remove("count")) {
+            state["counter"] = count;
+        }
+        Ok(state)
+    }
+
+    fn add_theme(mut state: JsonValue) -> Result<JsonValue> {
+        state["theme"] = json!("dark");
+        Ok(state)
+    }
+
+    fn reject(_state: JsonValue) -> Result<JsonValue> {
+        Err(ZubridgeError::StateError("unsupported layout".into()))
+    }
+
+    #[test]
+    fn runs_only_the_missing_steps_in_order() {
+        let migrations = MigrationRegistry::new()
+            .step(2, add_theme)
+            .step(1, rename_count);
+        assert_eq!(migrations.current_version(), 2);
+        assert_eq!(
+            migrations.migrate(json!({ "count": 1 }), 0).unwrap(),
+            json!({ "counter": 1, "theme": "dark" })
+        );
+        assert_eq!(
+            migrations.migrate(json!({ "count": 1 }), 1).unwrap(),
+            json!({ "count": 1, "theme": "dark" })
+        );
+        assert_eq!(
+            migrations.migrate(json!({ "counter": 1 }), 2).unwrap(),
+            json!({ "counter": 1 })
+        );
+    }
+
+    #[test]
+    fn failing_step_names_the_versions() {
+        let migrations = MigrationRegistry::new()
+            .step(1, rename_count)
+            .step(2, reject);
+        let err = migrations.migrate(json!({}), 0).unwrap_err();
+        assert!(matches!(
+            err,
+            ZubridgeError::Migration { from: 1, to: 2, .. }
+        ));
+        assert!(err.to_string().contains("unsupported layout"));
+    }
+
+    #[test]
+    fn newer_snapshots_are_rejected() {
+        let migrations = MigrationRegistry::new().step(1, rename_count);
+        let err = migrations.migrate(json!({}), 3).unwrap_err();
+        assert!(matches!(
+            err,
+            ZubridgeError::Migration { from: 3, to: 1, .. }
+        ));
+    }
+}
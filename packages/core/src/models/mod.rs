@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
+use std::sync::Arc;
 
 pub use serde_json::Value as JsonValue;
 
@@ -50,20 +51,92 @@ impl ZubridgeAction {
 }
 
 /// Options for the Zubridge plugin.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct ZubridgeOptions {
     /// The event name used for state updates. Defaults to `zubridge://state-update`.
     pub event_name: String,
+    /// Key-based access control applied to actions and thunks dispatched by
+    /// webviews. See [`AccessControl`](crate::access::AccessControl).
+    pub access_control: crate::access::AccessControl,
+    /// Host-defined policy deciding what each webview may dispatch, whether
+    /// it may register thunks, and whether it may read state. `None` allows
+    /// everything.
+    pub permission_policy: Option<Arc<dyn crate::permission::PermissionPolicy>>,
+    /// Encoding for state-update deltas. Defaults to [`DeltaMode::TopLevel`].
+    pub delta_mode: DeltaMode,
+    /// Minimum interval between state updates sent to one webview. Changes
+    /// within the interval are merged into a single update sent when it
+    /// elapses. `None` (the default) sends every change immediately. See
+    /// [`UpdateThrottle`](crate::throttle::UpdateThrottle).
+    pub update_throttle: Option<std::time::Duration>,
+    /// Maximum number of unacknowledged state updates per webview. A webview
+    /// that reaches it stops receiving updates until it acks again, then
+    /// gets a full-state resync. `None` (the default) never stalls.
+    pub max_in_flight: Option<usize>,
+    /// How long a state update may go unacknowledged before it is treated
+    /// as lost and the webview is resynced with the full state. `None` (the
+    /// default) waits indefinitely.
+    pub ack_timeout: Option<std::time::Duration>,
+    /// Append every executed action to an on-disk journal that
+    /// [`replay`](crate::journal::replay) can rebuild state from. `None` (the
+    /// default) keeps no journal.
+    pub journal: Option<crate::journal::JournalConfig>,
+    /// Keep an undo/redo history of committed changes, driven by the
+    /// built-in [`UNDO_ACTION_TYPE`](crate::history::UNDO_ACTION_TYPE) and
+    /// [`REDO_ACTION_TYPE`](crate::history::REDO_ACTION_TYPE) actions.
+    /// `None` (the default) keeps no history.
+    pub history: Option<crate::history::HistoryConfig>,
+    /// Record the latest this many actions, with the state after each, in a
+    /// [`DevtoolsSession`](crate::devtools::DevtoolsSession) for
+    /// time-travel debugging. `None` (the default) records nothing.
+    pub devtools_capacity: Option<usize>,
+    /// Stream the devtools session to the Redux DevTools app and accept its
+    /// dispatch and jump requests. See
+    /// [`RemoteDevtools`](crate::devtools::remote::RemoteDevtools). `None`
+    /// (the default) starts no bridge.
+    #[cfg(feature = "devtools")]
+    pub remote_devtools: Option<crate::devtools::remote::RemoteDevtoolsConfig>,
 }
 
 impl Default for ZubridgeOptions {
     fn default() -> Self {
         Self {
             event_name: "zubridge://state-update".to_string(),
+            access_control: crate::access::AccessControl::default(),
+            permission_policy: None,
+            delta_mode: DeltaMode::default(),
+            update_throttle: None,
+            max_in_flight: None,
+            ack_timeout: None,
+            journal: None,
+            history: None,
+            devtools_capacity: None,
+            #[cfg(feature = "devtools")]
+            remote_devtools: None,
         }
     }
 }
 
+impl Debug for ZubridgeOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("ZubridgeOptions");
+        debug
+            .field("event_name", &self.event_name)
+            .field("access_control", &self.access_control)
+            .field("permission_policy", &self.permission_policy.is_some())
+            .field("delta_mode", &self.delta_mode)
+            .field("update_throttle", &self.update_throttle)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("ack_timeout", &self.ack_timeout)
+            .field("journal", &self.journal)
+            .field("history", &self.history)
+            .field("devtools_capacity", &self.devtools_capacity);
+        #[cfg(feature = "devtools")]
+        debug.field("remote_devtools", &self.remote_devtools);
+        debug.finish()
+    }
+}
+
 /// Result of processing an action — mirrors the TS `ProcessResult` shape.
 #[derive(Debug, Clone, Serialize)]
 pub struct ProcessResult {
@@ -94,6 +167,85 @@ pub trait StateManager: Send + Sync + 'static {
 
     /// Apply an action to the state and return the new state.
     fn dispatch_action(&mut self, action: JsonValue) -> JsonValue;
+
+    /// Top-level keys changed by the most recent `dispatch_action`, if the
+    /// manager tracks them. See [`TryStateManager::changed_keys`].
+    fn changed_keys(&self) -> Option<Vec<String>> {
+        None
+    }
+}
+
+/// A state manager whose dispatch can fail.
+///
+/// Every [`StateManager`] is a `TryStateManager` through a blanket impl that
+/// never fails, so implement this trait directly only when actions can be
+/// rejected (unknown type, invalid payload, reducer error).
+///
+/// # Contract
+///
+/// In addition to the [`StateManager`] contract, a dispatch that returns `Err`
+/// **must** leave the state unchanged. Callers skip the state broadcast for a
+/// failed action and report the error back to the dispatching window —
+/// typically [`ZubridgeError::ActionProcessing`](crate::ZubridgeError::ActionProcessing),
+/// or a more specific variant such as
+/// [`ZubridgeError::InvalidAction`](crate::ZubridgeError::InvalidAction).
+pub trait TryStateManager: Send + Sync + 'static {
+    /// Get the current state of the app. See [`StateManager::get_initial_state`].
+    fn get_initial_state(&self) -> JsonValue;
+
+    /// Apply an action to the state and return the new state, or an error if
+    /// the action was rejected.
+    fn try_dispatch_action(&mut self, action: JsonValue) -> crate::Result<JsonValue>;
+
+    /// Action types (or patterns such as `COUNTER:*`) this manager handles,
+    /// for renderers to validate or autocomplete against. `None` — the
+    /// default — means the manager accepts arbitrary action types.
+    fn action_types(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Top-level keys changed by the most recent successful dispatch.
+    ///
+    /// When this returns `Some`, broadcasts only compare those keys against
+    /// each webview's baseline and skip webviews subscribed to none of them,
+    /// so it must list every key whose value changed (listing unchanged keys
+    /// is harmless). `None` — the default — means the manager doesn't track
+    /// changes and every broadcast diffs the full state.
+    fn changed_keys(&self) -> Option<Vec<String>> {
+        None
+    }
+
+    /// Merge previously persisted state into the current state — called
+    /// once by [`PersistedStateManager`](crate::persistence::PersistedStateManager)
+    /// on start. `state` holds only the persisted top-level keys.
+    ///
+    /// The default returns [`ZubridgeError::HydrateUnsupported`](crate::ZubridgeError::HydrateUnsupported),
+    /// so a manager that can't restore state fails visibly rather than
+    /// silently keeping its initial state.
+    fn hydrate(&mut self, _state: JsonValue) -> crate::Result<()> {
+        Err(crate::ZubridgeError::HydrateUnsupported)
+    }
+
+    /// True if [`hydrate`](Self::hydrate) restores state. Undo/redo history
+    /// needs it, and refuses to undo against a manager that returns `false`
+    /// — the default.
+    fn supports_hydrate(&self) -> bool {
+        false
+    }
+}
+
+impl<T: StateManager> TryStateManager for T {
+    fn get_initial_state(&self) -> JsonValue {
+        StateManager::get_initial_state(self)
+    }
+
+    fn try_dispatch_action(&mut self, action: JsonValue) -> crate::Result<JsonValue> {
+        Ok(self.dispatch_action(action))
+    }
+
+    fn changed_keys(&self) -> Option<Vec<String>> {
+        StateManager::changed_keys(self)
+    }
 }
 
 /// Payload sent to the renderer over the state-update event.
@@ -117,12 +269,29 @@ pub struct StateUpdatePayload {
 /// Delta describing what changed in the state since the previous update.
 #[derive(Debug, Clone, Serialize, Default)]
 pub struct StateDelta {
-    /// Top-level keys whose values changed (full new value attached)
+    /// Top-level keys (or, for nested subscriptions, dotted paths) whose
+    /// values changed (full new value attached)
     pub changed: serde_json::Map<String, JsonValue>,
-    /// Top-level keys that were removed
+    /// Top-level keys (or dotted paths) that were removed
     pub removed: Vec<String>,
 }
 
+/// How [`DeltaCalculator`](crate::deltas::DeltaCalculator) encodes changes.
+///
+/// Both modes produce a [`StateDelta`]; they differ only in how deep its
+/// `changed` and `removed` keys reach, so renderers need no configuration.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DeltaMode {
+    /// One entry per changed top-level key (or subscribed path), carrying its
+    /// whole new value.
+    #[default]
+    TopLevel,
+    /// One entry per changed leaf, keyed by its dotted path (`items.3.done`),
+    /// so a one-item edit to a large list resends only that item. A changed
+    /// value is sent whole when its paths would serialize larger than it.
+    Deep,
+}
+
 /// Source attribution for a state update.
 #[derive(Debug, Clone, Serialize, Default)]
 pub struct UpdateSource {
@@ -130,6 +299,32 @@ pub struct UpdateSource {
     pub action_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thunk_id: Option<String>,
+    /// Every action covered by a throttled update, oldest first. Empty when
+    /// the update covers a single action (see `action_id`).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub action_ids: Vec<String>,
+}
+
+impl UpdateSource {
+    /// Fold a later update's provenance into this one, for an update that
+    /// covers both. The latest action and thunk win; every covered action id
+    /// is kept in `action_ids`.
+    pub fn absorb(&mut self, later: UpdateSource) {
+        if self.action_ids.is_empty() {
+            self.action_ids.extend(self.action_id.clone());
+        }
+        if later.action_ids.is_empty() {
+            self.action_ids.extend(later.action_id.clone());
+        } else {
+            self.action_ids.extend(later.action_ids);
+        }
+        if later.action_id.is_some() {
+            self.action_id = later.action_id;
+        }
+        if later.thunk_id.is_some() {
+            self.thunk_id = later.thunk_id;
+        }
+    }
 }
 
 // ---------- Command payloads / responses ----------
@@ -248,3 +443,16 @@ pub struct UnsubscribeResult {
 pub struct GetWindowSubscriptionsResult {
     pub keys: Vec<String>,
 }
+
+#[derive(Serialize, Debug)]
+pub struct GetActionTypesResult {
+    /// `None` when the state manager doesn't declare its action types.
+    pub action_types: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DevtoolsJumpArgs {
+    /// [`DevtoolsEntry::index`](crate::devtools::DevtoolsEntry::index) of the
+    /// entry to view.
+    pub index: u64,
+}
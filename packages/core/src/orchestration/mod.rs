@@ -8,11 +8,61 @@
 //! - `packages/electron/src/main/mainThunkProcessor.ts`
 
 use crate::action::{ActionScheduler, EnqueueResult, QueuedAction};
+use crate::devtools::DevtoolsSession;
 use crate::error::{Result, ZubridgeError};
+use crate::history::{HistoryOp, UndoHistory};
+use crate::journal::ActionJournal;
 use crate::models::{JsonValue, StateManager, ZubridgeAction};
 use crate::state::StateManagerHandle;
 use crate::thunk::{ThunkEvent, ThunkManager};
 
+// ── Public types ──────────────────────────────────────────────────────────────
+
+/// Source label recorded for actions the host process dispatches itself, as
+/// opposed to actions relayed from a webview. Mirrors the Electron main
+/// process's reserved window id `0`.
+pub const HOST_SOURCE_LABEL: &str = "__zubridge_host__";
+
+/// An action the queue has executed, with the state it produced.
+///
+/// Returned for every action drained from the queue so platform wrappers can
+/// broadcast the resulting state and resolve whoever is awaiting the action
+/// (e.g. a pending `dispatch_action` IPC call). A rejected action carries its
+/// error in `result`; the state manager left state unchanged, so there is
+/// nothing to broadcast for it.
+#[derive(Debug)]
+pub struct ExecutedAction {
+    pub action: ZubridgeAction,
+    pub source_label: String,
+    pub result: Result<JsonValue>,
+    /// Keys the action changed, if the state manager reports them (see
+    /// [`TryStateManager::changed_keys`](crate::models::TryStateManager::changed_keys)).
+    /// Always `None` for a rejected action.
+    pub changed_keys: Option<Vec<String>>,
+}
+
+/// Outcome of [`ActionQueueManager::dispatch`].
+#[derive(Debug)]
+pub enum DispatchOutcome {
+    /// The action ran straight away and produced `state`. `drained` holds any
+    /// previously-queued actions that became eligible and ran after it.
+    Executed {
+        action_id: String,
+        state: JsonValue,
+        /// Keys the action changed, if the state manager reports them.
+        changed_keys: Option<Vec<String>>,
+        drained: Vec<ExecutedAction>,
+    },
+    /// The action is waiting behind an active thunk and will be returned from
+    /// a later [`ActionQueueManager::on_thunk_complete`] drain. `evicted` is
+    /// the queued action dropped to make room for it on overflow, if any —
+    /// it will never run.
+    Queued {
+        action_id: String,
+        evicted: Option<QueuedAction>,
+    },
+}
+
 // ── ActionQueueManager ────────────────────────────────────────────────────────
 
 /// Central orchestrator for action dispatch and thunk lifecycle.
@@ -28,26 +78,113 @@ use crate::thunk::{ThunkEvent, ThunkManager};
 /// (Promise chains, setTimeout) is handled at the platform-wrapper level
 /// (Tauri async command handlers, NAPI ThreadsafeFunction). The core only
 /// decides *ordering* and *eligibility* for execution.
+///
+/// A manager built via [`Default`] has no state manager yet; thunks can be
+/// registered but executing an action fails with
+/// [`ZubridgeError::StateManagerMissing`] until [`set_state_handle`] is called.
+///
+/// With a journal attached (see [`set_journal`]), every executed action is
+/// recorded along with its result. With a history attached (see
+/// [`set_history`]), committed changes can be undone and redone. With a
+/// devtools session attached (see [`set_devtools`]), committed actions are
+/// kept for time-travel debugging.
+///
+/// [`dispatch`]: ActionQueueManager::dispatch
+/// [`set_state_handle`]: ActionQueueManager::set_state_handle
+/// [`set_journal`]: ActionQueueManager::set_journal
+/// [`set_history`]: ActionQueueManager::set_history
+/// [`set_devtools`]: ActionQueueManager::set_devtools
+#[derive(Default)]
 pub struct ActionQueueManager {
     scheduler: ActionScheduler,
     thunk_manager: ThunkManager,
-    state_handle: StateManagerHandle,
+    state_handle: Option<StateManagerHandle>,
+    journal: Option<ActionJournal>,
+    history: Option<UndoHistory>,
+    devtools: Option<DevtoolsSession>,
 }
 
 impl ActionQueueManager {
     pub fn new(state_manager: impl StateManager + 'static) -> Self {
-        Self {
-            scheduler: ActionScheduler::new(),
-            thunk_manager: ThunkManager::new(),
-            state_handle: crate::state::new_handle(state_manager),
-        }
+        Self::with_state_handle(crate::state::new_handle(state_manager))
     }
 
     pub fn with_state_handle(state_handle: StateManagerHandle) -> Self {
         Self {
             scheduler: ActionScheduler::new(),
             thunk_manager: ThunkManager::new(),
-            state_handle,
+            state_handle: Some(state_handle),
+            journal: None,
+            history: None,
+            devtools: None,
+        }
+    }
+
+    /// Attach (or replace) the state manager used to execute actions. Used by
+    /// hosts that register their state manager after the queue is created.
+    pub fn set_state_handle(&mut self, state_handle: StateManagerHandle) {
+        self.state_handle = Some(state_handle);
+        self.check_history_support();
+    }
+
+    /// Attach (or, with `None`, detach) the journal executed actions are
+    /// recorded to.
+    pub fn set_journal(&mut self, journal: Option<ActionJournal>) {
+        self.journal = journal;
+    }
+
+    pub fn journal(&self) -> Option<&ActionJournal> {
+        self.journal.as_ref()
+    }
+
+    /// Attach (or, with `None`, detach) an undo/redo history. While one is
+    /// attached, [`UNDO_ACTION_TYPE`](crate::history::UNDO_ACTION_TYPE) and
+    /// [`REDO_ACTION_TYPE`](crate::history::REDO_ACTION_TYPE) actions are
+    /// handled by the queue and always run immediately. Its baseline is the
+    /// state when the next action runs.
+    ///
+    /// Undoing needs a state manager that can restore state (see
+    /// [`TryStateManager::supports_hydrate`](crate::models::TryStateManager::supports_hydrate));
+    /// against any other, history actions fail with
+    /// [`ZubridgeError::HydrateUnsupported`] and nothing is recorded.
+    pub fn set_history(&mut self, history: Option<UndoHistory>) {
+        self.history = history;
+        self.check_history_support();
+    }
+
+    pub fn history(&self) -> Option<&UndoHistory> {
+        self.history.as_ref()
+    }
+
+    /// Attach (or, with `None`, detach) a devtools session. Detaching a
+    /// paused session resumes dispatch; any actions it held are run by the
+    /// next drain.
+    pub fn set_devtools(&mut self, devtools: Option<DevtoolsSession>) {
+        self.devtools = devtools;
+    }
+
+    pub fn devtools(&self) -> Option<&DevtoolsSession> {
+        self.devtools.as_ref()
+    }
+
+    /// Jump the devtools session to entry `index` and return that entry's
+    /// state. Until [`devtools_resume`](Self::devtools_resume), dispatches
+    /// fail with [`ZubridgeError::Devtools`] and queued actions stay queued.
+    pub fn devtools_jump(&mut self, index: u64) -> Result<JsonValue> {
+        self.devtools
+            .as_mut()
+            .ok_or_else(|| ZubridgeError::Devtools("no devtools session".to_string()))?
+            .jump_to(index)
+    }
+
+    /// Return the devtools session to live state and run any queued actions
+    /// that became eligible while it was paused.
+    pub fn devtools_resume(&mut self) -> Vec<ExecutedAction> {
+        let resumed = self.devtools.as_mut().is_some_and(DevtoolsSession::resume);
+        if resumed {
+            self.drain_queue()
+        } else {
+            Vec::new()
         }
     }
 
@@ -55,24 +192,46 @@ impl ActionQueueManager {
 
     /// Dispatch `action` from `source_label`.
     ///
-    /// If the action can execute immediately it is processed and the new state
-    /// is returned. Otherwise the action is queued and `Ok(None)` is returned;
-    /// the caller receives the state update via the platform's event system
-    /// (Tauri `emit`, NAPI callback, etc.).
+    /// If the action can execute immediately it is processed and
+    /// [`DispatchOutcome::Executed`] carries the new state; a rejected action
+    /// returns the state manager's error instead. Otherwise the
+    /// action is queued behind the active thunk; its state is returned from
+    /// the [`on_thunk_complete`](Self::on_thunk_complete) call that drains it.
+    /// An id is assigned if the action has none, so callers can correlate the
+    /// queued action with its eventual [`ExecutedAction`].
     pub fn dispatch(
         &mut self,
-        action: ZubridgeAction,
+        mut action: ZubridgeAction,
         source_label: String,
-    ) -> Result<Option<JsonValue>> {
+    ) -> Result<DispatchOutcome> {
+        if let Some(devtools) = &self.devtools {
+            devtools.check_dispatch()?;
+        }
+        let action_id = action
+            .id
+            .get_or_insert_with(|| uuid::Uuid::new_v4().to_string())
+            .clone();
+        if self.history.is_some() && HistoryOp::from_action_type(&action.action_type).is_some() {
+            action.immediate = Some(true);
+        }
         let ctx = self.thunk_manager.scheduler_context();
         match self.scheduler.enqueue(action, source_label, &ctx) {
             EnqueueResult::ExecuteNow(queued) => {
-                let new_state = self.execute_action(queued)?;
+                let (state, changed_keys) =
+                    self.execute_action(&queued.action, &queued.source_label)?;
                 // After any execution, drain any newly unblocked queue items.
-                self.drain_queue()?;
-                Ok(Some(new_state))
+                let drained = self.drain_queue();
+                Ok(DispatchOutcome::Executed {
+                    action_id,
+                    state,
+                    changed_keys,
+                    drained,
+                })
             }
-            EnqueueResult::Queued => Ok(None),
+            EnqueueResult::Queued => Ok(DispatchOutcome::Queued {
+                action_id,
+                evicted: self.scheduler.take_evicted(),
+            }),
             EnqueueResult::Rejected(e) => Err(e),
         }
     }
@@ -80,7 +239,7 @@ impl ActionQueueManager {
     /// Called by the platform layer when a thunk completes (or fails).
     ///
     /// Drains any queued actions that became eligible and returns both the
-    /// lifecycle events and the new state produced by each drained action.
+    /// lifecycle events and each drained action with the state it produced.
     /// Platform wrappers must emit the returned states to subscribers —
     /// `StateManager` has no subscriber mechanism, so this is the only path
     /// through which those updates become visible after thunk completion.
@@ -88,7 +247,7 @@ impl ActionQueueManager {
         &mut self,
         thunk_id: &str,
         error: Option<String>,
-    ) -> Result<(Vec<ThunkEvent>, Vec<JsonValue>)> {
+    ) -> Result<(Vec<ThunkEvent>, Vec<ExecutedAction>)> {
         let (_, events) = match self.thunk_manager.complete(thunk_id, error) {
             Ok(result) => result,
             Err(_) => return Ok((Vec::new(), Vec::new())), // Thunk not found — ignore.
@@ -96,9 +255,9 @@ impl ActionQueueManager {
 
         // Drain unconditionally: child-thunk completions remove non-concurrent
         // tasks that may have been blocking already-queued actions.
-        let states = self.drain_queue()?;
+        let drained = self.drain_queue();
 
-        Ok((events, states))
+        Ok((events, drained))
     }
 
     /// Register a thunk.
@@ -132,6 +291,13 @@ impl ActionQueueManager {
         self.thunk_manager.execute_thunk(thunk_id)
     }
 
+    /// Drop every thunk owned by `source_label` (e.g. on webview close) and
+    /// drain any queued actions that were only blocked by those thunks.
+    pub fn drop_label(&mut self, source_label: &str) -> Result<Vec<ExecutedAction>> {
+        self.thunk_manager.drop_label(source_label);
+        Ok(self.drain_queue())
+    }
+
     pub fn thunk_manager(&self) -> &ThunkManager {
         &self.thunk_manager
     }
@@ -150,26 +316,93 @@ impl ActionQueueManager {
 
     // ── Private helpers ───────────────────────────────────────────────────────
 
-    fn execute_action(&mut self, queued: QueuedAction) -> Result<JsonValue> {
-        let action = queued.action;
-        let action_json = action.to_legacy_json();
+    /// Log when a history is attached to a state manager that can't restore.
+    fn check_history_support(&self) {
+        let unsupported = self
+            .state_handle
+            .as_ref()
+            .is_some_and(|handle| !supports_hydrate(handle));
+        if self.history.is_some() && unsupported {
+            log::error!(
+                "zubridge: undo/redo history is disabled; the state manager does not support hydrate"
+            );
+        }
+    }
 
-        let new_state = {
-            let mut guard = self
-                .state_handle
-                .lock()
-                .map_err(|e| ZubridgeError::StateError(e.to_string()))?;
-            guard.dispatch_action(action_json)
+    fn execute_action(
+        &mut self,
+        action: &ZubridgeAction,
+        source_label: &str,
+    ) -> Result<(JsonValue, Option<Vec<String>>)> {
+        let handle = self
+            .state_handle
+            .clone()
+            .ok_or(ZubridgeError::StateManagerMissing)?;
+        let group = self.history_group(action);
+        let restorable = self.history.is_some() && supports_hydrate(&handle);
+        // The state an undo or redo hydrated, journaled so replay can
+        // restore it without the history.
+        let mut hydrated = None;
+        let result = match self.history.as_mut() {
+            Some(history) => match HistoryOp::from_action_type(&action.action_type) {
+                Some(_) if !restorable => Err(ZubridgeError::HydrateUnsupported),
+                Some(op) => apply_history(&handle, history, op).map(|(state, keys, restored)| {
+                    hydrated = restored;
+                    (state, keys)
+                }),
+                None if !restorable => {
+                    crate::state::dispatch_tracked(&handle, action.to_legacy_json())
+                }
+                None => {
+                    if !history.has_baseline() {
+                        history.reset(&crate::state::read_state(&handle)?);
+                    }
+                    let result = crate::state::dispatch_tracked(&handle, action.to_legacy_json());
+                    if let Ok((state, _)) = &result {
+                        history.record(group.as_deref(), state);
+                    }
+                    result
+                }
+            },
+            None => crate::state::dispatch_tracked(&handle, action.to_legacy_json()),
         };
+        if let (Some(devtools), Ok((state, _))) = (self.devtools.as_mut(), &result) {
+            devtools.record(action, source_label, state);
+        }
+        if let Some(journal) = self.journal.as_mut() {
+            // A journal write failure must not fail the action itself.
+            let outcome = result.as_ref().map(|_| ());
+            if let Err(err) = journal.record(action, source_label, outcome, hydrated.as_ref()) {
+                log::warn!("zubridge: failed to journal action: {err}");
+            }
+        }
+        result
+    }
 
-        Ok(new_state)
+    /// The root thunk `action` was dispatched from, if any, for grouping its
+    /// history entry. Always `None` without a history.
+    fn history_group(&self, action: &ZubridgeAction) -> Option<String> {
+        self.history.as_ref()?;
+        let parent_id = action.thunk_parent_id.as_ref()?;
+        Some(
+            self.thunk_manager
+                .get(parent_id)
+                .and_then(|record| record.root_thunk_id.clone())
+                .unwrap_or_else(|| parent_id.clone()),
+        )
     }
 
     /// Drain all immediately-eligible actions from the queue and execute them.
     ///
-    /// Returns the new state produced by each executed action in order.
-    fn drain_queue(&mut self) -> Result<Vec<JsonValue>> {
-        let mut states = Vec::new();
+    /// Returns each executed action with the state it produced, in order. A
+    /// rejected action does not stop the drain — its error is recorded on its
+    /// [`ExecutedAction`] and the next eligible action runs. Nothing runs
+    /// while a devtools session is paused.
+    fn drain_queue(&mut self) -> Vec<ExecutedAction> {
+        let mut executed = Vec::new();
+        if matches!(&self.devtools, Some(devtools) if devtools.is_paused()) {
+            return executed;
+        }
         loop {
             let ctx = self.thunk_manager.scheduler_context();
             let ready = self.scheduler.drain_ready(&ctx);
@@ -177,11 +410,52 @@ impl ActionQueueManager {
                 break;
             }
             for queued in ready {
-                states.push(self.execute_action(queued)?);
+                let (result, changed_keys) =
+                    match self.execute_action(&queued.action, &queued.source_label) {
+                        Ok((state, changed_keys)) => (Ok(state), changed_keys),
+                        Err(err) => (Err(err), None),
+                    };
+                executed.push(ExecutedAction {
+                    action: queued.action,
+                    source_label: queued.source_label,
+                    result,
+                    changed_keys,
+                });
             }
         }
-        Ok(states)
+        executed
+    }
+}
+
+/// True if the state manager behind `handle` can restore state, which
+/// undo/redo needs.
+fn supports_hydrate(handle: &StateManagerHandle) -> bool {
+    handle.lock().is_ok_and(|guard| guard.supports_hydrate())
+}
+
+/// Undo or redo one history entry by hydrating the state manager. Reports
+/// the restored keys as changed, and returns the state it hydrated (`None`
+/// if there was nothing to undo or redo). If hydrating fails the history is
+/// reset to the state the manager is actually in.
+fn apply_history(
+    handle: &StateManagerHandle,
+    history: &mut UndoHistory,
+    op: HistoryOp,
+) -> Result<(JsonValue, Option<Vec<String>>, Option<JsonValue>)> {
+    let mut guard = handle
+        .lock()
+        .map_err(|e| ZubridgeError::StateError(e.to_string()))?;
+    let Some(restore) = history.apply(op) else {
+        return Ok((guard.get_initial_state(), Some(Vec::new()), None));
+    };
+    let changed_keys = restore
+        .as_object()
+        .map(|restore| restore.keys().cloned().collect());
+    if let Err(err) = guard.hydrate(restore.clone()) {
+        history.reset(&guard.get_initial_state());
+        return Err(ZubridgeError::ActionProcessing(err.to_string()));
     }
+    Ok((guard.get_initial_state(), changed_keys, Some(restore)))
 }
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
@@ -189,7 +463,7 @@ impl ActionQueueManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::StateManager;
+    use crate::models::{StateManager, TryStateManager};
     use std::sync::{Arc, Mutex};
 
     /// Minimal state manager that counts dispatched actions.
@@ -208,6 +482,27 @@ mod tests {
         }
     }
 
+    /// Counts `INC` actions and rejects everything else without touching state.
+    struct StrictState {
+        count: usize,
+    }
+
+    impl TryStateManager for StrictState {
+        fn get_initial_state(&self) -> JsonValue {
+            serde_json::json!({ "count": self.count })
+        }
+        fn try_dispatch_action(&mut self, action: JsonValue) -> Result<JsonValue> {
+            if action["type"] != "INC" {
+                return Err(ZubridgeError::ActionProcessing(format!(
+                    "unknown action type: {}",
+                    action["type"]
+                )));
+            }
+            self.count += 1;
+            Ok(self.get_initial_state())
+        }
+    }
+
     fn manager() -> (ActionQueueManager, Arc<Mutex<usize>>) {
         let counter = Arc::new(Mutex::new(0_usize));
         let mgr = ActionQueueManager::new(CountingState {
@@ -249,7 +544,7 @@ mod tests {
     fn normal_action_dispatched_immediately_when_idle() {
         let (mut mgr, counter) = manager();
         let result = mgr.dispatch(action("INC"), "main".into()).unwrap();
-        assert!(result.is_some());
+        assert!(matches!(result, DispatchOutcome::Executed { .. }));
         assert_eq!(*counter.lock().unwrap(), 1);
         assert_eq!(mgr.queue_len(), 0);
     }
@@ -265,7 +560,7 @@ mod tests {
 
         // Normal action should be queued, not executed yet.
         let result = mgr.dispatch(action("INC"), "main".into()).unwrap();
-        assert!(result.is_none()); // queued
+        assert!(matches!(result, DispatchOutcome::Queued { .. }));
         assert_eq!(*counter.lock().unwrap(), 0);
         assert_eq!(mgr.queue_len(), 1);
     }
@@ -304,7 +599,7 @@ mod tests {
 
         // Action for root T1 is blocked because T2's non-concurrent task is running.
         let result = mgr.dispatch(thunk_action("INC", "t1"), "main".into()).unwrap();
-        assert!(result.is_none());
+        assert!(matches!(result, DispatchOutcome::Queued { .. }));
         assert_eq!(mgr.queue_len(), 1);
 
         // Child T2 completes — its task is removed; drain should unblock the queued action.
@@ -327,11 +622,17 @@ mod tests {
         assert_eq!(mgr.queue_len(), 2);
 
         // Complete the thunk — states from both drained actions are returned.
-        let (_events, states) = mgr.on_thunk_complete("t1", None).unwrap();
+        let (_events, drained) = mgr.on_thunk_complete("t1", None).unwrap();
         assert_eq!(mgr.queue_len(), 0);
-        assert_eq!(states.len(), 2, "one state per drained action");
-        assert_eq!(states[0], serde_json::json!({ "count": 1 }));
-        assert_eq!(states[1], serde_json::json!({ "count": 2 }));
+        assert_eq!(drained.len(), 2, "one state per drained action");
+        assert_eq!(
+            drained[0].result.as_ref().unwrap(),
+            &serde_json::json!({ "count": 1 })
+        );
+        assert_eq!(
+            drained[1].result.as_ref().unwrap(),
+            &serde_json::json!({ "count": 2 })
+        );
     }
 
     #[test]
@@ -343,7 +644,7 @@ mod tests {
         mgr.execute_thunk("t1");
 
         let result = mgr.dispatch(immediate_action("INC"), "main".into()).unwrap();
-        assert!(result.is_some()); // executed immediately
+        assert!(matches!(result, DispatchOutcome::Executed { .. })); // executed immediately
         assert_eq!(*counter.lock().unwrap(), 1);
     }
 
@@ -359,7 +660,309 @@ mod tests {
         let result = mgr
             .dispatch(thunk_action("INC", "t1"), "main".into())
             .unwrap();
-        assert!(result.is_some());
+        assert!(matches!(result, DispatchOutcome::Executed { .. }));
         assert_eq!(*counter.lock().unwrap(), 1);
     }
+
+    #[test]
+    fn drained_actions_carry_id_and_source_label() {
+        let (mut mgr, _counter) = manager();
+
+        mgr.register_thunk("t1".into(), None, "main".into(), None, false, false)
+            .unwrap();
+        mgr.execute_thunk("t1");
+
+        let DispatchOutcome::Queued { action_id, evicted } =
+            mgr.dispatch(action("INC"), "popup".into()).unwrap()
+        else {
+            panic!("expected the action to be queued behind t1");
+        };
+        assert!(evicted.is_none());
+
+        let (_events, drained) = mgr.on_thunk_complete("t1", None).unwrap();
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].action.id.as_deref(), Some(action_id.as_str()));
+        assert_eq!(drained[0].source_label, "popup");
+    }
+
+    #[test]
+    fn journal_records_executed_actions_and_replays_them() {
+        let path = std::env::temp_dir()
+            .join(format!("zubridge-orchestration-{}", uuid::Uuid::new_v4()))
+            .join("actions.ndjson");
+        let config = crate::journal::JournalConfig::new(&path);
+        let mut mgr =
+            ActionQueueManager::with_state_handle(crate::state::new_handle(StrictState {
+                count: 0,
+            }));
+        mgr.set_journal(Some(ActionJournal::open(config).unwrap()));
+
+        mgr.register_thunk("t1".into(), None, "main".into(), None, false, false)
+            .unwrap();
+        mgr.execute_thunk("t1");
+        mgr.dispatch(thunk_action("INC", "t1"), "main".into())
+            .unwrap();
+        mgr.dispatch(action("INC"), "popup".into()).unwrap();
+        assert!(mgr
+            .dispatch(immediate_action("NOPE"), "popup".into())
+            .is_err());
+        mgr.on_thunk_complete("t1", None).unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let entries: Vec<crate::journal::JournalEntry> = text
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].thunk_parent_id.as_deref(), Some("t1"));
+        assert!(entries[1].error.is_some());
+        assert_eq!(entries[2].source_label, "popup");
+
+        let files = mgr.journal().unwrap().files();
+        let (state, summary) =
+            crate::journal::replay_files(&files, &mut StrictState { count: 0 }).unwrap();
+        assert_eq!(state, serde_json::json!({ "count": 2 }));
+        assert_eq!(summary.skipped, 1);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn dispatch_assigns_missing_action_id() {
+        let (mut mgr, _counter) = manager();
+        let result = mgr
+            .dispatch(ZubridgeAction { id: None, ..action("INC") }, "main".into())
+            .unwrap();
+        let DispatchOutcome::Executed { action_id, .. } = result else {
+            panic!("expected immediate execution");
+        };
+        assert!(!action_id.is_empty());
+    }
+
+    #[test]
+    fn executed_actions_carry_reported_changed_keys() {
+        let slices = crate::slice::SliceStateManager::builder()
+            .slice("count", |state: &JsonValue, action: &JsonValue| {
+                if action["type"] == "INC" {
+                    serde_json::json!(state.as_i64().unwrap_or(0) + 1)
+                } else {
+                    state.clone()
+                }
+            })
+            .build()
+            .unwrap();
+        let mut mgr = ActionQueueManager::with_state_handle(crate::state::new_handle(slices));
+        let DispatchOutcome::Executed { changed_keys, .. } =
+            mgr.dispatch(action("INC"), "main".into()).unwrap()
+        else {
+            panic!("expected immediate execution");
+        };
+        assert_eq!(changed_keys, Some(vec!["count".to_string()]));
+
+        // Managers that don't track changes report none.
+        let (mut mgr, _counter) = manager();
+        let DispatchOutcome::Executed { changed_keys, .. } =
+            mgr.dispatch(action("INC"), "main".into()).unwrap()
+        else {
+            panic!("expected immediate execution");
+        };
+        assert_eq!(changed_keys, None);
+    }
+
+    #[test]
+    fn history_undoes_a_root_thunk_as_one_unit() {
+        let slices = crate::slice::SliceStateManager::builder()
+            .slice("count", |state: &JsonValue, action: &JsonValue| {
+                if action["type"] == "INC" {
+                    serde_json::json!(state.as_i64().unwrap_or(0) + 1)
+                } else {
+                    state.clone()
+                }
+            })
+            .build()
+            .unwrap();
+        let mut mgr = ActionQueueManager::with_state_handle(crate::state::new_handle(slices));
+        mgr.set_history(Some(UndoHistory::new(
+            crate::history::HistoryConfig::default(),
+        )));
+        let count = |outcome: DispatchOutcome| match outcome {
+            DispatchOutcome::Executed { state, .. } => state["count"].clone(),
+            DispatchOutcome::Queued { .. } => panic!("expected immediate execution"),
+        };
+
+        mgr.dispatch(action("INC"), "main".into()).unwrap();
+        mgr.register_thunk("t1".into(), None, "main".into(), None, false, false)
+            .unwrap();
+        mgr.execute_thunk("t1");
+        for _ in 0..2 {
+            mgr.dispatch(thunk_action("INC", "t1"), "main".into())
+                .unwrap();
+        }
+
+        // UNDO runs straight away even though t1 blocks normal actions.
+        let undo = action(crate::history::UNDO_ACTION_TYPE);
+        let redo = action(crate::history::REDO_ACTION_TYPE);
+        let DispatchOutcome::Executed { changed_keys, .. } =
+            mgr.dispatch(undo.clone(), "popup".into()).unwrap()
+        else {
+            panic!("expected UNDO to run immediately");
+        };
+        assert_eq!(changed_keys, Some(vec!["count".to_string()]));
+        mgr.on_thunk_complete("t1", None).unwrap();
+        let state = crate::state::read_state(mgr.state_handle.as_ref().unwrap()).unwrap();
+        assert_eq!(state["count"], 1);
+
+        assert_eq!(count(mgr.dispatch(redo, "main".into()).unwrap()), 3);
+        assert_eq!(count(mgr.dispatch(undo.clone(), "main".into()).unwrap()), 1);
+        // Back to the baseline, before the slice was first set.
+        assert!(count(mgr.dispatch(undo.clone(), "main".into()).unwrap()).is_null());
+        // Nothing left to undo: state is unchanged.
+        assert!(count(mgr.dispatch(undo, "main".into()).unwrap()).is_null());
+    }
+
+    #[test]
+    fn journaled_undo_replays_by_hydrating() {
+        let counter = || {
+            crate::slice::SliceStateManager::builder()
+                .slice("count", |state: &JsonValue, action: &JsonValue| {
+                    if action["type"] == "INC" {
+                        serde_json::json!(state.as_i64().unwrap_or(0) + 1)
+                    } else {
+                        state.clone()
+                    }
+                })
+                .build()
+                .unwrap()
+        };
+        let path = std::env::temp_dir()
+            .join(format!("zubridge-orchestration-{}", uuid::Uuid::new_v4()))
+            .join("actions.ndjson");
+        let mut mgr = ActionQueueManager::with_state_handle(crate::state::new_handle(counter()));
+        mgr.set_history(Some(UndoHistory::new(
+            crate::history::HistoryConfig::default(),
+        )));
+        let config = crate::journal::JournalConfig::new(&path);
+        mgr.set_journal(Some(ActionJournal::open(config).unwrap()));
+
+        let undo = action(crate::history::UNDO_ACTION_TYPE);
+        let redo = action(crate::history::REDO_ACTION_TYPE);
+        for dispatched in [action("INC"), action("INC"), undo.clone(), action("INC")] {
+            mgr.dispatch(dispatched, "main".into()).unwrap();
+        }
+        mgr.dispatch(redo, "main".into()).unwrap();
+        let live = crate::state::read_state(mgr.state_handle.as_ref().unwrap()).unwrap();
+        assert_eq!(live["count"], 2);
+
+        let files = mgr.journal().unwrap().files();
+        let (state, summary) = crate::journal::replay_files(&files, &mut counter()).unwrap();
+        assert_eq!(state, live);
+        // The REDO had nothing to redo once INC cleared the redo stack.
+        assert_eq!(summary.applied, 4);
+        assert_eq!(summary.skipped, 1);
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn history_refuses_to_undo_without_hydrate() {
+        let (mut mgr, counter) = manager();
+        mgr.set_history(Some(UndoHistory::new(
+            crate::history::HistoryConfig::default(),
+        )));
+        mgr.dispatch(action("INC"), "main".into()).unwrap();
+        mgr.dispatch(action("INC"), "main".into()).unwrap();
+
+        let err = mgr
+            .dispatch(action(crate::history::UNDO_ACTION_TYPE), "main".into())
+            .unwrap_err();
+        assert!(matches!(err, ZubridgeError::HydrateUnsupported));
+        assert_eq!(*counter.lock().unwrap(), 2);
+        assert_eq!(mgr.history().unwrap().depth(), (0, 0));
+    }
+
+    #[test]
+    fn devtools_jump_pauses_dispatch_until_resumed() {
+        let (mut mgr, counter) = manager();
+        mgr.set_devtools(Some(DevtoolsSession::new(10)));
+        mgr.dispatch(action("INC"), "main".into()).unwrap();
+        mgr.register_thunk("t1".into(), None, "main".into(), None, false, false)
+            .unwrap();
+        mgr.execute_thunk("t1");
+        mgr.dispatch(action("INC"), "popup".into()).unwrap();
+        assert_eq!(mgr.queue_len(), 1);
+
+        assert_eq!(
+            mgr.devtools_jump(0).unwrap(),
+            serde_json::json!({ "count": 1 })
+        );
+        assert!(matches!(
+            mgr.dispatch(immediate_action("INC"), "main".into()),
+            Err(ZubridgeError::Devtools(_))
+        ));
+        // Completing the thunk doesn't release the queue while paused.
+        let (_events, drained) = mgr.on_thunk_complete("t1", None).unwrap();
+        assert!(drained.is_empty());
+        assert_eq!(*counter.lock().unwrap(), 1);
+
+        let drained = mgr.devtools_resume();
+        assert_eq!(drained.len(), 1);
+        assert_eq!(*counter.lock().unwrap(), 2);
+        let devtools = mgr.devtools().unwrap();
+        assert_eq!(devtools.entries().count(), 2);
+        assert_eq!(devtools.get(1).unwrap().source_label, "popup");
+    }
+
+    #[test]
+    fn drop_label_releases_actions_blocked_by_its_thunks() {
+        let (mut mgr, counter) = manager();
+
+        mgr.register_thunk("t1".into(), None, "popup".into(), None, false, false)
+            .unwrap();
+        mgr.execute_thunk("t1");
+        mgr.dispatch(action("INC"), "main".into()).unwrap();
+        assert_eq!(mgr.queue_len(), 1);
+
+        let drained = mgr.drop_label("popup").unwrap();
+        assert_eq!(drained.len(), 1);
+        assert_eq!(mgr.queue_len(), 0);
+        assert_eq!(*counter.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn dispatch_without_state_manager_fails() {
+        let mut mgr = ActionQueueManager::default();
+        let err = mgr.dispatch(action("INC"), "main".into()).unwrap_err();
+        assert!(matches!(err, ZubridgeError::StateManagerMissing));
+    }
+
+    #[test]
+    fn rejected_action_returns_action_processing_error() {
+        let mut mgr =
+            ActionQueueManager::with_state_handle(crate::state::new_handle(StrictState {
+                count: 0,
+            }));
+        let err = mgr.dispatch(action("BOOM"), "main".into()).unwrap_err();
+        assert!(matches!(err, ZubridgeError::ActionProcessing(_)));
+        let handle = mgr.state_handle.as_ref().unwrap();
+        assert_eq!(crate::state::read_state(handle).unwrap()["count"], 0);
+    }
+
+    #[test]
+    fn rejected_queued_action_does_not_stop_drain() {
+        let mut mgr =
+            ActionQueueManager::with_state_handle(crate::state::new_handle(StrictState {
+                count: 0,
+            }));
+        mgr.register_thunk("t1".into(), None, "main".into(), None, false, false)
+            .unwrap();
+        mgr.execute_thunk("t1");
+        mgr.dispatch(action("BOOM"), "main".into()).unwrap();
+        mgr.dispatch(action("INC"), "main".into()).unwrap();
+
+        let (_events, drained) = mgr.on_thunk_complete("t1", None).unwrap();
+        assert_eq!(drained.len(), 2);
+        assert!(matches!(
+            drained[0].result,
+            Err(ZubridgeError::ActionProcessing(_))
+        ));
+        assert_eq!(drained[1].result.as_ref().unwrap()["count"], 1);
+    }
 }
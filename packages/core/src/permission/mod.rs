@@ -0,0 +1,396 @@
+//! Host-defined per-window permissions.
+//!
+//! A [`PermissionPolicy`] decides whether a webview may dispatch an action,
+//! register a thunk, read state, or use the devtools commands — e.g. "the settings window may dispatch
+//! `THEME:*` but the overlay window may only read". It runs before, and
+//! independently of, key-based [`AccessControl`](crate::access::AccessControl).
+//!
+//! [`GlobPermissionPolicy`] is a ready-made policy configured from JSON:
+//!
+//! ```
+//! use zubridge_core::permission::{GlobPermissionPolicy, PermissionPolicy, PermissionRequest};
+//!
+//! let policy = GlobPermissionPolicy::from_json(r#"{
+//!     "windows": {
+//!         "settings": { "dispatch": ["THEME:*"], "thunks": true },
+//!         "overlay-*": { "dispatch": [] }
+//!     },
+//!     "default": { "dispatch": ["*"], "thunks": true }
+//! }"#).unwrap();
+//!
+//! let read = PermissionRequest::ReadState { keys: None };
+//! assert!(policy.check("overlay-1", &read).is_allowed());
+//! ```
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::error::{Result, ZubridgeError};
+use crate::models::ZubridgeAction;
+use crate::thunk::ThunkRecord;
+
+// ── PermissionRequest ─────────────────────────────────────────────────────────
+
+/// An operation a webview is attempting, with the context a policy needs to
+/// judge it.
+#[derive(Debug, Clone, Copy)]
+pub enum PermissionRequest<'a> {
+    /// Dispatch `action`. `thunk` is the record of the thunk named by
+    /// `action.thunk_parent_id`, if any.
+    Dispatch {
+        action: &'a ZubridgeAction,
+        thunk: Option<&'a ThunkRecord>,
+    },
+    /// Register the thunk `thunk_id`, declaring `keys`. `parent` is the
+    /// record of its parent thunk, if any.
+    RegisterThunk {
+        thunk_id: &'a str,
+        keys: Option<&'a [String]>,
+        parent: Option<&'a ThunkRecord>,
+    },
+    /// Read state, optionally narrowed to `keys`.
+    ReadState { keys: Option<&'a [String]> },
+    /// Inspect or time-travel the devtools session through the debug-only
+    /// `devtools_*` commands.
+    Devtools,
+}
+
+// ── Permission ────────────────────────────────────────────────────────────────
+
+/// A policy's verdict.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Permission {
+    Allow,
+    /// Deny, with a reason reported back to the webview.
+    Deny(String),
+}
+
+impl Permission {
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, Self::Allow)
+    }
+}
+
+// ── PermissionPolicy ──────────────────────────────────────────────────────────
+
+/// Decides what each webview may do. Register one through
+/// [`ZubridgeOptions::permission_policy`](crate::models::ZubridgeOptions::permission_policy).
+///
+/// Host-dispatched actions (from
+/// [`HOST_SOURCE_LABEL`](crate::orchestration::HOST_SOURCE_LABEL)) are not
+/// subject to the policy.
+pub trait PermissionPolicy: Send + Sync + 'static {
+    fn check(&self, label: &str, request: &PermissionRequest<'_>) -> Permission;
+}
+
+/// Consult `policy` and turn a denial into
+/// [`ZubridgeError::PermissionDenied`].
+pub fn enforce(
+    policy: &dyn PermissionPolicy,
+    label: &str,
+    request: &PermissionRequest<'_>,
+) -> Result<()> {
+    match policy.check(label, request) {
+        Permission::Allow => Ok(()),
+        Permission::Deny(message) => Err(ZubridgeError::PermissionDenied {
+            source_label: label.to_string(),
+            message,
+        }),
+    }
+}
+
+// ── GlobPermissionPolicy ──────────────────────────────────────────────────────
+
+/// What a single window (or group of windows) may do.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WindowPermissions {
+    /// Action-type globs the window may dispatch (`THEME:*`, `*`). Empty
+    /// means the window may not dispatch at all.
+    #[serde(default)]
+    pub dispatch: Vec<String>,
+    /// Whether the window may register thunks.
+    #[serde(default)]
+    pub thunks: bool,
+    /// Whether the window may read state.
+    #[serde(default = "default_true")]
+    pub read: bool,
+    /// Whether the window may use the devtools commands.
+    #[serde(default)]
+    pub devtools: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl WindowPermissions {
+    /// Permissions that allow everything.
+    pub fn allow_all() -> Self {
+        Self {
+            dispatch: vec!["*".to_string()],
+            thunks: true,
+            read: true,
+            devtools: true,
+        }
+    }
+
+    fn may_dispatch(&self, action_type: &str) -> bool {
+        self.dispatch
+            .iter()
+            .any(|pattern| glob_match(pattern, action_type))
+    }
+}
+
+/// A [`PermissionPolicy`] mapping window-label globs to
+/// [`WindowPermissions`].
+///
+/// A label takes the permissions of its exact entry if there is one, else of
+/// the longest glob that matches it, else `default`. Without a `default`,
+/// windows no entry matches are unrestricted.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GlobPermissionPolicy {
+    #[serde(default)]
+    pub windows: HashMap<String, WindowPermissions>,
+    #[serde(default)]
+    pub default: Option<WindowPermissions>,
+}
+
+impl GlobPermissionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a policy from its JSON form.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| ZubridgeError::Serialization(e.to_string()))
+    }
+
+    /// Add (or replace) the permissions for `label_pattern`.
+    pub fn window(
+        mut self,
+        label_pattern: impl Into<String>,
+        permissions: WindowPermissions,
+    ) -> Self {
+        self.windows.insert(label_pattern.into(), permissions);
+        self
+    }
+
+    pub fn with_default(mut self, permissions: WindowPermissions) -> Self {
+        self.default = Some(permissions);
+        self
+    }
+
+    fn permissions_for(&self, label: &str) -> Option<&WindowPermissions> {
+        self.windows
+            .get(label)
+            .or_else(|| {
+                self.windows
+                    .iter()
+                    .filter(|(pattern, _)| pattern.contains('*') && glob_match(pattern, label))
+                    .max_by_key(|(pattern, _)| pattern.len())
+                    .map(|(_, permissions)| permissions)
+            })
+            .or(self.default.as_ref())
+    }
+}
+
+impl PermissionPolicy for GlobPermissionPolicy {
+    fn check(&self, label: &str, request: &PermissionRequest<'_>) -> Permission {
+        let Some(permissions) = self.permissions_for(label) else {
+            return Permission::Allow;
+        };
+        match request {
+            PermissionRequest::Dispatch { action, .. } => {
+                if permissions.may_dispatch(&action.action_type) {
+                    Permission::Allow
+                } else {
+                    Permission::Deny(format!("may not dispatch {}", action.action_type))
+                }
+            }
+            PermissionRequest::RegisterThunk { thunk_id, .. } => {
+                if permissions.thunks {
+                    Permission::Allow
+                } else {
+                    Permission::Deny(format!("may not register thunk {thunk_id}"))
+                }
+            }
+            PermissionRequest::ReadState { .. } => {
+                if permissions.read {
+                    Permission::Allow
+                } else {
+                    Permission::Deny("may not read state".to_string())
+                }
+            }
+            PermissionRequest::Devtools => {
+                if permissions.devtools {
+                    Permission::Allow
+                } else {
+                    Permission::Deny("may not use devtools".to_string())
+                }
+            }
+        }
+    }
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No `*` in the pattern: exact match.
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(action_type: &str) -> ZubridgeAction {
+        ZubridgeAction {
+            id: None,
+            action_type: action_type.to_string(),
+            payload: None,
+            source_label: None,
+            thunk_parent_id: None,
+            immediate: None,
+            keys: None,
+            bypass_access_control: None,
+            starts_thunk: None,
+            ends_thunk: None,
+        }
+    }
+
+    fn dispatch(action: &ZubridgeAction) -> PermissionRequest<'_> {
+        PermissionRequest::Dispatch {
+            action,
+            thunk: None,
+        }
+    }
+
+    fn policy() -> GlobPermissionPolicy {
+        GlobPermissionPolicy::from_json(
+            r#"{
+                "windows": {
+                    "settings": { "dispatch": ["THEME:*"], "thunks": true },
+                    "overlay-*": { "dispatch": [] },
+                    "overlay-admin*": { "dispatch": ["*"], "read": false }
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn glob_match_handles_prefix_suffix_and_infix_wildcards() {
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("THEME:*", "THEME:TOGGLE"));
+        assert!(!glob_match("THEME:*", "COUNTER:SET"));
+        assert!(glob_match("*:SET", "COUNTER:SET"));
+        assert!(glob_match("a*b*c", "a-b-b-c"));
+        assert!(!glob_match("a*b*c", "a-c"));
+        assert!(glob_match("exact", "exact"));
+        assert!(!glob_match("exact", "exactly"));
+    }
+
+    #[test]
+    fn dispatch_is_limited_to_matching_action_types() {
+        let policy = policy();
+        let theme = action("THEME:TOGGLE");
+        let counter = action("COUNTER:SET");
+        assert!(policy.check("settings", &dispatch(&theme)).is_allowed());
+        assert_eq!(
+            policy.check("settings", &dispatch(&counter)),
+            Permission::Deny("may not dispatch COUNTER:SET".to_string())
+        );
+        assert!(!policy.check("overlay-1", &dispatch(&theme)).is_allowed());
+    }
+
+    #[test]
+    fn longest_matching_label_glob_wins() {
+        let policy = policy();
+        let read = PermissionRequest::ReadState { keys: None };
+        assert!(policy.check("overlay-1", &read).is_allowed());
+        assert!(!policy.check("overlay-admin-2", &read).is_allowed());
+        assert!(policy
+            .check("overlay-admin-2", &dispatch(&action("COUNTER:SET")))
+            .is_allowed());
+    }
+
+    #[test]
+    fn thunk_registration_requires_permission() {
+        let policy = policy();
+        let request = PermissionRequest::RegisterThunk {
+            thunk_id: "t1",
+            keys: None,
+            parent: None,
+        };
+        assert!(policy.check("settings", &request).is_allowed());
+        assert!(!policy.check("overlay-1", &request).is_allowed());
+    }
+
+    #[test]
+    fn unmatched_labels_fall_back_to_default() {
+        let counter = action("COUNTER:SET");
+        assert!(policy().check("main", &dispatch(&counter)).is_allowed());
+
+        let strict = policy().with_default(WindowPermissions {
+            dispatch: Vec::new(),
+            thunks: false,
+            read: true,
+            devtools: false,
+        });
+        assert!(!strict.check("main", &dispatch(&counter)).is_allowed());
+    }
+
+    #[test]
+    fn devtools_must_be_granted_explicitly() {
+        let policy = GlobPermissionPolicy::from_json(
+            r#"{
+                "windows": { "debugger": { "devtools": true } },
+                "default": { "dispatch": ["*"] }
+            }"#,
+        )
+        .unwrap();
+        let request = PermissionRequest::Devtools;
+        assert!(policy.check("debugger", &request).is_allowed());
+        assert!(!policy.check("main", &request).is_allowed());
+    }
+
+    #[test]
+    fn enforce_reports_permission_denied() {
+        let err = enforce(&policy(), "overlay-1", &dispatch(&action("THEME:SET"))).unwrap_err();
+        match err {
+            ZubridgeError::PermissionDenied {
+                source_label,
+                message,
+            } => {
+                assert_eq!(source_label, "overlay-1");
+                assert!(message.contains("THEME:SET"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn malformed_json_is_a_serialization_error() {
+        assert!(matches!(
+            GlobPermissionPolicy::from_json("{ \"windows\": [] }"),
+            Err(ZubridgeError::Serialization(_))
+        ));
+    }
+}
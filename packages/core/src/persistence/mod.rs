@@ -0,0 +1,668 @@
+//! State persistence.
+//!
+//! [`PersistedStateManager`] wraps any [`TryStateManager`] so its state
+//! survives restarts: it hydrates the inner manager from a
+//! [`PersistenceBackend`] when built, then saves a snapshot after dispatches,
+//! debounced on a background thread so a burst of actions costs one write.
+//! A [`KeyFilter`] restricts which top-level keys are persisted.
+//!
+//! ```
+//! use std::sync::Arc;
+//! use serde_json::json;
+//! use zubridge_core::persistence::{
+//!     KeyFilter, MemoryBackend, PersistedStateManager, PersistenceConfig,
+//! };
+//! use zubridge_core::slice::SliceStateManager;
+//! use zubridge_core::TryStateManager;
+//!
+//! let counter = SliceStateManager::builder()
+//!     .slice("counter", |state, action| match action["type"].as_str() {
+//!         Some("COUNTER:INCREMENT") => json!(state.as_i64().unwrap_or(0) + 1),
+//!         _ if state.is_null() => json!(0),
+//!         _ => state.clone(),
+//!     })
+//!     .build()
+//!     .unwrap();
+//! let backend = Arc::new(MemoryBackend::new());
+//! let config = PersistenceConfig {
+//!     keys: KeyFilter::Allow(vec!["counter".into()]),
+//!     ..PersistenceConfig::default()
+//! };
+//! let mut manager = PersistedStateManager::new(counter, backend.clone(), config);
+//! manager
+//!     .try_dispatch_action(json!({ "type": "COUNTER:INCREMENT" }))
+//!     .unwrap();
+//! manager.flush().unwrap();
+//! assert_eq!(backend.snapshot().unwrap().state, json!({ "counter": 1 }));
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, ZubridgeError};
+use crate::migration::MigrationRegistry;
+use crate::models::{JsonValue, TryStateManager};
+
+/// A persisted state snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PersistedSnapshot {
+    /// Schema version the state was saved at. See
+    /// [`MigrationRegistry`](crate::migration::MigrationRegistry).
+    #[serde(default)]
+    pub version: u32,
+    pub state: JsonValue,
+}
+
+// ── Backends ──────────────────────────────────────────────────────────────────
+
+/// Storage for persisted snapshots.
+pub trait PersistenceBackend: Send + Sync {
+    /// Load the stored snapshot, or `None` if nothing has been saved yet.
+    fn load(&self) -> Result<Option<PersistedSnapshot>>;
+
+    /// Replace the stored snapshot.
+    fn save(&self, snapshot: &PersistedSnapshot) -> Result<()>;
+}
+
+/// Stores the snapshot as a JSON file. Saves write a sibling temporary file
+/// and rename it over the target, so a crash mid-save leaves the previous
+/// snapshot intact.
+#[derive(Debug, Clone)]
+pub struct JsonFileBackend {
+    path: PathBuf,
+}
+
+impl JsonFileBackend {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl PersistenceBackend for JsonFileBackend {
+    fn load(&self) -> Result<Option<PersistedSnapshot>> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(persistence_error(&self.path, err)),
+        };
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| ZubridgeError::Serialization(format!("{}: {e}", self.path.display())))
+    }
+
+    fn save(&self, snapshot: &PersistedSnapshot) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|e| persistence_error(dir, e))?;
+        }
+        let bytes = serde_json::to_vec(snapshot)
+            .map_err(|e| ZubridgeError::Serialization(e.to_string()))?;
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        fs::write(&tmp, bytes).map_err(|e| persistence_error(&tmp, e))?;
+        fs::rename(&tmp, &self.path).map_err(|e| persistence_error(&self.path, e))
+    }
+}
+
+fn persistence_error(path: &Path, err: std::io::Error) -> ZubridgeError {
+    ZubridgeError::Persistence(format!("{}: {err}", path.display()))
+}
+
+/// Keeps the snapshot in memory — for tests, or for state that should only
+/// survive a state-manager rebuild within one process.
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    stored: Mutex<Option<PersistedSnapshot>>,
+    saves: Mutex<usize>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A backend that starts out holding `snapshot`.
+    pub fn with_snapshot(snapshot: PersistedSnapshot) -> Self {
+        Self {
+            stored: Mutex::new(Some(snapshot)),
+            ..Self::default()
+        }
+    }
+
+    /// The stored snapshot.
+    pub fn snapshot(&self) -> Option<PersistedSnapshot> {
+        self.stored.lock().ok().and_then(|stored| stored.clone())
+    }
+
+    /// Number of saves so far.
+    pub fn save_count(&self) -> usize {
+        self.saves.lock().map(|saves| *saves).unwrap_or(0)
+    }
+}
+
+impl PersistenceBackend for MemoryBackend {
+    fn load(&self) -> Result<Option<PersistedSnapshot>> {
+        self.stored
+            .lock()
+            .map(|stored| stored.clone())
+            .map_err(|e| ZubridgeError::StateError(e.to_string()))
+    }
+
+    fn save(&self, snapshot: &PersistedSnapshot) -> Result<()> {
+        *self
+            .stored
+            .lock()
+            .map_err(|e| ZubridgeError::StateError(e.to_string()))? = Some(snapshot.clone());
+        if let Ok(mut saves) = self.saves.lock() {
+            *saves += 1;
+        }
+        Ok(())
+    }
+}
+
+// ── Configuration ─────────────────────────────────────────────────────────────
+
+/// Which top-level state keys are persisted (and restored).
+#[derive(Debug, Clone, Default)]
+pub enum KeyFilter {
+    /// Every key.
+    #[default]
+    All,
+    /// Only these keys.
+    Allow(Vec<String>),
+    /// Every key except these.
+    Deny(Vec<String>),
+}
+
+impl KeyFilter {
+    pub fn allows(&self, key: &str) -> bool {
+        match self {
+            KeyFilter::All => true,
+            KeyFilter::Allow(keys) => keys.iter().any(|k| k == key),
+            KeyFilter::Deny(keys) => !keys.iter().any(|k| k == key),
+        }
+    }
+
+    /// `state` narrowed to the allowed top-level keys. Non-object states
+    /// have no keys to filter and are returned whole.
+    pub fn apply(&self, state: &JsonValue) -> JsonValue {
+        match (self, state) {
+            (KeyFilter::All, _) => state.clone(),
+            (_, JsonValue::Object(map)) => JsonValue::Object(
+                map.iter()
+                    .filter(|(key, _)| self.allows(key))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+            ),
+            _ => state.clone(),
+        }
+    }
+}
+
+/// Options for [`PersistedStateManager`].
+#[derive(Debug, Clone)]
+pub struct PersistenceConfig {
+    /// Save once dispatches have been quiet for this long. Default 500 ms.
+    pub debounce: Duration,
+    /// Save at the latest this long after the first unsaved dispatch, even
+    /// if dispatches keep arriving. Default 5 s.
+    pub max_wait: Duration,
+    /// Top-level keys to persist. Default: all.
+    pub keys: KeyFilter,
+    /// Upgrades snapshots saved at older schema versions on hydrate. New
+    /// snapshots are saved at its current version. Default: none (v0).
+    pub migrations: MigrationRegistry,
+}
+
+impl Default for PersistenceConfig {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(500),
+            max_wait: Duration::from_secs(5),
+            keys: KeyFilter::All,
+            migrations: MigrationRegistry::new(),
+        }
+    }
+}
+
+// ── Saver thread ──────────────────────────────────────────────────────────────
+
+enum SaveRequest {
+    Snapshot(JsonValue),
+    /// Save anything pending now and report the outcome.
+    Flush(Sender<Result<()>>),
+}
+
+struct Saver {
+    requests: Option<Sender<SaveRequest>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Saver {
+    fn spawn(backend: Arc<dyn PersistenceBackend>, config: &PersistenceConfig) -> Self {
+        let (requests, received) = mpsc::channel();
+        let (debounce, max_wait) = (config.debounce, config.max_wait);
+        let version = config.migrations.current_version();
+        let thread = std::thread::Builder::new()
+            .name("zubridge-persistence".to_string())
+            .spawn(move || run_saver(backend.as_ref(), version, debounce, max_wait, received))
+            .map_err(|err| log::warn!("zubridge: failed to start the persistence thread: {err}"))
+            .ok();
+        Self {
+            requests: thread.as_ref().map(|_| requests),
+            thread,
+        }
+    }
+
+    fn schedule(&self, state: JsonValue) {
+        if let Some(requests) = &self.requests {
+            let _ = requests.send(SaveRequest::Snapshot(state));
+        }
+    }
+
+    fn flush(&self) -> Result<()> {
+        let Some(requests) = &self.requests else {
+            return Err(ZubridgeError::Persistence(
+                "persistence thread is not running".to_string(),
+            ));
+        };
+        let (reply, outcome) = mpsc::channel();
+        requests
+            .send(SaveRequest::Flush(reply))
+            .map_err(|e| ZubridgeError::Persistence(e.to_string()))?;
+        outcome
+            .recv()
+            .map_err(|e| ZubridgeError::Persistence(e.to_string()))?
+    }
+}
+
+impl Drop for Saver {
+    /// Save anything still pending before the manager goes away.
+    fn drop(&mut self) {
+        self.requests.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run_saver(
+    backend: &dyn PersistenceBackend,
+    version: u32,
+    debounce: Duration,
+    max_wait: Duration,
+    received: Receiver<SaveRequest>,
+) {
+    // The latest unsaved state, when it first became dirty, and when it
+    // last changed.
+    let mut pending: Option<(JsonValue, Instant, Instant)> = None;
+    let save = |pending: &mut Option<(JsonValue, Instant, Instant)>| match pending.take() {
+        Some((state, _, _)) => backend.save(&PersistedSnapshot { version, state }),
+        None => Ok(()),
+    };
+    loop {
+        let request = match &pending {
+            Some((_, first, last)) => {
+                let deadline = (*last + debounce).min(*first + max_wait);
+                received.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            }
+            None => received.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match request {
+            Ok(SaveRequest::Snapshot(state)) => {
+                let now = Instant::now();
+                let first = pending.as_ref().map_or(now, |(_, first, _)| *first);
+                pending = Some((state, first, now));
+            }
+            Ok(SaveRequest::Flush(reply)) => {
+                let _ = reply.send(save(&mut pending));
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                if let Err(err) = save(&mut pending) {
+                    log::warn!("zubridge: failed to persist state: {err}");
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                if let Err(err) = save(&mut pending) {
+                    log::warn!("zubridge: failed to persist state: {err}");
+                }
+                return;
+            }
+        }
+    }
+}
+
+// ── PersistedStateManager ─────────────────────────────────────────────────────
+
+/// A [`TryStateManager`] that persists the state of the manager it wraps.
+/// See the module docs.
+pub struct PersistedStateManager<S> {
+    inner: S,
+    keys: KeyFilter,
+    saver: Saver,
+    hydrate_error: Option<ZubridgeError>,
+}
+
+impl<S: TryStateManager> PersistedStateManager<S> {
+    /// Wrap `inner`, hydrating it from `backend`'s snapshot via
+    /// [`TryStateManager::hydrate`]. The snapshot is first upgraded by
+    /// `config.migrations`, then narrowed to `config.keys`. If loading,
+    /// migrating or hydrating fails, `inner` keeps its initial state; the
+    /// error is logged and available from
+    /// [`hydrate_error`](Self::hydrate_error).
+    pub fn new(
+        mut inner: S,
+        backend: Arc<dyn PersistenceBackend>,
+        config: PersistenceConfig,
+    ) -> Self {
+        let hydrated = backend.load().and_then(|snapshot| match snapshot {
+            Some(snapshot) => {
+                let state = config
+                    .migrations
+                    .migrate(snapshot.state, snapshot.version)?;
+                inner.hydrate(config.keys.apply(&state))
+            }
+            None => Ok(()),
+        });
+        let hydrate_error = hydrated.err();
+        match &hydrate_error {
+            Some(ZubridgeError::HydrateUnsupported) => log::error!(
+                "zubridge: the persisted state was not restored; the state manager must override `hydrate`"
+            ),
+            Some(err) => {
+                log::warn!("zubridge: starting from the initial state; hydrate failed: {err}")
+            }
+            None => {}
+        }
+        Self {
+            inner,
+            saver: Saver::spawn(backend, &config),
+            keys: config.keys,
+            hydrate_error,
+        }
+    }
+
+    /// Why hydration failed, if it did.
+    pub fn hydrate_error(&self) -> Option<&ZubridgeError> {
+        self.hydrate_error.as_ref()
+    }
+
+    /// Save any pending snapshot now, without waiting for the debounce.
+    pub fn flush(&self) -> Result<()> {
+        self.saver.flush()
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+impl<S: TryStateManager> TryStateManager for PersistedStateManager<S> {
+    fn get_initial_state(&self) -> JsonValue {
+        self.inner.get_initial_state()
+    }
+
+    fn try_dispatch_action(&mut self, action: JsonValue) -> Result<JsonValue> {
+        let state = self.inner.try_dispatch_action(action)?;
+        // Skip the save when the manager reports that only unpersisted keys
+        // (or none at all) changed.
+        let persisted_change = self
+            .inner
+            .changed_keys()
+            .map_or(true, |keys| keys.iter().any(|key| self.keys.allows(key)));
+        if persisted_change {
+            self.saver.schedule(self.keys.apply(&state));
+        }
+        Ok(state)
+    }
+
+    fn action_types(&self) -> Option<Vec<String>> {
+        self.inner.action_types()
+    }
+
+    fn changed_keys(&self) -> Option<Vec<String>> {
+        self.inner.changed_keys()
+    }
+
+    /// Hydrating after construction (e.g. an undo) is a change like any
+    /// other, so it is saved too.
+    fn hydrate(&mut self, state: JsonValue) -> Result<()> {
+        self.inner.hydrate(state)?;
+        self.saver
+            .schedule(self.keys.apply(&self.inner.get_initial_state()));
+        Ok(())
+    }
+
+    fn supports_hydrate(&self) -> bool {
+        self.inner.supports_hydrate()
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::slice::SliceStateManager;
+    use serde_json::json;
+
+    fn slices() -> SliceStateManager {
+        SliceStateManager::builder()
+            .slice("counter", |state, action| match action["type"].as_str() {
+                Some("COUNTER:INCREMENT") => json!(state.as_i64().unwrap_or(0) + 1),
+                _ if state.is_null() => json!(0),
+                _ => state.clone(),
+            })
+            .slice("theme", |state, action| match action["type"].as_str() {
+                Some("THEME:TOGGLE") if state == "dark" => json!("light"),
+                Some("THEME:TOGGLE") => json!("dark"),
+                _ if state.is_null() => json!("dark"),
+                _ => state.clone(),
+            })
+            .build()
+            .unwrap()
+    }
+
+    fn increment() -> JsonValue {
+        json!({ "type": "COUNTER:INCREMENT" })
+    }
+
+    fn slow_config(keys: KeyFilter) -> PersistenceConfig {
+        PersistenceConfig {
+            debounce: Duration::from_secs(3600),
+            max_wait: Duration::from_secs(3600),
+            keys,
+            migrations: MigrationRegistry::new(),
+        }
+    }
+
+    #[test]
+    fn hydrates_from_the_stored_snapshot() {
+        let backend = Arc::new(MemoryBackend::with_snapshot(PersistedSnapshot {
+            version: 0,
+            state: json!({ "counter": 7, "theme": "light", "stale": true }),
+        }));
+        let manager = PersistedStateManager::new(slices(), backend, slow_config(KeyFilter::All));
+        assert!(manager.hydrate_error().is_none());
+        assert_eq!(
+            manager.get_initial_state(),
+            json!({ "counter": 7, "theme": "light" })
+        );
+    }
+
+    #[test]
+    fn saves_are_debounced_into_one_write() {
+        let backend = Arc::new(MemoryBackend::new());
+        let mut manager =
+            PersistedStateManager::new(slices(), backend.clone(), slow_config(KeyFilter::All));
+        for _ in 0..3 {
+            manager.try_dispatch_action(increment()).unwrap();
+        }
+        assert_eq!(backend.save_count(), 0);
+        manager.flush().unwrap();
+        assert_eq!(backend.save_count(), 1);
+        assert_eq!(
+            backend.snapshot().unwrap().state,
+            json!({ "counter": 3, "theme": "dark" })
+        );
+    }
+
+    #[test]
+    fn quiet_period_triggers_a_save() {
+        let backend = Arc::new(MemoryBackend::new());
+        let config = PersistenceConfig {
+            debounce: Duration::from_millis(5),
+            ..PersistenceConfig::default()
+        };
+        let mut manager = PersistedStateManager::new(slices(), backend.clone(), config);
+        manager.try_dispatch_action(increment()).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while backend.save_count() == 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(backend.save_count(), 1);
+    }
+
+    #[test]
+    fn key_filters_limit_what_is_saved_and_restored() {
+        let backend = Arc::new(MemoryBackend::new());
+        let mut manager = PersistedStateManager::new(
+            slices(),
+            backend.clone(),
+            slow_config(KeyFilter::Deny(vec!["counter".into()])),
+        );
+        // Only a denied key changed, so nothing is scheduled.
+        manager.try_dispatch_action(increment()).unwrap();
+        manager.flush().unwrap();
+        assert_eq!(backend.save_count(), 0);
+
+        manager
+            .try_dispatch_action(json!({ "type": "THEME:TOGGLE" }))
+            .unwrap();
+        manager.flush().unwrap();
+        assert_eq!(
+            backend.snapshot().unwrap().state,
+            json!({ "theme": "light" })
+        );
+
+        let restored = PersistedStateManager::new(
+            slices(),
+            Arc::new(MemoryBackend::with_snapshot(PersistedSnapshot {
+                version: 0,
+                state: json!({ "counter": 9, "theme": "light" }),
+            })),
+            slow_config(KeyFilter::Allow(vec!["counter".into()])),
+        );
+        assert_eq!(
+            restored.get_initial_state(),
+            json!({ "counter": 9, "theme": "dark" })
+        );
+    }
+
+    fn rename_count(mut state: JsonValue) -> Result<JsonValue> {
+        if let Some(count) = state.as_object_mut().and_then(|s| s.remove("count")) {
+            state["counter"] = count;
+        }
+        Ok(state)
+    }
+
+    #[test]
+    fn hydrate_migrates_old_snapshots_and_saves_the_current_version() {
+        let backend = Arc::new(MemoryBackend::with_snapshot(PersistedSnapshot {
+            version: 0,
+            state: json!({ "count": 4 }),
+        }));
+        let config = PersistenceConfig {
+            migrations: MigrationRegistry::new().step(1, rename_count),
+            ..slow_config(KeyFilter::All)
+        };
+        let mut manager = PersistedStateManager::new(slices(), backend.clone(), config);
+        assert_eq!(manager.get_initial_state()["counter"], 4);
+
+        manager.try_dispatch_action(increment()).unwrap();
+        manager.flush().unwrap();
+        let saved = backend.snapshot().unwrap();
+        assert_eq!(saved.version, 1);
+        assert_eq!(saved.state["counter"], 5);
+    }
+
+    #[test]
+    fn failed_migration_falls_back_to_the_initial_state() {
+        let backend = Arc::new(MemoryBackend::with_snapshot(PersistedSnapshot {
+            version: 0,
+            state: json!({ "counter": 4 }),
+        }));
+        let config = PersistenceConfig {
+            migrations: MigrationRegistry::new()
+                .step(1, |_| Err(ZubridgeError::StateError("bad layout".into()))),
+            ..slow_config(KeyFilter::All)
+        };
+        let manager = PersistedStateManager::new(slices(), backend, config);
+        assert!(matches!(
+            manager.hydrate_error(),
+            Some(ZubridgeError::Migration { from: 0, to: 1, .. })
+        ));
+        assert_eq!(manager.get_initial_state()["counter"], 0);
+    }
+
+    #[test]
+    fn managers_without_hydrate_report_it() {
+        struct Plain(i64);
+
+        impl crate::models::StateManager for Plain {
+            fn get_initial_state(&self) -> JsonValue {
+                json!({ "counter": self.0 })
+            }
+
+            fn dispatch_action(&mut self, _action: JsonValue) -> JsonValue {
+                self.0 += 1;
+                json!({ "counter": self.0 })
+            }
+        }
+
+        let backend = Arc::new(MemoryBackend::with_snapshot(PersistedSnapshot {
+            version: 0,
+            state: json!({ "counter": 4 }),
+        }));
+        let manager = PersistedStateManager::new(Plain(0), backend, slow_config(KeyFilter::All));
+        assert!(matches!(
+            manager.hydrate_error(),
+            Some(ZubridgeError::HydrateUnsupported)
+        ));
+        assert_eq!(manager.get_initial_state()["counter"], 0);
+    }
+
+    #[test]
+    fn dropping_the_manager_saves_pending_state() {
+        let backend = Arc::new(MemoryBackend::new());
+        let mut manager =
+            PersistedStateManager::new(slices(), backend.clone(), slow_config(KeyFilter::All));
+        manager.try_dispatch_action(increment()).unwrap();
+        drop(manager);
+        assert_eq!(backend.snapshot().unwrap().state["counter"], 1);
+    }
+
+    #[test]
+    fn json_file_backend_round_trips() {
+        let dir = std::env::temp_dir().join(format!("zubridge-{}", uuid::Uuid::new_v4()));
+        let backend = JsonFileBackend::new(dir.join("nested").join("state.json"));
+        assert_eq!(backend.load().unwrap(), None);
+        let snapshot = PersistedSnapshot {
+            version: 0,
+            state: json!({ "counter": 2 }),
+        };
+        backend.save(&snapshot).unwrap();
+        assert_eq!(backend.load().unwrap(), Some(snapshot));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,321 @@
+//! Reducer-composition state manager.
+//!
+//! [`SliceStateManager`] is the Rust counterpart of Redux's `combineReducers`:
+//! one reducer per top-level state key, composed into a single
+//! [`TryStateManager`]. Every dispatch records which top-level keys actually
+//! changed and reports them through
+//! [`TryStateManager::changed_keys`], so broadcasts hand that set to
+//! [`DeltaCalculator::compute_keys`](crate::deltas::DeltaCalculator::compute_keys)
+//! and never compare (or re-send) the slices an action left alone.
+//!
+//! ```
+//! use serde_json::json;
+//! use zubridge_core::slice::SliceStateManager;
+//! use zubridge_core::TryStateManager;
+//!
+//! let mut manager = SliceStateManager::builder()
+//!     .slice("counter", |state, action| match action["type"].as_str() {
+//!         Some("COUNTER:INCREMENT") => json!(state.as_i64().unwrap_or(0) + 1),
+//!         _ if state.is_null() => json!(0),
+//!         _ => state.clone(),
+//!     })
+//!     .slice("theme", |state, _action| {
+//!         if state.is_null() { json!("dark") } else { state.clone() }
+//!     })
+//!     .build()
+//!     .unwrap();
+//!
+//! manager
+//!     .try_dispatch_action(json!({ "type": "COUNTER:INCREMENT" }))
+//!     .unwrap();
+//! assert_eq!(manager.last_changed_keys(), ["counter".to_string()]);
+//! ```
+
+use crate::error::{Result, ZubridgeError};
+use crate::models::{JsonValue, TryStateManager};
+
+/// Action type passed to every reducer once, with a `null` slice state, to
+/// obtain the slice's initial value. Mirrors Redux's `@@redux/INIT`.
+pub const SLICE_INIT_ACTION_TYPE: &str = "@@zubridge/INIT";
+
+type SliceReducer = Box<dyn Fn(&JsonValue, &JsonValue) -> Result<JsonValue> + Send + Sync>;
+
+struct Slice {
+    key: String,
+    reducer: SliceReducer,
+}
+
+// ── Builder ───────────────────────────────────────────────────────────────────
+
+/// Builder for [`SliceStateManager`]. Slices are reduced in registration
+/// order.
+#[derive(Default)]
+pub struct SliceStateManagerBuilder {
+    slices: Vec<Slice>,
+}
+
+impl SliceStateManagerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an infallible reducer for the top-level key `key`.
+    ///
+    /// The reducer receives the slice's current value and the action in the
+    /// legacy `{ type, payload }` shape, and returns the slice's next value.
+    /// Returning the current value unchanged marks the slice as untouched.
+    pub fn slice<F>(self, key: impl Into<String>, reducer: F) -> Self
+    where
+        F: Fn(&JsonValue, &JsonValue) -> JsonValue + Send + Sync + 'static,
+    {
+        self.try_slice(key, move |state, action| Ok(reducer(state, action)))
+    }
+
+    /// Register a reducer that can reject an action. If any slice reducer
+    /// fails, no slice is updated and the error is returned from the
+    /// dispatch.
+    pub fn try_slice<F>(mut self, key: impl Into<String>, reducer: F) -> Self
+    where
+        F: Fn(&JsonValue, &JsonValue) -> Result<JsonValue> + Send + Sync + 'static,
+    {
+        self.slices.push(Slice {
+            key: key.into(),
+            reducer: Box::new(reducer),
+        });
+        self
+    }
+
+    /// Seed each slice by running its reducer against a `null` state and a
+    /// [`SLICE_INIT_ACTION_TYPE`] action, then build the manager. Fails if a
+    /// key was registered twice or a reducer rejects the init action.
+    pub fn build(self) -> Result<SliceStateManager> {
+        let init = serde_json::json!({ "type": SLICE_INIT_ACTION_TYPE, "payload": null });
+        let mut state = serde_json::Map::new();
+        for slice in &self.slices {
+            if state.contains_key(&slice.key) {
+                return Err(ZubridgeError::StateError(format!(
+                    "duplicate slice key: {}",
+                    slice.key
+                )));
+            }
+            let initial = (slice.reducer)(&JsonValue::Null, &init)?;
+            state.insert(slice.key.clone(), initial);
+        }
+        Ok(SliceStateManager {
+            slices: self.slices,
+            state,
+            last_changed: Vec::new(),
+        })
+    }
+}
+
+// ── SliceStateManager ─────────────────────────────────────────────────────────
+
+/// A state manager composed of one reducer per top-level key.
+///
+/// Every action is passed to every slice reducer; only slices whose value
+/// changed are written back and reported by
+/// [`last_changed_keys`](Self::last_changed_keys).
+pub struct SliceStateManager {
+    slices: Vec<Slice>,
+    state: serde_json::Map<String, JsonValue>,
+    last_changed: Vec<String>,
+}
+
+impl SliceStateManager {
+    pub fn builder() -> SliceStateManagerBuilder {
+        SliceStateManagerBuilder::new()
+    }
+
+    /// Top-level keys changed by the most recent successful dispatch, in
+    /// slice registration order. Empty before the first dispatch and after a
+    /// dispatch that changed nothing.
+    pub fn last_changed_keys(&self) -> &[String] {
+        &self.last_changed
+    }
+
+    /// Registered slice keys, in registration order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.slices.iter().map(|slice| slice.key.as_str())
+    }
+}
+
+impl TryStateManager for SliceStateManager {
+    fn get_initial_state(&self) -> JsonValue {
+        JsonValue::Object(self.state.clone())
+    }
+
+    fn try_dispatch_action(&mut self, action: JsonValue) -> Result<JsonValue> {
+        // Reduce every slice before writing any back, so a rejecting reducer
+        // leaves the whole state untouched.
+        let mut updates = Vec::new();
+        for slice in &self.slices {
+            let current = self.state.get(&slice.key).unwrap_or(&JsonValue::Null);
+            let next = (slice.reducer)(current, &action)?;
+            if &next != current {
+                updates.push((slice.key.clone(), next));
+            }
+        }
+
+        self.last_changed.clear();
+        for (key, value) in updates {
+            self.last_changed.push(key.clone());
+            self.state.insert(key, value);
+        }
+        Ok(self.get_initial_state())
+    }
+
+    fn changed_keys(&self) -> Option<Vec<String>> {
+        Some(self.last_changed.clone())
+    }
+
+    /// Restore each registered slice present in `state` directly, without
+    /// running its reducer. Unknown keys are ignored.
+    fn hydrate(&mut self, state: JsonValue) -> Result<()> {
+        let JsonValue::Object(mut persisted) = state else {
+            return Err(ZubridgeError::StateError(
+                "persisted slice state must be an object".to_string(),
+            ));
+        };
+        self.last_changed.clear();
+        for slice in &self.slices {
+            if let Some(value) = persisted.remove(&slice.key) {
+                self.last_changed.push(slice.key.clone());
+                self.state.insert(slice.key.clone(), value);
+            }
+        }
+        Ok(())
+    }
+
+    fn supports_hydrate(&self) -> bool {
+        true
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn counter_reducer(state: &JsonValue, action: &JsonValue) -> JsonValue {
+        let value = state.as_i64().unwrap_or(0);
+        match action["type"].as_str() {
+            Some("COUNTER:INCREMENT") => json!(value + 1),
+            Some("COUNTER:SET") => action["payload"].clone(),
+            Some("STATE:RESET") => json!(0),
+            _ => json!(value),
+        }
+    }
+
+    fn theme_reducer(state: &JsonValue, action: &JsonValue) -> JsonValue {
+        match action["type"].as_str() {
+            Some("THEME:TOGGLE") if state == "dark" => json!("light"),
+            Some("THEME:TOGGLE") => json!("dark"),
+            Some("STATE:RESET") => json!("dark"),
+            _ if state.is_null() => json!("dark"),
+            _ => state.clone(),
+        }
+    }
+
+    fn manager() -> SliceStateManager {
+        SliceStateManager::builder()
+            .slice("counter", counter_reducer)
+            .slice("theme", theme_reducer)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn build_seeds_initial_state_from_reducers() {
+        let manager = manager();
+        assert_eq!(
+            manager.get_initial_state(),
+            json!({ "counter": 0, "theme": "dark" })
+        );
+        assert!(manager.last_changed_keys().is_empty());
+    }
+
+    #[test]
+    fn dispatch_records_only_the_touched_slice() {
+        let mut manager = manager();
+        let state = manager
+            .try_dispatch_action(json!({ "type": "COUNTER:INCREMENT" }))
+            .unwrap();
+        assert_eq!(state, json!({ "counter": 1, "theme": "dark" }));
+        assert_eq!(manager.last_changed_keys(), ["counter".to_string()]);
+
+        manager
+            .try_dispatch_action(json!({ "type": "THEME:TOGGLE" }))
+            .unwrap();
+        assert_eq!(manager.last_changed_keys(), ["theme".to_string()]);
+    }
+
+    #[test]
+    fn action_touching_several_slices_records_each() {
+        let mut manager = manager();
+        manager
+            .try_dispatch_action(json!({ "type": "COUNTER:SET", "payload": 4 }))
+            .unwrap();
+        manager
+            .try_dispatch_action(json!({ "type": "THEME:TOGGLE" }))
+            .unwrap();
+        manager
+            .try_dispatch_action(json!({ "type": "STATE:RESET" }))
+            .unwrap();
+        assert_eq!(
+            manager.last_changed_keys(),
+            ["counter".to_string(), "theme".to_string()]
+        );
+    }
+
+    #[test]
+    fn no_op_action_records_nothing() {
+        let mut manager = manager();
+        manager
+            .try_dispatch_action(json!({ "type": "COUNTER:INCREMENT" }))
+            .unwrap();
+        manager
+            .try_dispatch_action(json!({ "type": "UNRELATED" }))
+            .unwrap();
+        assert!(manager.last_changed_keys().is_empty());
+    }
+
+    #[test]
+    fn rejecting_reducer_leaves_every_slice_unchanged() {
+        let mut manager = SliceStateManager::builder()
+            .slice("counter", counter_reducer)
+            .try_slice("guard", |state, action| {
+                if action["type"] == "FORBIDDEN" {
+                    return Err(ZubridgeError::ActionProcessing("forbidden".into()));
+                }
+                Ok(if state.is_null() {
+                    json!(true)
+                } else {
+                    state.clone()
+                })
+            })
+            .build()
+            .unwrap();
+        manager
+            .try_dispatch_action(json!({ "type": "COUNTER:INCREMENT" }))
+            .unwrap();
+
+        let err = manager
+            .try_dispatch_action(json!({ "type": "FORBIDDEN" }))
+            .unwrap_err();
+        assert!(matches!(err, ZubridgeError::ActionProcessing(_)));
+        assert_eq!(manager.get_initial_state()["counter"], 1);
+        assert_eq!(manager.last_changed_keys(), ["counter".to_string()]);
+    }
+
+    #[test]
+    fn duplicate_slice_key_is_rejected() {
+        let result = SliceStateManager::builder()
+            .slice("counter", counter_reducer)
+            .slice("counter", counter_reducer)
+            .build();
+        assert!(matches!(result, Err(ZubridgeError::StateError(_))));
+    }
+}
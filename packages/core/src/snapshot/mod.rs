@@ -0,0 +1,231 @@
+//! Structurally shared state snapshots.
+//!
+//! A [`Snapshot`] holds an object state as one `Arc` per top-level key behind
+//! an `Arc` root, so cloning one is O(1) and filtering one to a subscription
+//! copies pointers rather than values. Snapshots built with
+//! [`Snapshot::share`] reuse the previous snapshot's `Arc` for every key whose
+//! value didn't change, which lets delta computation skip unchanged keys by
+//! pointer equality instead of a deep `==`, and lets every webview's delta
+//! baseline point at the same values.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::{Serialize, Serializer};
+
+use crate::models::JsonValue;
+use crate::subscription::{self, StatePath};
+
+#[derive(Debug)]
+enum Root {
+    /// An object state, one shared value per top-level key.
+    Object(BTreeMap<String, Arc<JsonValue>>),
+    /// Any other state; never diffed, so kept whole.
+    Value(JsonValue),
+}
+
+/// An immutable, structurally shared state snapshot. See the module docs.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    root: Arc<Root>,
+}
+
+impl From<JsonValue> for Snapshot {
+    fn from(state: JsonValue) -> Self {
+        Self::from_value(state)
+    }
+}
+
+impl Serialize for Snapshot {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match &*self.root {
+            Root::Object(entries) => {
+                serializer.collect_map(entries.iter().map(|(key, value)| (key, &**value)))
+            }
+            Root::Value(value) => value.serialize(serializer),
+        }
+    }
+}
+
+impl Default for Snapshot {
+    fn default() -> Self {
+        Self::from_entries(BTreeMap::new())
+    }
+}
+
+impl Snapshot {
+    /// Wrap `state` without sharing any values.
+    pub fn from_value(state: JsonValue) -> Self {
+        match state {
+            JsonValue::Object(map) => Self::from_entries(
+                map.into_iter()
+                    .map(|(key, value)| (key, Arc::new(value)))
+                    .collect(),
+            ),
+            other => Self {
+                root: Arc::new(Root::Value(other)),
+            },
+        }
+    }
+
+    /// Wrap `state`, reusing `prev`'s value for every top-level key whose
+    /// value is unchanged.
+    pub fn share(state: JsonValue, prev: &Snapshot) -> Self {
+        Self::share_where(state, prev, |_| true)
+    }
+
+    /// Like [`share`](Self::share), but only compares the keys in `changed`:
+    /// every other key present in `prev` is assumed unchanged and reused
+    /// without comparison.
+    pub fn share_except(state: JsonValue, prev: &Snapshot, changed: &[String]) -> Self {
+        Self::share_where(state, prev, |key| changed.iter().any(|c| c == key))
+    }
+
+    fn share_where(state: JsonValue, prev: &Snapshot, compare: impl Fn(&str) -> bool) -> Self {
+        let JsonValue::Object(map) = state else {
+            return Self::from_value(state);
+        };
+        let Some(prev_entries) = prev.entries() else {
+            return Self::from_value(JsonValue::Object(map));
+        };
+        Self::from_entries(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let shared = match prev_entries.get(&key) {
+                        Some(prev_value) if !compare(&key) || **prev_value == value => {
+                            prev_value.clone()
+                        }
+                        _ => Arc::new(value),
+                    };
+                    (key, shared)
+                })
+                .collect(),
+        )
+    }
+
+    /// Build an object snapshot from already-shared entries.
+    pub fn from_entries(entries: BTreeMap<String, Arc<JsonValue>>) -> Self {
+        Self {
+            root: Arc::new(Root::Object(entries)),
+        }
+    }
+
+    /// The top-level entries, or `None` for a non-object state.
+    pub fn entries(&self) -> Option<&BTreeMap<String, Arc<JsonValue>>> {
+        match &*self.root {
+            Root::Object(entries) => Some(entries),
+            Root::Value(_) => None,
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Arc<JsonValue>> {
+        self.entries()?.get(key)
+    }
+
+    /// Resolve `path` against the snapshot.
+    pub fn get_path(&self, path: &StatePath) -> Option<&JsonValue> {
+        match &*self.root {
+            Root::Object(entries) => {
+                let (first, rest) = path.segments().split_first()?;
+                let value = entries.get(first)?;
+                subscription::resolve(rest, value)
+            }
+            Root::Value(value) => path.get(value),
+        }
+    }
+
+    /// A snapshot holding only `keys`, sharing their values with `self`.
+    /// Keys missing from `self` are skipped; a non-object snapshot is
+    /// returned as is.
+    pub fn select<'a>(&self, keys: impl IntoIterator<Item = &'a str>) -> Snapshot {
+        let Some(entries) = self.entries() else {
+            return self.clone();
+        };
+        Self::from_entries(
+            keys.into_iter()
+                .filter_map(|key| Some((key.to_string(), entries.get(key)?.clone())))
+                .collect(),
+        )
+    }
+
+    /// True if both snapshots are the same allocation — i.e. one is a clone
+    /// of the other.
+    pub fn ptr_eq(a: &Snapshot, b: &Snapshot) -> bool {
+        Arc::ptr_eq(&a.root, &b.root)
+    }
+
+    /// Materialize the snapshot as a plain JSON value (deep clone).
+    pub fn to_value(&self) -> JsonValue {
+        match &*self.root {
+            Root::Object(entries) => JsonValue::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| (key.clone(), (**value).clone()))
+                    .collect(),
+            ),
+            Root::Value(value) => value.clone(),
+        }
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn share_reuses_unchanged_values() {
+        let first = Snapshot::from_value(json!({ "a": [1, 2, 3], "b": { "x": 1 } }));
+        let second = Snapshot::share(json!({ "a": [1, 2, 3], "b": { "x": 2 } }), &first);
+        assert!(Arc::ptr_eq(
+            first.get("a").unwrap(),
+            second.get("a").unwrap()
+        ));
+        assert!(!Arc::ptr_eq(
+            first.get("b").unwrap(),
+            second.get("b").unwrap()
+        ));
+        assert_eq!(
+            second.to_value(),
+            json!({ "a": [1, 2, 3], "b": { "x": 2 } })
+        );
+    }
+
+    #[test]
+    fn share_except_trusts_the_changed_key_list() {
+        let first = Snapshot::from_value(json!({ "a": 1, "b": 2 }));
+        // `b` is not listed, so its stale value is kept without comparison.
+        let second = Snapshot::share_except(json!({ "a": 5, "b": 3 }), &first, &["a".into()]);
+        assert_eq!(second.to_value(), json!({ "a": 5, "b": 2 }));
+    }
+
+    #[test]
+    fn select_shares_values_and_skips_missing_keys() {
+        let full = Snapshot::from_value(json!({ "a": 1, "b": 2 }));
+        let scoped = full.select(["a", "missing"]);
+        assert_eq!(scoped.to_value(), json!({ "a": 1 }));
+        assert!(Arc::ptr_eq(
+            full.get("a").unwrap(),
+            scoped.get("a").unwrap()
+        ));
+    }
+
+    #[test]
+    fn get_path_resolves_nested_values() {
+        let snapshot = Snapshot::from_value(json!({ "user": { "tags": ["x", "y"] } }));
+        assert_eq!(
+            snapshot.get_path(&StatePath::parse("user.tags.1")),
+            Some(&json!("y"))
+        );
+        assert_eq!(snapshot.get_path(&StatePath::parse("user.name")), None);
+    }
+
+    #[test]
+    fn non_object_state_is_kept_whole() {
+        let snapshot = Snapshot::share(json!(42), &Snapshot::from_value(json!({ "a": 1 })));
+        assert!(snapshot.entries().is_none());
+        assert_eq!(snapshot.to_value(), json!(42));
+    }
+}
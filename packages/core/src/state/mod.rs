@@ -1,22 +1,37 @@
 use std::sync::{Arc, Mutex};
 
 use crate::error::{Result, ZubridgeError};
-use crate::models::{JsonValue, StateManager};
+use crate::models::{JsonValue, TryStateManager};
 
-/// Thread-safe handle to a [`StateManager`] implementation.
-pub type StateManagerHandle = Arc<Mutex<dyn StateManager>>;
+/// Thread-safe handle to a [`TryStateManager`] implementation. Any
+/// [`StateManager`](crate::models::StateManager) qualifies via the blanket impl.
+pub type StateManagerHandle = Arc<Mutex<dyn TryStateManager>>;
 
 /// Wrap a concrete state manager in a thread-safe handle.
-pub fn new_handle<S: StateManager>(state_manager: S) -> StateManagerHandle {
+pub fn new_handle<S: TryStateManager>(state_manager: S) -> StateManagerHandle {
     Arc::new(Mutex::new(state_manager))
 }
 
 /// Apply an action via the supplied state manager. Returns the new state.
+///
+/// Errors raised by the state manager are returned unchanged, so a typed
+/// rejection such as [`ZubridgeError::InvalidAction`] or
+/// [`ZubridgeError::UnknownActionType`] reaches the caller as that variant.
 pub fn dispatch(handle: &StateManagerHandle, action: JsonValue) -> Result<JsonValue> {
+    dispatch_tracked(handle, action).map(|(state, _)| state)
+}
+
+/// [`dispatch`], also returning the keys the action changed as reported by
+/// [`TryStateManager::changed_keys`]. Both are read under the same lock.
+pub fn dispatch_tracked(
+    handle: &StateManagerHandle,
+    action: JsonValue,
+) -> Result<(JsonValue, Option<Vec<String>>)> {
     let mut guard = handle
         .lock()
         .map_err(|e| ZubridgeError::StateError(e.to_string()))?;
-    Ok(guard.dispatch_action(action))
+    let state = guard.try_dispatch_action(action)?;
+    Ok((state, guard.changed_keys()))
 }
 
 /// Read the current state via the supplied state manager.
@@ -26,3 +41,77 @@ pub fn read_state(handle: &StateManagerHandle) -> Result<JsonValue> {
         .map_err(|e| ZubridgeError::StateError(e.to_string()))?;
     Ok(guard.get_initial_state())
 }
+
+/// Read the action types declared by the supplied state manager.
+pub fn action_types(handle: &StateManagerHandle) -> Result<Option<Vec<String>>> {
+    let guard = handle
+        .lock()
+        .map_err(|e| ZubridgeError::StateError(e.to_string()))?;
+    Ok(guard.action_types())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::StateManager;
+    use serde_json::json;
+
+    struct Counter(i64);
+
+    impl StateManager for Counter {
+        fn get_initial_state(&self) -> JsonValue {
+            json!({ "counter": self.0 })
+        }
+
+        fn dispatch_action(&mut self, _action: JsonValue) -> JsonValue {
+            self.0 += 1;
+            json!({ "counter": self.0 })
+        }
+    }
+
+    struct Strict(i64);
+
+    impl TryStateManager for Strict {
+        fn get_initial_state(&self) -> JsonValue {
+            json!({ "counter": self.0 })
+        }
+
+        fn try_dispatch_action(&mut self, action: JsonValue) -> Result<JsonValue> {
+            match action["type"].as_str() {
+                Some("INCREMENT") => {
+                    self.0 += 1;
+                    Ok(json!({ "counter": self.0 }))
+                }
+                Some("BAD_PAYLOAD") => Err(ZubridgeError::Serialization("bad payload".into())),
+                other => Err(ZubridgeError::ActionProcessing(format!(
+                    "unknown action type: {other:?}"
+                ))),
+            }
+        }
+    }
+
+    #[test]
+    fn infallible_manager_dispatches_through_blanket_impl() {
+        let handle = new_handle(Counter(0));
+        let state = dispatch(&handle, json!({ "type": "ANY" })).unwrap();
+        assert_eq!(state["counter"], 1);
+    }
+
+    #[test]
+    fn rejected_action_leaves_state_unchanged() {
+        let handle = new_handle(Strict(3));
+        let err = dispatch(&handle, json!({ "type": "NOPE" })).unwrap_err();
+        assert!(matches!(err, ZubridgeError::ActionProcessing(_)));
+        assert_eq!(read_state(&handle).unwrap()["counter"], 3);
+    }
+
+    #[test]
+    fn other_errors_pass_through_unchanged() {
+        let handle = new_handle(Strict(0));
+        let err = dispatch(&handle, json!({ "type": "BAD_PAYLOAD" })).unwrap_err();
+        match err {
+            ZubridgeError::Serialization(msg) => assert!(msg.contains("bad payload")),
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+}
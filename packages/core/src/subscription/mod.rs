@@ -1,11 +1,198 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 
+use crate::access::WILDCARD_KEY;
+use crate::deltas::DiffState;
 use crate::models::JsonValue;
+use crate::snapshot::Snapshot;
 
-/// Tracks which top-level state keys each webview is subscribed to.
+// ── StatePath ─────────────────────────────────────────────────────────────────
+
+/// A path into the state tree, parsed from a subscription key.
+///
+/// Keys are either dotted (`user.profile.name`), slash-separated
+/// (`items/3`), or RFC 6901 JSON Pointers (`/items/3`, with `~1` and `~0`
+/// escaping `/` and `~`). A key with no separator is a top-level key. A
+/// segment addresses an object member or, on an array, the element at that
+/// index.
+///
+/// Resolved against a state with [`parse_in`](Self::parse_in), a key that
+/// names a top-level member exactly (`user.settings`, `a/b`) stays that
+/// member rather than being split.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StatePath {
+    segments: Vec<String>,
+}
+
+impl StatePath {
+    pub fn parse(key: &str) -> Self {
+        let segments = if let Some(pointer) = key.strip_prefix('/') {
+            pointer
+                .split('/')
+                .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+                .collect()
+        } else if key.contains('/') {
+            key.split('/').map(str::to_string).collect()
+        } else {
+            key.split('.').map(str::to_string).collect()
+        };
+        Self { segments }
+    }
+
+    /// Parse `key` as a path into `state`: a key naming a top-level member of
+    /// `state` exactly is that member, even when it contains `.` or `/`;
+    /// other keys are split as by [`parse`](Self::parse). JSON Pointers are
+    /// always parsed as pointers.
+    pub fn parse_in<S: DiffState + ?Sized>(key: &str, state: &S) -> Self {
+        if !key.starts_with('/') && state.member(key).is_some() {
+            return Self {
+                segments: vec![key.to_string()],
+            };
+        }
+        Self::parse(key)
+    }
+
+    pub fn segments(&self) -> &[String] {
+        &self.segments
+    }
+
+    /// True for a single-segment path, i.e. a plain top-level key.
+    pub fn is_top_level(&self) -> bool {
+        self.segments.len() == 1
+    }
+
+    /// True if `self` equals `ancestor` or lies beneath it.
+    pub fn starts_with(&self, ancestor: &StatePath) -> bool {
+        self.segments.starts_with(&ancestor.segments)
+    }
+
+    /// The dotted form used for path-keyed delta entries, or `None` if a
+    /// segment is empty or itself contains a `.`.
+    pub fn to_dotted(&self) -> Option<String> {
+        if self.segments.is_empty()
+            || self
+                .segments
+                .iter()
+                .any(|segment| segment.is_empty() || segment.contains('.'))
+        {
+            return None;
+        }
+        Some(self.segments.join("."))
+    }
+
+    /// Resolve the path against `state`.
+    pub fn get<'a>(&self, state: &'a JsonValue) -> Option<&'a JsonValue> {
+        resolve(&self.segments, state)
+    }
+}
+
+/// Resolve `segments` against `state`; see [`StatePath::get`].
+pub(crate) fn resolve<'a>(segments: &[String], state: &'a JsonValue) -> Option<&'a JsonValue> {
+    segments.iter().try_fold(state, |node, segment| match node {
+        JsonValue::Object(map) => map.get(segment),
+        JsonValue::Array(items) => items.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+/// Parse `keys` into paths into `state` (see [`StatePath::parse_in`]),
+/// dropping any path already covered by another (`user` covers `user.name`).
+/// The result is sorted.
+pub fn normalize_paths<'a, S: DiffState + ?Sized>(
+    state: &S,
+    keys: impl IntoIterator<Item = &'a str>,
+) -> Vec<StatePath> {
+    let mut paths: Vec<StatePath> = keys
+        .into_iter()
+        .map(|key| StatePath::parse_in(key, state))
+        .collect();
+    paths.sort_by_key(|path| path.segments.len());
+    let mut kept: Vec<StatePath> = Vec::with_capacity(paths.len());
+    for path in paths {
+        if !kept.iter().any(|ancestor| path.starts_with(ancestor)) {
+            kept.push(path);
+        }
+    }
+    kept.sort();
+    kept
+}
+
+/// Build the minimal tree holding exactly the values at `keys`.
+///
+/// Intermediate nodes keep the type they have in `state`: a path through an
+/// array yields an array with the element at its original index and `null`
+/// in the positions before it, so `items/3` filters `{"items": [..]}` to
+/// `{"items": [null, null, null, ..]}` and `state.items[3]` resolves the same
+/// way on the renderer. Paths missing from `state` are omitted. A non-object
+/// `state` is returned unchanged.
+pub fn filter_paths<'a>(state: &JsonValue, keys: impl IntoIterator<Item = &'a str>) -> JsonValue {
+    if !state.is_object() {
+        return state.clone();
+    }
+    let mut filtered = JsonValue::Object(serde_json::Map::new());
+    for path in normalize_paths(state, keys) {
+        let Some(value) = path.get(state) else {
+            continue;
+        };
+        if path.segments.is_empty() {
+            // The empty pointer addresses the whole document.
+            return state.clone();
+        }
+        insert_at(&mut filtered, state, &path.segments, value.clone());
+    }
+    filtered
+}
+
+/// Insert `value` at `segments` below `node`, where `node` mirrors `source`.
+/// Intermediate nodes are created as empty arrays or objects matching their
+/// counterparts in `source`; array elements before the inserted index are
+/// padded with `null`.
+fn insert_at(node: &mut JsonValue, source: &JsonValue, segments: &[String], value: JsonValue) {
+    let Some((first, rest)) = segments.split_first() else {
+        return;
+    };
+    let Some(child_source) = resolve(std::slice::from_ref(first), source) else {
+        return;
+    };
+    let slot = match node {
+        JsonValue::Object(map) => map.entry(first.clone()).or_insert(JsonValue::Null),
+        JsonValue::Array(items) => {
+            let Ok(index) = first.parse::<usize>() else {
+                return;
+            };
+            if items.len() <= index {
+                items.resize(index + 1, JsonValue::Null);
+            }
+            &mut items[index]
+        }
+        _ => return,
+    };
+    if rest.is_empty() {
+        *slot = value;
+        return;
+    }
+    if slot.is_null() {
+        *slot = empty_like(child_source);
+    }
+    insert_at(slot, child_source, rest, value);
+}
+
+/// An empty array if `source` is an array, otherwise an empty object.
+fn empty_like(source: &JsonValue) -> JsonValue {
+    if source.is_array() {
+        JsonValue::Array(Vec::new())
+    } else {
+        JsonValue::Object(serde_json::Map::new())
+    }
+}
+
+// ── SubscriptionManager ───────────────────────────────────────────────────────
+
+/// Tracks which state paths each webview is subscribed to.
 ///
-/// A webview with no entry receives every key (default-all). Once a webview has
-/// any explicit subscription, only the keys in its set are forwarded.
+/// A webview with no entry receives every key (default-all), as does one
+/// subscribed to [`WILDCARD_KEY`]. Otherwise only the paths in its set are
+/// forwarded. See [`StatePath`] for the accepted key forms.
 #[derive(Debug, Default)]
 pub struct SubscriptionManager {
     by_label: HashMap<String, HashSet<String>>,
@@ -64,15 +251,47 @@ impl SubscriptionManager {
         self.by_label.remove(label);
     }
 
-    /// Filter `state` to only the keys this webview is subscribed to. If the
-    /// webview has no explicit subscription, the full state is returned.
+    /// The subscription set scoping what `label` receives, or `None` if it
+    /// receives everything (no entry, or subscribed to [`WILDCARD_KEY`]).
+    fn scoped_keys(&self, label: &str) -> Option<&HashSet<String>> {
+        self.by_label
+            .get(label)
+            .filter(|keys| !keys.contains(WILDCARD_KEY))
+    }
+
+    /// Subscribed paths for `label` into `state`, normalized by
+    /// [`normalize_paths`], when at least one of them is nested. `None` for
+    /// unscoped labels and for labels subscribed only to top-level keys, whose
+    /// deltas are computed per top-level key.
+    pub fn nested_paths_for<S: DiffState + ?Sized>(
+        &self,
+        label: &str,
+        state: &S,
+    ) -> Option<Vec<StatePath>> {
+        let keys = self.scoped_keys(label)?;
+        let paths = normalize_paths(state, keys.iter().map(String::as_str));
+        paths
+            .iter()
+            .any(|path| !path.is_top_level())
+            .then_some(paths)
+    }
+
+    /// Filter `state` to only the paths this webview is subscribed to (see
+    /// [`filter_paths`]). If the webview has no explicit subscription or is
+    /// subscribed to [`WILDCARD_KEY`], the full state is returned.
     pub fn filter_for(&self, label: &str, state: &JsonValue) -> JsonValue {
-        let Some(keys) = self.by_label.get(label) else {
+        let Some(keys) = self.scoped_keys(label) else {
             return state.clone();
         };
         let JsonValue::Object(map) = state else {
             return state.clone();
         };
+        if keys
+            .iter()
+            .any(|key| !StatePath::parse_in(key, state).is_top_level())
+        {
+            return filter_paths(state, keys.iter().map(String::as_str));
+        }
         let mut filtered = serde_json::Map::with_capacity(keys.len());
         for key in keys {
             if let Some(value) = map.get(key) {
@@ -81,8 +300,65 @@ impl SubscriptionManager {
         }
         JsonValue::Object(filtered)
     }
+
+    /// True if a change to the top-level `keys` can affect what `label`
+    /// receives: a default-all label is affected by any key, a subscribed
+    /// one only by keys it names exactly or its paths start with.
+    pub fn intersects(&self, label: &str, keys: &[String]) -> bool {
+        let Some(subscribed) = self.scoped_keys(label) else {
+            return !keys.is_empty();
+        };
+        subscribed.iter().any(|sub| {
+            let path = StatePath::parse(sub);
+            keys.contains(sub)
+                || path
+                    .segments
+                    .first()
+                    .is_some_and(|first| keys.contains(first))
+        })
+    }
+
+    /// [`filter_for`](Self::filter_for) over a [`Snapshot`]. Subscribed
+    /// top-level keys share their values with `snapshot`, so webviews with
+    /// the same subscription get equal (and cheaply cloned) results; only the
+    /// partial trees built for nested paths are copied.
+    pub fn filter_snapshot(&self, label: &str, snapshot: &Snapshot) -> Snapshot {
+        let Some(keys) = self.scoped_keys(label) else {
+            return snapshot.clone();
+        };
+        let Some(entries) = snapshot.entries() else {
+            return snapshot.clone();
+        };
+        let mut shared = BTreeMap::new();
+        let mut partial = serde_json::Map::new();
+        for path in normalize_paths(snapshot, keys.iter().map(String::as_str)) {
+            let Some((first, rest)) = path.segments.split_first() else {
+                // The empty pointer addresses the whole document.
+                return snapshot.clone();
+            };
+            let Some(value) = entries.get(first) else {
+                continue;
+            };
+            if rest.is_empty() {
+                shared.insert(first.clone(), value.clone());
+            } else if let Some(leaf) = resolve(rest, value) {
+                let root = partial
+                    .entry(first.clone())
+                    .or_insert_with(|| empty_like(value));
+                insert_at(root, value, rest, leaf.clone());
+            }
+        }
+        shared.extend(
+            partial
+                .into_iter()
+                .map(|(key, value)| (key, Arc::new(value))),
+        );
+        Snapshot::from_entries(shared)
+    }
 }
 
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,4 +393,159 @@ mod tests {
         let state = serde_json::json!({ "a": 1, "b": 2 });
         assert_eq!(m.filter_for("main", &state), serde_json::json!({ "a": 1 }));
     }
+
+    #[test]
+    fn wildcard_subscription_receives_everything() {
+        let mut m = SubscriptionManager::new();
+        m.subscribe("main", &["*".into(), "a".into()]);
+        let state = serde_json::json!({ "a": 1, "b": 2 });
+        assert_eq!(m.filter_for("main", &state), state);
+        let snapshot = Snapshot::from_value(state.clone());
+        assert!(Snapshot::ptr_eq(
+            &m.filter_snapshot("main", &snapshot),
+            &snapshot
+        ));
+        assert!(m.intersects("main", &["b".to_string()]));
+        assert_eq!(m.nested_paths_for("main", &state), None);
+    }
+
+    #[test]
+    fn paths_parse_dotted_slash_and_pointer_forms() {
+        let segments = |key: &str| StatePath::parse(key).segments().to_vec();
+        assert_eq!(segments("user.profile.name"), ["user", "profile", "name"]);
+        assert_eq!(segments("items/3"), ["items", "3"]);
+        assert_eq!(segments("/items/3"), ["items", "3"]);
+        assert_eq!(segments("/a~1b/c~0d"), ["a/b", "c~d"]);
+        assert_eq!(segments("/a.b"), ["a.b"]);
+        assert_eq!(StatePath::parse("/a.b").to_dotted(), None);
+    }
+
+    #[test]
+    fn filter_builds_minimal_tree_for_nested_paths() {
+        let mut m = SubscriptionManager::new();
+        m.subscribe(
+            "main",
+            &["settings.theme".into(), "user/profile/name".into()],
+        );
+        let state = serde_json::json!({
+            "settings": { "theme": "dark", "lang": "en" },
+            "user": { "profile": { "name": "Ada", "age": 36 }, "id": 1 },
+            "counter": 5
+        });
+        assert_eq!(
+            m.filter_for("main", &state),
+            serde_json::json!({
+                "settings": { "theme": "dark" },
+                "user": { "profile": { "name": "Ada" } }
+            })
+        );
+    }
+
+    #[test]
+    fn exact_top_level_keys_are_not_split() {
+        let mut m = SubscriptionManager::new();
+        m.subscribe(
+            "main",
+            &["user.settings".into(), "a/b".into(), "user.name".into()],
+        );
+        let state = serde_json::json!({
+            "user.settings": 1,
+            "a/b": 2,
+            "user": { "settings": 3, "name": "Ada" }
+        });
+        let expected = serde_json::json!({
+            "user.settings": 1,
+            "a/b": 2,
+            "user": { "name": "Ada" }
+        });
+        assert_eq!(m.filter_for("main", &state), expected);
+        let snapshot = Snapshot::from_value(state.clone());
+        assert_eq!(m.filter_snapshot("main", &snapshot).to_value(), expected);
+        let segments = |key: &str| StatePath::parse_in(key, &state).segments().to_vec();
+        assert_eq!(segments("user.settings"), ["user.settings"]);
+        assert_eq!(segments("user.name"), ["user", "name"]);
+        assert_eq!(segments("/user/settings"), ["user", "settings"]);
+        assert!(m.intersects("main", &["user.settings".to_string()]));
+    }
+
+    #[test]
+    fn filter_indexes_into_arrays_and_skips_missing_paths() {
+        let mut m = SubscriptionManager::new();
+        m.subscribe(
+            "main",
+            &["items/1".into(), "items/9".into(), "missing.key".into()],
+        );
+        let state = serde_json::json!({ "items": ["a", "b", "c"] });
+        assert_eq!(
+            m.filter_for("main", &state),
+            serde_json::json!({ "items": [null, "b"] })
+        );
+    }
+
+    #[test]
+    fn filter_keeps_arrays_along_nested_paths() {
+        let mut m = SubscriptionManager::new();
+        m.subscribe("main", &["items/2/done".into(), "items/0/title".into()]);
+        let state = serde_json::json!({
+            "items": [
+                { "title": "a", "done": false },
+                { "title": "b", "done": true },
+                { "title": "c", "done": true }
+            ]
+        });
+        let expected = serde_json::json!({
+            "items": [{ "title": "a" }, null, { "done": true }]
+        });
+        assert_eq!(m.filter_for("main", &state), expected);
+        let snapshot = Snapshot::from_value(state);
+        assert_eq!(m.filter_snapshot("main", &snapshot).to_value(), expected);
+    }
+
+    #[test]
+    fn ancestor_subscription_covers_nested_paths() {
+        let mut m = SubscriptionManager::new();
+        m.subscribe("main", &["user".into(), "user.profile.name".into()]);
+        let state = serde_json::json!({ "user": { "profile": { "name": "Ada" }, "id": 1 } });
+        assert_eq!(m.filter_for("main", &state), state);
+        assert_eq!(
+            m.nested_paths_for("main", &state),
+            None,
+            "only the top-level `user` path remains after normalization"
+        );
+    }
+
+    #[test]
+    fn filter_snapshot_shares_top_level_values() {
+        let mut m = SubscriptionManager::new();
+        m.subscribe("main", &["a".to_string(), "c.x".to_string()]);
+        let snapshot = Snapshot::from_value(serde_json::json!({
+            "a": [1, 2, 3],
+            "b": 2,
+            "c": { "x": 1, "y": 2 }
+        }));
+        let scoped = m.filter_snapshot("main", &snapshot);
+        assert_eq!(
+            scoped.to_value(),
+            serde_json::json!({ "a": [1, 2, 3], "c": { "x": 1 } })
+        );
+        assert!(Arc::ptr_eq(
+            snapshot.get("a").unwrap(),
+            scoped.get("a").unwrap()
+        ));
+        assert!(Snapshot::ptr_eq(
+            &m.filter_snapshot("other", &snapshot),
+            &snapshot
+        ));
+    }
+
+    #[test]
+    fn intersects_matches_changed_keys_against_subscribed_roots() {
+        let mut m = SubscriptionManager::new();
+        m.subscribe("main", &["settings.theme".to_string(), "counter".to_string()]);
+        assert!(m.intersects("main", &["settings".to_string()]));
+        assert!(m.intersects("main", &["counter".to_string(), "x".to_string()]));
+        assert!(!m.intersects("main", &["user".to_string()]));
+        assert!(m.intersects("other", &["user".to_string()]));
+        assert!(!m.intersects("other", &[]));
+    }
 }
@@ -0,0 +1,220 @@
+//! Per-webview state-update throttling.
+//!
+//! [`UpdateThrottle`] decides, for each state change, whether a webview gets
+//! an update now or later. The first change after a quiet period is sent
+//! straight away and opens a window; changes inside the window are deferred
+//! and, once it elapses, sent as one update whose delta is computed against
+//! the last state the webview received — the merge of every deferred delta.
+//! Sequence numbers are only assigned when an update is actually emitted, so
+//! they stay gap-free.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::models::UpdateSource;
+
+/// Whether an update offered to [`UpdateThrottle::offer`] should be emitted.
+#[derive(Debug, Clone)]
+pub enum ThrottleDecision {
+    /// Emit now with the given provenance, which also covers any deferred
+    /// updates whose window elapsed before they were flushed. A new window
+    /// has opened for the webview.
+    EmitNow(Option<UpdateSource>),
+    /// The webview's window is open; the update will be returned from
+    /// [`UpdateThrottle::due`] once it elapses.
+    Deferred,
+}
+
+#[derive(Debug)]
+struct LabelWindow {
+    opened_at: Instant,
+    /// Provenance of the deferred updates, merged.
+    pending: Option<UpdateSource>,
+    has_pending: bool,
+}
+
+/// Coalesces state updates per webview within a fixed window.
+#[derive(Debug)]
+pub struct UpdateThrottle {
+    window: Duration,
+    by_label: HashMap<String, LabelWindow>,
+}
+
+impl UpdateThrottle {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            by_label: HashMap::new(),
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Offer an update for `label` produced at `now`, with its provenance.
+    pub fn offer(
+        &mut self,
+        label: &str,
+        source: Option<UpdateSource>,
+        now: Instant,
+    ) -> ThrottleDecision {
+        if let Some(state) = self.by_label.get_mut(label) {
+            if now.duration_since(state.opened_at) < self.window {
+                merge_source(&mut state.pending, source);
+                state.has_pending = true;
+                return ThrottleDecision::Deferred;
+            }
+        }
+        let mut pending = self.by_label.remove(label).and_then(|state| state.pending);
+        merge_source(&mut pending, source);
+        self.by_label.insert(
+            label.to_string(),
+            LabelWindow {
+                opened_at: now,
+                pending: None,
+                has_pending: false,
+            },
+        );
+        ThrottleDecision::EmitNow(pending)
+    }
+
+    /// Take the deferred updates of every webview whose window has elapsed
+    /// by `now`, with their merged provenance. Each returned webview starts
+    /// a new window, since the caller is about to emit to it; webviews with
+    /// nothing deferred go quiet, so their next update is sent immediately.
+    pub fn due(&mut self, now: Instant) -> Vec<(String, Option<UpdateSource>)> {
+        let window = self.window;
+        let mut due = Vec::new();
+        self.by_label.retain(|label, state| {
+            if now.duration_since(state.opened_at) < window {
+                return true;
+            }
+            if !state.has_pending {
+                return false;
+            }
+            due.push((label.clone(), state.pending.take()));
+            state.has_pending = false;
+            state.opened_at = now;
+            true
+        });
+        due.sort_by(|a, b| a.0.cmp(&b.0));
+        due
+    }
+
+    /// True if any webview has a deferred update.
+    pub fn has_pending(&self) -> bool {
+        self.by_label.values().any(|state| state.has_pending)
+    }
+
+    /// Drop the window for `label` (e.g. on webview close).
+    pub fn forget(&mut self, label: &str) {
+        self.by_label.remove(label);
+    }
+}
+
+fn merge_source(merged: &mut Option<UpdateSource>, later: Option<UpdateSource>) {
+    match (merged.as_mut(), later) {
+        (Some(merged), Some(later)) => merged.absorb(later),
+        (None, later) => *merged = later,
+        (Some(_), None) => {}
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_millis(16);
+
+    fn source(action_id: &str) -> Option<UpdateSource> {
+        Some(UpdateSource {
+            action_id: Some(action_id.to_string()),
+            ..UpdateSource::default()
+        })
+    }
+
+    fn action_id(decision: &ThrottleDecision) -> Option<&str> {
+        match decision {
+            ThrottleDecision::EmitNow(source) => source.as_ref()?.action_id.as_deref(),
+            ThrottleDecision::Deferred => None,
+        }
+    }
+
+    #[test]
+    fn first_update_is_sent_and_later_ones_are_deferred() {
+        let mut throttle = UpdateThrottle::new(WINDOW);
+        let start = Instant::now();
+        let first = throttle.offer("main", source("a1"), start);
+        assert_eq!(action_id(&first), Some("a1"));
+        let later = start + Duration::from_millis(5);
+        assert!(matches!(
+            throttle.offer("main", source("a2"), later),
+            ThrottleDecision::Deferred
+        ));
+        let other = throttle.offer("other", source("a2"), later);
+        assert_eq!(action_id(&other), Some("a2"));
+        assert!(throttle.due(start + Duration::from_millis(10)).is_empty());
+    }
+
+    #[test]
+    fn due_merges_the_provenance_of_deferred_updates() {
+        let mut throttle = UpdateThrottle::new(WINDOW);
+        let start = Instant::now();
+        throttle.offer("main", source("a1"), start);
+        throttle.offer("main", source("a2"), start + Duration::from_millis(1));
+        throttle.offer("main", None, start + Duration::from_millis(2));
+        throttle.offer("main", source("a3"), start + Duration::from_millis(3));
+        assert!(throttle.has_pending());
+
+        let due = throttle.due(start + WINDOW);
+        assert_eq!(due.len(), 1);
+        let (label, source) = &due[0];
+        assert_eq!(label, "main");
+        let source = source.as_ref().unwrap();
+        assert_eq!(source.action_id.as_deref(), Some("a3"));
+        assert_eq!(source.action_ids, vec!["a2", "a3"]);
+        assert!(!throttle.has_pending());
+    }
+
+    #[test]
+    fn quiet_windows_close_so_the_next_update_is_immediate() {
+        let mut throttle = UpdateThrottle::new(WINDOW);
+        let start = Instant::now();
+        throttle.offer("main", source("a1"), start);
+        assert!(throttle.due(start + WINDOW).is_empty());
+        let next = throttle.offer("main", source("a2"), start + WINDOW * 2);
+        assert_eq!(action_id(&next), Some("a2"));
+    }
+
+    #[test]
+    fn flushed_labels_start_a_new_window() {
+        let mut throttle = UpdateThrottle::new(WINDOW);
+        let start = Instant::now();
+        throttle.offer("main", source("a1"), start);
+        throttle.offer("main", source("a2"), start + Duration::from_millis(1));
+        let flushed_at = start + WINDOW;
+        assert_eq!(throttle.due(flushed_at).len(), 1);
+        assert!(matches!(
+            throttle.offer("main", source("a3"), flushed_at + Duration::from_millis(1)),
+            ThrottleDecision::Deferred
+        ));
+    }
+
+    #[test]
+    fn an_update_after_an_unflushed_window_carries_its_deferred_sources() {
+        let mut throttle = UpdateThrottle::new(WINDOW);
+        let start = Instant::now();
+        throttle.offer("main", source("a1"), start);
+        throttle.offer("main", source("a2"), start + Duration::from_millis(1));
+        let ThrottleDecision::EmitNow(Some(source)) =
+            throttle.offer("main", source("a3"), start + WINDOW)
+        else {
+            panic!("expected the update to be emitted");
+        };
+        assert_eq!(source.action_ids, vec!["a2", "a3"]);
+        assert!(!throttle.has_pending());
+    }
+}
@@ -11,7 +11,9 @@
 //! - `packages/electron/src/thunk/tracking/StateUpdateTracker.ts`
 
 use std::collections::{HashMap, HashSet};
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
 
 // ── ThunkState ────────────────────────────────────────────────────────────────
 
@@ -399,10 +401,42 @@ pub type ThunkRegistry = ThunkManager;
 
 /// Tracks which state-update events each webview has acknowledged.
 ///
-/// Carried forward unchanged from P1.
+/// With a [`max_in_flight`](Self::set_max_in_flight) limit set, also applies
+/// backpressure: a webview with that many unacked updates is *stalled* and
+/// receives nothing until it acks again, at which point it resumes with a
+/// full-state resync.
+///
+/// Each pending update records when it was sent, so acks feed a per-label
+/// [`AckLatencyHistogram`] and [`sweep_expired`](Self::sweep_expired) can
+/// treat overdue ones as lost.
 #[derive(Debug, Default)]
 pub struct StateUpdateTracker {
-    pending_by_label: HashMap<String, HashSet<String>>,
+    pending_by_label: HashMap<String, HashMap<String, Instant>>,
+    max_in_flight: Option<usize>,
+    stalled: HashSet<String>,
+    latency_by_label: HashMap<String, AckLatencyHistogram>,
+}
+
+/// Backpressure transition for a webview, reported to the host.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SlowConsumerEvent {
+    /// `label` reached the in-flight limit; updates to it are paused.
+    Stalled { label: String, in_flight: usize },
+    /// `label` acked again; it is sent a full-state resync and updates resume.
+    Resumed { label: String },
+}
+
+/// Whether an update may be sent to a webview. See
+/// [`StateUpdateTracker::admit`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admission {
+    Send,
+    /// The webview is stalled; skip it.
+    Hold,
+    /// The webview just reached the in-flight limit and is now stalled;
+    /// skip it and report the event.
+    Stall(SlowConsumerEvent),
 }
 
 impl StateUpdateTracker {
@@ -410,11 +444,43 @@ impl StateUpdateTracker {
         Self::default()
     }
 
+    /// Cap the number of unacked updates per webview. `None` (the default)
+    /// never stalls a webview.
+    pub fn set_max_in_flight(&mut self, max_in_flight: Option<usize>) {
+        self.max_in_flight = max_in_flight;
+        if max_in_flight.is_none() {
+            self.stalled.clear();
+        }
+    }
+
+    pub fn max_in_flight(&self) -> Option<usize> {
+        self.max_in_flight
+    }
+
     pub fn record_pending(&mut self, label: &str, update_id: &str) {
         self.pending_by_label
             .entry(label.to_string())
             .or_default()
-            .insert(update_id.to_string());
+            .insert(update_id.to_string(), Instant::now());
+    }
+
+    /// Decide whether an update may be sent to `label`, stalling it if it
+    /// has reached the in-flight limit.
+    pub fn admit(&mut self, label: &str) -> Admission {
+        if self.stalled.contains(label) {
+            return Admission::Hold;
+        }
+        let in_flight = self.pending_count(label);
+        match self.max_in_flight {
+            Some(max) if in_flight >= max => {
+                self.stalled.insert(label.to_string());
+                Admission::Stall(SlowConsumerEvent::Stalled {
+                    label: label.to_string(),
+                    in_flight,
+                })
+            }
+            _ => Admission::Send,
+        }
     }
 
     /// Mark `update_id` for `label` as acked. Returns `true` if the entry existed.
@@ -422,15 +488,90 @@ impl StateUpdateTracker {
         let Some(entry) = self.pending_by_label.get_mut(label) else {
             return false;
         };
-        let removed = entry.remove(update_id);
+        let sent_at = entry.remove(update_id);
         if entry.is_empty() {
             self.pending_by_label.remove(label);
         }
-        removed
+        let Some(sent_at) = sent_at else {
+            return false;
+        };
+        self.latency_by_label
+            .entry(label.to_string())
+            .or_default()
+            .record(sent_at.elapsed());
+        true
     }
 
+    /// Drop every update sent more than `timeout` before `now` and still
+    /// unacked, treating it as lost. Returns the labels that lost updates,
+    /// sorted; each needs a full-state resync, since its renderer may have
+    /// missed a delta.
+    pub fn sweep_expired(&mut self, now: Instant, timeout: Duration) -> Vec<String> {
+        let mut expired = Vec::new();
+        self.pending_by_label.retain(|label, pending| {
+            let before = pending.len();
+            pending.retain(|_, sent_at| now.saturating_duration_since(*sent_at) < timeout);
+            let lost = before - pending.len();
+            if lost > 0 {
+                self.latency_by_label
+                    .entry(label.clone())
+                    .or_default()
+                    .timed_out += lost as u64;
+                expired.push(label.clone());
+            }
+            !pending.is_empty()
+        });
+        expired.sort();
+        expired
+    }
+
+    /// Ack latency recorded for `label`, if it has acked or lost any update.
+    pub fn ack_latency(&self, label: &str) -> Option<&AckLatencyHistogram> {
+        self.latency_by_label.get(label)
+    }
+
+    pub fn ack_latencies(&self) -> &HashMap<String, AckLatencyHistogram> {
+        &self.latency_by_label
+    }
+
+    /// Un-stall `label` once it is back under the in-flight limit (i.e.
+    /// after an [`ack`](Self::ack)). Returns the event to report; the
+    /// caller owes the webview a full-state resync.
+    pub fn resume(&mut self, label: &str) -> Option<SlowConsumerEvent> {
+        let under_limit = self
+            .max_in_flight
+            .map_or(true, |max| self.pending_count(label) < max);
+        if under_limit && self.stalled.remove(label) {
+            return Some(SlowConsumerEvent::Resumed {
+                label: label.to_string(),
+            });
+        }
+        None
+    }
+
+    pub fn is_stalled(&self, label: &str) -> bool {
+        self.stalled.contains(label)
+    }
+
+    /// Labels currently stalled, sorted.
+    pub fn stalled_labels(&self) -> Vec<String> {
+        let mut labels: Vec<String> = self.stalled.iter().cloned().collect();
+        labels.sort();
+        labels
+    }
+
+    /// Forget `label`'s pending updates (and stall), e.g. after a resync.
+    /// Its latency history is kept; [`forget_label`](Self::forget_label)
+    /// drops that too.
     pub fn drop_label(&mut self, label: &str) {
         self.pending_by_label.remove(label);
+        self.stalled.remove(label);
+    }
+
+    /// Drop everything tracked for a closed webview.
+    pub fn forget_label(&mut self, label: &str) {
+        self.drop_label(label);
+        self.latency_by_label.remove(label);
     }
 
     pub fn pending_count(&self, label: &str) -> usize {
@@ -442,6 +583,63 @@ impl StateUpdateTracker {
 
     pub fn clear(&mut self) {
         self.pending_by_label.clear();
+        self.stalled.clear();
+        self.latency_by_label.clear();
+    }
+}
+
+// ── AckLatencyHistogram ───────────────────────────────────────────────────────
+
+/// Upper bounds, in milliseconds, of the [`AckLatencyHistogram`] buckets.
+/// Latencies above the last bound land in a final overflow bucket.
+pub const ACK_LATENCY_BUCKETS_MS: [u64; 10] = [1, 2, 5, 10, 25, 50, 100, 250, 500, 1000];
+
+/// Distribution of one webview's state-update ack latencies.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AckLatencyHistogram {
+    /// Ack counts per [`ACK_LATENCY_BUCKETS_MS`] bucket, plus the overflow
+    /// bucket.
+    pub buckets: [u64; ACK_LATENCY_BUCKETS_MS.len() + 1],
+    /// Number of acks recorded.
+    pub count: u64,
+    pub total_ms: f64,
+    pub max_ms: f64,
+    /// Updates swept as lost rather than acked.
+    pub timed_out: u64,
+}
+
+impl AckLatencyHistogram {
+    pub fn record(&mut self, latency: Duration) {
+        let ms = latency.as_secs_f64() * 1000.0;
+        let bucket = ACK_LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| ms <= bound as f64)
+            .unwrap_or(ACK_LATENCY_BUCKETS_MS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.total_ms += ms;
+        self.max_ms = self.max_ms.max(ms);
+    }
+
+    pub fn mean_ms(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.total_ms / self.count as f64)
+    }
+
+    /// Upper bound of the bucket holding the `quantile` (0.0–1.0) latency,
+    /// or `None` if nothing was recorded or it lies in the overflow bucket.
+    pub fn quantile_bound_ms(&self, quantile: f64) -> Option<u64> {
+        if self.count == 0 {
+            return None;
+        }
+        let target = (quantile.clamp(0.0, 1.0) * self.count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket;
+            if seen >= target {
+                return ACK_LATENCY_BUCKETS_MS.get(i).copied();
+            }
+        }
+        None
     }
 }
 
@@ -748,4 +946,86 @@ mod tests {
         tracker.drop_label("popup");
         assert_eq!(tracker.pending_count("popup"), 0);
     }
+
+    #[test]
+    fn labels_over_the_in_flight_limit_stall_until_they_ack() {
+        let mut tracker = StateUpdateTracker::new();
+        tracker.set_max_in_flight(Some(2));
+        for id in ["u1", "u2"] {
+            assert_eq!(tracker.admit("main"), Admission::Send);
+            tracker.record_pending("main", id);
+        }
+        assert_eq!(
+            tracker.admit("main"),
+            Admission::Stall(SlowConsumerEvent::Stalled {
+                label: "main".into(),
+                in_flight: 2,
+            })
+        );
+        assert_eq!(tracker.admit("main"), Admission::Hold);
+        assert_eq!(tracker.admit("other"), Admission::Send);
+        assert_eq!(tracker.resume("main"), None);
+
+        tracker.ack("main", "u1");
+        assert_eq!(
+            tracker.resume("main"),
+            Some(SlowConsumerEvent::Resumed {
+                label: "main".into()
+            })
+        );
+        assert!(!tracker.is_stalled("main"));
+        assert_eq!(tracker.admit("main"), Admission::Send);
+        assert_eq!(tracker.resume("main"), None);
+    }
+
+    #[test]
+    fn dropping_a_label_clears_its_stall() {
+        let mut tracker = StateUpdateTracker::new();
+        tracker.set_max_in_flight(Some(1));
+        tracker.record_pending("main", "u1");
+        assert!(matches!(tracker.admit("main"), Admission::Stall(_)));
+        assert_eq!(tracker.stalled_labels(), vec!["main"]);
+        tracker.drop_label("main");
+        assert_eq!(tracker.admit("main"), Admission::Send);
+    }
+
+    #[test]
+    fn acks_are_recorded_in_the_latency_histogram() {
+        let mut tracker = StateUpdateTracker::new();
+        tracker.record_pending("main", "u1");
+        assert!(tracker.ack("main", "u1"));
+        assert!(!tracker.ack("main", "u1"));
+        let histogram = tracker.ack_latency("main").unwrap();
+        assert_eq!(histogram.count, 1);
+        assert_eq!(histogram.buckets.iter().sum::<u64>(), 1);
+        assert!(tracker.ack_latency("other").is_none());
+    }
+
+    #[test]
+    fn sweep_expired_drops_overdue_updates() {
+        let mut tracker = StateUpdateTracker::new();
+        tracker.record_pending("main", "u1");
+        tracker.record_pending("popup", "u2");
+        let timeout = Duration::from_secs(5);
+        assert!(tracker.sweep_expired(Instant::now(), timeout).is_empty());
+
+        let expired = tracker.sweep_expired(Instant::now() + timeout * 2, timeout);
+        assert_eq!(expired, vec!["main", "popup"]);
+        assert_eq!(tracker.pending_count("main"), 0);
+        assert_eq!(tracker.ack_latency("main").unwrap().timed_out, 1);
+        assert!(!tracker.ack("main", "u1"));
+    }
+
+    #[test]
+    fn histogram_quantiles_report_bucket_bounds() {
+        let mut histogram = AckLatencyHistogram::default();
+        for ms in [1, 3, 4, 40, 2000] {
+            histogram.record(Duration::from_millis(ms));
+        }
+        assert_eq!(histogram.count, 5);
+        assert_eq!(histogram.quantile_bound_ms(0.5), Some(5));
+        assert_eq!(histogram.quantile_bound_ms(0.8), Some(50));
+        assert_eq!(histogram.quantile_bound_ms(1.0), None);
+        assert_eq!(histogram.max_ms, 2000.0);
+    }
 }
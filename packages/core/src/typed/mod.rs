@@ -0,0 +1,415 @@
+//! Typed state managers.
+//!
+//! [`TypedStateManager`] lets a host work with its own serde state struct and
+//! action enum rather than hand-parsing `{ type, payload }` JSON. Wrap one in
+//! a [`TypedAdapter`] to obtain a [`TryStateManager`] that the platform
+//! wrappers accept anywhere a [`StateManager`](crate::models::StateManager)
+//! is.
+//!
+//! The adapter deserializes the legacy action shape produced by
+//! [`ZubridgeAction::to_legacy_json`](crate::models::ZubridgeAction::to_legacy_json)
+//! straight into the action enum, so an adjacently tagged enum maps one
+//! variant per action type:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use zubridge_core::typed::{TypedAdapter, TypedStateManager};
+//!
+//! #[derive(Serialize)]
+//! struct Counter {
+//!     counter: i64,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! #[serde(tag = "type", content = "payload")]
+//! enum CounterAction {
+//!     #[serde(rename = "COUNTER:INCREMENT")]
+//!     Increment,
+//!     #[serde(rename = "COUNTER:SET")]
+//!     Set(i64),
+//! }
+//!
+//! struct CounterStore(i64);
+//!
+//! impl TypedStateManager<Counter, CounterAction> for CounterStore {
+//!     type Error = std::convert::Infallible;
+//!
+//!     fn state(&self) -> Counter {
+//!         Counter { counter: self.0 }
+//!     }
+//!
+//!     fn reduce(&self, action: CounterAction) -> Result<Counter, Self::Error> {
+//!         let counter = match action {
+//!             CounterAction::Increment => self.0 + 1,
+//!             CounterAction::Set(value) => value,
+//!         };
+//!         Ok(Counter { counter })
+//!     }
+//!
+//!     fn commit(&mut self, state: Counter) {
+//!         self.0 = state.counter;
+//!     }
+//! }
+//!
+//! let handle = zubridge_core::state::new_handle(TypedAdapter::new(CounterStore(0)));
+//! ```
+
+use std::fmt::Display;
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::{Result, ZubridgeError};
+use crate::models::{JsonValue, TryStateManager};
+
+// ── TypedStateManager ─────────────────────────────────────────────────────────
+
+/// A state manager over a serializable state `S` and a deserializable action
+/// type `A`.
+///
+/// # Contract
+///
+/// An action is applied in two steps: `reduce` computes the state it leads to
+/// without touching the manager, and [`TypedAdapter`] passes that state to
+/// `commit` only once it has serialized. A rejected action or an
+/// unserializable state therefore leaves the manager unchanged, as the
+/// [`TryStateManager`] contract requires, without copying it per action.
+/// After `commit(next)`, `state` must return a value equal to `next`.
+pub trait TypedStateManager<S, A>: Send + Sync + 'static
+where
+    S: Serialize,
+    A: DeserializeOwned,
+{
+    /// Error returned when an action is rejected. Reported to the dispatcher
+    /// as [`ZubridgeError::ActionProcessing`] using its `Display` output.
+    type Error: Display;
+
+    /// Snapshot of the current state.
+    fn state(&self) -> S;
+
+    /// The state `action` leads to from the current one.
+    fn reduce(&self, action: A) -> std::result::Result<S, Self::Error>;
+
+    /// Make `state`, as returned by [`reduce`](Self::reduce), the current
+    /// state.
+    fn commit(&mut self, state: S);
+}
+
+// ── TypedAdapter ──────────────────────────────────────────────────────────────
+
+/// Adapts a [`TypedStateManager`] to the JSON-based [`TryStateManager`].
+///
+/// Actions that don't deserialize into `A` (unknown type, payload of the wrong
+/// shape) are rejected with [`ZubridgeError::InvalidAction`] before the inner
+/// manager sees them.
+pub struct TypedAdapter<M, S, A> {
+    manager: M,
+    _types: PhantomData<fn() -> (S, A)>,
+}
+
+impl<M, S, A> TypedAdapter<M, S, A>
+where
+    M: TypedStateManager<S, A>,
+    S: Serialize,
+    A: DeserializeOwned,
+{
+    pub fn new(manager: M) -> Self {
+        Self {
+            manager,
+            _types: PhantomData,
+        }
+    }
+
+    pub fn inner(&self) -> &M {
+        &self.manager
+    }
+
+    pub fn inner_mut(&mut self) -> &mut M {
+        &mut self.manager
+    }
+
+    pub fn into_inner(self) -> M {
+        self.manager
+    }
+
+    fn state_json(&self) -> Result<JsonValue> {
+        state_json(&self.manager)
+    }
+}
+
+impl<M, S, A> TryStateManager for TypedAdapter<M, S, A>
+where
+    M: TypedStateManager<S, A>,
+    S: Serialize + 'static,
+    A: DeserializeOwned + 'static,
+{
+    fn get_initial_state(&self) -> JsonValue {
+        self.state_json().unwrap_or_else(|err| {
+            log::error!("zubridge: failed to serialize typed state: {err}");
+            JsonValue::Null
+        })
+    }
+
+    /// Deserialize `action` into `A`, reduce it and commit the result.
+    ///
+    /// A reduced state that fails to serialize is reported as
+    /// [`ZubridgeError::Serialization`] and never committed, leaving the state
+    /// unchanged.
+    fn try_dispatch_action(&mut self, action: JsonValue) -> Result<JsonValue> {
+        let action_type = action
+            .get("type")
+            .and_then(JsonValue::as_str)
+            .map(str::to_string);
+        let typed = A::deserialize(action).map_err(|e| ZubridgeError::InvalidAction {
+            action_type,
+            message: e.to_string(),
+        })?;
+        let next = self
+            .manager
+            .reduce(typed)
+            .map_err(|e| ZubridgeError::ActionProcessing(e.to_string()))?;
+        let state = to_json(&next)?;
+        self.manager.commit(next);
+        Ok(state)
+    }
+}
+
+fn state_json<M, S, A>(manager: &M) -> Result<JsonValue>
+where
+    M: TypedStateManager<S, A>,
+    S: Serialize,
+    A: DeserializeOwned,
+{
+    to_json(&manager.state())
+}
+
+fn to_json<S: Serialize>(state: &S) -> Result<JsonValue> {
+    serde_json::to_value(state).map_err(|e| ZubridgeError::Serialization(e.to_string()))
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Debug, Serialize)]
+    struct AppState {
+        counter: i64,
+        theme: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(tag = "type", content = "payload")]
+    enum AppAction {
+        #[serde(rename = "COUNTER:INCREMENT")]
+        Increment,
+        #[serde(rename = "COUNTER:SET")]
+        Set(i64),
+        #[serde(rename = "THEME:SET")]
+        SetTheme { name: String },
+    }
+
+    struct Store {
+        counter: i64,
+        theme: String,
+    }
+
+    impl TypedStateManager<AppState, AppAction> for Store {
+        type Error = String;
+
+        fn state(&self) -> AppState {
+            AppState {
+                counter: self.counter,
+                theme: self.theme.clone(),
+            }
+        }
+
+        fn reduce(&self, action: AppAction) -> std::result::Result<AppState, String> {
+            let mut next = self.state();
+            match action {
+                AppAction::Increment => next.counter += 1,
+                AppAction::Set(value) if value < 0 => {
+                    return Err(format!("counter cannot be negative: {value}"))
+                }
+                AppAction::Set(value) => next.counter = value,
+                AppAction::SetTheme { name } => next.theme = name,
+            }
+            Ok(next)
+        }
+
+        fn commit(&mut self, state: AppState) {
+            self.counter = state.counter;
+            self.theme = state.theme;
+        }
+    }
+
+    fn adapter() -> TypedAdapter<Store, AppState, AppAction> {
+        TypedAdapter::new(Store {
+            counter: 0,
+            theme: "dark".into(),
+        })
+    }
+
+    fn legacy(action_type: &str, payload: Option<JsonValue>) -> JsonValue {
+        crate::models::ZubridgeAction {
+            id: None,
+            action_type: action_type.to_string(),
+            payload,
+            source_label: None,
+            thunk_parent_id: None,
+            immediate: None,
+            keys: None,
+            bypass_access_control: None,
+            starts_thunk: None,
+            ends_thunk: None,
+        }
+        .to_legacy_json()
+    }
+
+    #[test]
+    fn initial_state_is_serialized() {
+        let typed = adapter();
+        assert_eq!(
+            typed.get_initial_state(),
+            json!({ "counter": 0, "theme": "dark" })
+        );
+    }
+
+    #[test]
+    fn unit_variant_accepts_null_payload() {
+        let mut typed = adapter();
+        let state = typed
+            .try_dispatch_action(legacy("COUNTER:INCREMENT", None))
+            .unwrap();
+        assert_eq!(state["counter"], 1);
+    }
+
+    #[test]
+    fn payload_variants_are_deserialized() {
+        let mut typed = adapter();
+        typed
+            .try_dispatch_action(legacy("COUNTER:SET", Some(json!(5))))
+            .unwrap();
+        let state = typed
+            .try_dispatch_action(legacy("THEME:SET", Some(json!({ "name": "light" }))))
+            .unwrap();
+        assert_eq!(state, json!({ "counter": 5, "theme": "light" }));
+    }
+
+    #[test]
+    fn unknown_action_type_is_invalid_action() {
+        let mut typed = adapter();
+        let err = typed
+            .try_dispatch_action(legacy("MYSTERY", None))
+            .unwrap_err();
+        match err {
+            ZubridgeError::InvalidAction { action_type, .. } => {
+                assert_eq!(action_type.as_deref(), Some("MYSTERY"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn malformed_payload_is_invalid_action() {
+        let mut typed = adapter();
+        let err = typed
+            .try_dispatch_action(legacy("COUNTER:SET", Some(json!("five"))))
+            .unwrap_err();
+        assert!(matches!(err, ZubridgeError::InvalidAction { .. }));
+        assert_eq!(typed.get_initial_state()["counter"], 0);
+    }
+
+    #[test]
+    fn missing_type_is_invalid_action() {
+        let mut typed = adapter();
+        let err = typed
+            .try_dispatch_action(json!({ "payload": 1 }))
+            .unwrap_err();
+        match err {
+            ZubridgeError::InvalidAction { action_type, .. } => assert!(action_type.is_none()),
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn rejected_action_reports_action_processing() {
+        let mut typed = adapter();
+        let err = typed
+            .try_dispatch_action(legacy("COUNTER:SET", Some(json!(-1))))
+            .unwrap_err();
+        match err {
+            ZubridgeError::ActionProcessing(msg) => assert!(msg.contains("negative")),
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert_eq!(typed.inner().counter, 0);
+    }
+
+    #[test]
+    fn unserializable_state_discards_the_action() {
+        #[derive(Clone)]
+        struct Poisonable(bool);
+
+        impl Serialize for Poisonable {
+            fn serialize<Ser: serde::Serializer>(
+                &self,
+                serializer: Ser,
+            ) -> std::result::Result<Ser::Ok, Ser::Error> {
+                if self.0 {
+                    Err(serde::ser::Error::custom("poisoned"))
+                } else {
+                    serializer.serialize_bool(false)
+                }
+            }
+        }
+
+        #[derive(Deserialize)]
+        #[serde(tag = "type")]
+        enum PoisonAction {
+            #[serde(rename = "POISON")]
+            Poison,
+        }
+
+        impl TypedStateManager<Poisonable, PoisonAction> for Poisonable {
+            type Error = std::convert::Infallible;
+
+            fn state(&self) -> Poisonable {
+                self.clone()
+            }
+
+            fn reduce(
+                &self,
+                _action: PoisonAction,
+            ) -> std::result::Result<Poisonable, Self::Error> {
+                Ok(Poisonable(true))
+            }
+
+            fn commit(&mut self, state: Poisonable) {
+                *self = state;
+            }
+        }
+
+        let mut typed = TypedAdapter::new(Poisonable(false));
+        let err = typed
+            .try_dispatch_action(json!({ "type": "POISON" }))
+            .unwrap_err();
+        assert!(matches!(err, ZubridgeError::Serialization(_)));
+        assert!(!typed.inner().0);
+    }
+
+    #[test]
+    fn rejection_surfaces_through_state_dispatch() {
+        let handle = crate::state::new_handle(adapter());
+        let err = crate::state::dispatch(&handle, legacy("MYSTERY", None)).unwrap_err();
+        match err {
+            ZubridgeError::InvalidAction { action_type, .. } => {
+                assert_eq!(action_type.as_deref(), Some("MYSTERY"));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+}
@@ -4,6 +4,5 @@ pub mod tauri;
 #[cfg(feature = "uniffi")]
 pub mod uniffi;
 
-// Placeholder: napi wrapper implemented in P5.
 #[cfg(feature = "napi")]
 pub mod napi;
@@ -1,4 +1,516 @@
-// NAPI-RS bindings implemented in P5.
-// This stub ensures `zubridge_core::wrappers::napi` is reachable when the
-// `napi` feature is enabled, and that the four CI matrix jobs that include
-// `napi` exercise a real code path rather than a silent no-op.
+//! NAPI-RS bindings.
+//!
+//! Exposes the core to Node-API runtimes as a `ZubridgeCore` class built on
+//! runtime-neutral primitives: a state manager supplied as JS callbacks,
+//! dispatch and thunk lifecycle through the [`ActionQueueManager`], and
+//! per-subscriber state updates (filtered by [`SubscriptionManager`],
+//! delta-encoded by a [`Broadcaster`]) delivered to a JS callback through
+//! an [`EventEmitter`] backed by a `ThreadsafeFunction`. IPC channel names
+//! and process wiring belong to the runtime-specific wrapper (e.g. the
+//! Electron main process), not to this binding.
+//!
+//! Every method must be called on the JS thread that constructed the
+//! instance. State manager callbacks run synchronously on that thread, and
+//! may not call back into the same `ZubridgeCore`.
+//!
+//! The loadable `.node` addon is the `zubridge-node-native` crate
+//! (`packages/node-native`), a `cdylib` depending on this crate with the
+//! `napi` feature; its Node smoke test runs in CI. Doc tests can't link with
+//! the feature on, since the Node-API symbols are only resolved when Node
+//! loads the addon.
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+use std::ptr;
+use std::thread::ThreadId;
+
+use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{sys, Env, JsDeferred, JsFunction, JsObject, JsUnknown, NapiValue, Ref, ValueType};
+use napi_derive::napi;
+
+use crate::broadcast::Broadcaster;
+use crate::emit::EventEmitter;
+use crate::error::ZubridgeError;
+use crate::models::{JsonValue, TryStateManager, UpdateSource, ZubridgeAction};
+use crate::orchestration::{ActionQueueManager, DispatchOutcome, ExecutedAction};
+use crate::state::StateManagerHandle;
+use crate::subscription::SubscriptionManager;
+
+/// Event name passed to the event callback for state updates. The payload is
+/// a [`StateUpdatePayload`].
+pub const STATE_UPDATE_EVENT: &str = "state-update";
+
+/// Source id recorded for actions dispatched without one.
+pub const DEFAULT_SOURCE_ID: &str = crate::orchestration::HOST_SOURCE_LABEL;
+
+// ── JsStateManager ────────────────────────────────────────────────────────────
+
+/// A [`TryStateManager`] backed by a JS object with `getState()` and
+/// `dispatchAction(action)` methods, and optionally `actionTypes()` and
+/// `changedKeys()`. `dispatchAction` returns the new state (or nothing, in
+/// which case `getState()` is read); throwing rejects the action.
+struct JsStateManager {
+    env: sys::napi_env,
+    manager: Ref<()>,
+    thread: ThreadId,
+}
+
+// SAFETY: `env` and `manager` are only used on `thread`, the JS thread that
+// created them; `call` refuses to run anywhere else.
+unsafe impl Send for JsStateManager {}
+unsafe impl Sync for JsStateManager {}
+
+impl JsStateManager {
+    fn new(env: &Env, manager: &JsObject) -> Result<Self> {
+        Ok(Self {
+            env: env.raw(),
+            manager: env.create_reference(manager)?,
+            thread: std::thread::current().id(),
+        })
+    }
+
+    /// Call `method` on the JS object, converting its return value. `None`
+    /// when the method is missing or returns `undefined`.
+    fn call(&self, method: &str, arg: Option<&JsonValue>) -> crate::Result<Option<JsonValue>> {
+        if std::thread::current().id() != self.thread {
+            return Err(ZubridgeError::StateError(
+                "JS state manager called off the JS thread".to_string(),
+            ));
+        }
+        // SAFETY: on the thread that owns `env`, checked above.
+        let env = unsafe { Env::from_raw(self.env) };
+        let result = (|| {
+            let manager: JsObject = env.get_reference_value(&self.manager)?;
+            let function = manager.get_named_property::<JsUnknown>(method)?;
+            if function.get_type()? != ValueType::Function {
+                return Ok(None);
+            }
+            // SAFETY: the type was checked above.
+            let function: JsFunction = unsafe { function.cast() };
+            let args = arg
+                .map(|arg| env.to_js_value(arg))
+                .transpose()?
+                .into_iter()
+                .collect::<Vec<_>>();
+            let value = function.call(Some(&manager), &args)?;
+            match value.get_type()? {
+                ValueType::Undefined => Ok(None),
+                _ => env.from_js_value(value).map(Some),
+            }
+        })();
+        result.map_err(|err| ZubridgeError::StateError(take_exception(&env, err)))
+    }
+}
+
+impl TryStateManager for JsStateManager {
+    fn get_initial_state(&self) -> JsonValue {
+        match self.call("getState", None) {
+            Ok(state) => state.unwrap_or_default(),
+            Err(err) => {
+                log::warn!("zubridge: getState failed: {err}");
+                JsonValue::Null
+            }
+        }
+    }
+
+    fn try_dispatch_action(&mut self, action: JsonValue) -> crate::Result<JsonValue> {
+        let state = self
+            .call("dispatchAction", Some(&action))
+            .map_err(|err| ZubridgeError::ActionProcessing(err.to_string()))?;
+        match state {
+            Some(state) => Ok(state),
+            None => Ok(self.call("getState", None)?.unwrap_or_default()),
+        }
+    }
+
+    fn action_types(&self) -> Option<Vec<String>> {
+        let types = self.call("actionTypes", None).ok()??;
+        serde_json::from_value(types).ok()
+    }
+
+    fn changed_keys(&self) -> Option<Vec<String>> {
+        let keys = self.call("changedKeys", None).ok()??;
+        serde_json::from_value(keys).ok()
+    }
+}
+
+impl Drop for JsStateManager {
+    fn drop(&mut self) {
+        if std::thread::current().id() != self.thread {
+            log::warn!("zubridge: JS state manager dropped off the JS thread; leaking it");
+            return;
+        }
+        // SAFETY: on the thread that owns `env`, checked above.
+        let env = unsafe { Env::from_raw(self.env) };
+        let _ = self.manager.unref(env);
+    }
+}
+
+/// Clear the JS exception behind `err`, if any, and describe it.
+fn take_exception(env: &Env, err: napi::Error) -> String {
+    if err.status != Status::PendingException {
+        return err.reason;
+    }
+    let mut exception = ptr::null_mut();
+    // SAFETY: `env` is valid on this thread and an exception is pending.
+    let status = unsafe { sys::napi_get_and_clear_last_exception(env.raw(), &mut exception) };
+    if status != sys::Status::napi_ok || exception.is_null() {
+        return err.reason;
+    }
+    // SAFETY: `exception` was just returned for `env`.
+    let exception = unsafe { JsUnknown::from_raw_unchecked(env.raw(), exception) };
+    exception
+        .coerce_to_string()
+        .and_then(|message| message.into_utf8())
+        .and_then(|message| message.into_owned())
+        .unwrap_or(err.reason)
+}
+
+// ── NapiEmitter ───────────────────────────────────────────────────────────────
+
+type EventCallback = ThreadsafeFunction<(String, String, JsonValue), ErrorStrategy::Fatal>;
+
+/// [`EventEmitter`] that queues each event onto the JS thread, calling
+/// `callback(target, event, payload)`.
+pub struct NapiEmitter {
+    callback: EventCallback,
+}
+
+impl EventEmitter for NapiEmitter {
+    fn emit(&self, target: &str, event: &str, payload: &JsonValue) -> crate::Result<()> {
+        let status = self.callback.call(
+            (target.to_string(), event.to_string(), payload.clone()),
+            ThreadsafeFunctionCallMode::NonBlocking,
+        );
+        match status {
+            Status::Ok => Ok(()),
+            status => Err(ZubridgeError::EmitError(format!(
+                "failed to queue {event} for {target}: {status}"
+            ))),
+        }
+    }
+}
+
+// ── ZubridgeCore ──────────────────────────────────────────────────────────────
+
+type DispatchDeferred = JsDeferred<String, Box<dyn FnOnce(Env) -> Result<String>>>;
+
+/// Options for [`ZubridgeCore::register_thunk`].
+#[napi(object)]
+pub struct RegisterThunkOptions {
+    pub parent_id: Option<String>,
+    pub keys: Option<Vec<String>>,
+    pub bypass_access_control: Option<bool>,
+    pub immediate: Option<bool>,
+}
+
+struct Inner {
+    state: StateManagerHandle,
+    queue: ActionQueueManager,
+    subscriptions: SubscriptionManager,
+    subscribers: BTreeSet<String>,
+    /// Created once an event emitter is set.
+    broadcaster: Option<Broadcaster<NapiEmitter>>,
+    /// Promises of dispatches still queued behind a thunk, by action id.
+    pending: HashMap<String, DispatchDeferred>,
+}
+
+/// The Zubridge core for Node-API runtimes. See the module docs.
+#[napi]
+pub struct ZubridgeCore {
+    inner: RefCell<Inner>,
+}
+
+#[napi]
+impl ZubridgeCore {
+    #[napi(
+        constructor,
+        ts_args_type = "stateManager: { getState(): unknown; dispatchAction(action: unknown): unknown; actionTypes?(): string[] | null; changedKeys?(): string[] | null }"
+    )]
+    pub fn new(env: Env, state_manager: JsObject) -> Result<Self> {
+        let state = crate::state::new_handle(JsStateManager::new(&env, &state_manager)?);
+        Ok(Self {
+            inner: RefCell::new(Inner {
+                queue: ActionQueueManager::with_state_handle(state.clone()),
+                state,
+                subscriptions: SubscriptionManager::new(),
+                subscribers: BTreeSet::new(),
+                broadcaster: None,
+                pending: HashMap::new(),
+            }),
+        })
+    }
+
+    /// Register the callback state updates are delivered to. It does not
+    /// keep the process alive.
+    #[napi(ts_args_type = "callback: (target: string, event: string, payload: unknown) => void")]
+    pub fn set_event_emitter(&self, env: Env, mut callback: EventCallback) -> Result<()> {
+        callback.unref(&env)?;
+        let emitter = NapiEmitter { callback };
+        match &mut self.inner()?.broadcaster {
+            Some(broadcaster) => broadcaster.set_emitter(emitter),
+            broadcaster => *broadcaster = Some(Broadcaster::new(emitter, STATE_UPDATE_EVENT)),
+        }
+        Ok(())
+    }
+
+    /// The current state, filtered to `subscriber_id`'s subscription if
+    /// given.
+    #[napi(ts_return_type = "unknown")]
+    pub fn get_state(&self, subscriber_id: Option<String>) -> Result<JsonValue> {
+        let inner = self.inner()?;
+        let state = inner.state()?;
+        Ok(match subscriber_id {
+            Some(id) => inner.subscriptions.filter_for(&id, &state),
+            None => state,
+        })
+    }
+
+    /// Action types declared by the state manager, or `null`.
+    #[napi]
+    pub fn action_types(&self) -> Result<Option<Vec<String>>> {
+        crate::state::action_types(&self.inner()?.state).map_err(to_napi_error)
+    }
+
+    /// Dispatch `action` (a [`ZubridgeAction`]) from `source_id`. Resolves
+    /// with the action id once it has been applied and broadcast, which
+    /// waits while it is queued behind another source's thunk; rejects if
+    /// the state manager rejects it.
+    #[napi(
+        ts_args_type = "action: unknown, sourceId?: string",
+        ts_return_type = "Promise<string>"
+    )]
+    pub fn dispatch(
+        &self,
+        env: Env,
+        action: JsonValue,
+        source_id: Option<String>,
+    ) -> Result<JsObject> {
+        let action: ZubridgeAction = serde_json::from_value(action)
+            .map_err(|e| Error::new(Status::InvalidArg, format!("invalid action: {e}")))?;
+        let source_id = source_id.unwrap_or_else(|| DEFAULT_SOURCE_ID.to_string());
+        let mut inner = self.inner()?;
+        let (deferred, promise) = env.create_deferred()?;
+        let thunk_id = action.thunk_parent_id.clone();
+        match inner.queue.dispatch(action, source_id) {
+            Ok(DispatchOutcome::Executed {
+                action_id,
+                state,
+                changed_keys,
+                drained,
+            }) => {
+                inner.broadcast(state, changed_keys.as_deref(), Some(&action_id), thunk_id);
+                resolve(deferred, Ok(action_id));
+                inner.publish_drained(drained);
+            }
+            Ok(DispatchOutcome::Queued { action_id, evicted }) => {
+                if let Some(evicted) = evicted.and_then(|evicted| evicted.action.id) {
+                    if let Some(deferred) = inner.pending.remove(&evicted) {
+                        deferred.reject(Error::from_reason(format!(
+                            "action {evicted} was evicted from the full action queue"
+                        )));
+                    }
+                }
+                inner.pending.insert(action_id, deferred);
+            }
+            Err(err) => deferred.reject(to_napi_error(err)),
+        }
+        Ok(promise)
+    }
+
+    /// Register a thunk from `source_id` and start it. A root thunk holds
+    /// back other sources' actions until it completes.
+    #[napi]
+    pub fn register_thunk(
+        &self,
+        thunk_id: String,
+        source_id: String,
+        options: Option<RegisterThunkOptions>,
+    ) -> Result<()> {
+        let options = options.unwrap_or(RegisterThunkOptions {
+            parent_id: None,
+            keys: None,
+            bypass_access_control: None,
+            immediate: None,
+        });
+        let mut inner = self.inner()?;
+        inner
+            .queue
+            .register_thunk(
+                thunk_id.clone(),
+                options.parent_id,
+                source_id,
+                options.keys,
+                options.bypass_access_control.unwrap_or(false),
+                options.immediate.unwrap_or(false),
+            )
+            .map_err(to_napi_error)?;
+        inner.queue.execute_thunk(&thunk_id);
+        Ok(())
+    }
+
+    /// Complete (or, with `error`, fail) a thunk, then apply and broadcast
+    /// the actions queued behind it.
+    #[napi]
+    pub fn complete_thunk(&self, thunk_id: String, error: Option<String>) -> Result<()> {
+        let mut inner = self.inner()?;
+        let (_events, drained) = inner
+            .queue
+            .on_thunk_complete(&thunk_id, error)
+            .map_err(to_napi_error)?;
+        inner.publish_drained(drained);
+        Ok(())
+    }
+
+    /// Start sending state updates to `subscriber_id`, for every key until
+    /// it [`subscribe`](Self::subscribe)s to some. Sends the current state.
+    #[napi]
+    pub fn add_subscriber(&self, subscriber_id: String) -> Result<()> {
+        let mut inner = self.inner()?;
+        inner.subscribers.insert(subscriber_id.clone());
+        inner.resync(&subscriber_id)
+    }
+
+    /// Limit `subscriber_id`'s updates to `keys` (adding it if needed).
+    /// Returns its subscribed keys.
+    #[napi]
+    pub fn subscribe(&self, subscriber_id: String, keys: Vec<String>) -> Result<Vec<String>> {
+        let mut inner = self.inner()?;
+        let subscribed = inner.subscriptions.subscribe(&subscriber_id, &keys);
+        inner.subscribers.insert(subscriber_id.clone());
+        inner.resync(&subscriber_id)?;
+        Ok(subscribed)
+    }
+
+    /// Remove `keys` from `subscriber_id`'s subscription; an empty
+    /// subscription receives every key again. Returns its subscribed keys.
+    #[napi]
+    pub fn unsubscribe(&self, subscriber_id: String, keys: Vec<String>) -> Result<Vec<String>> {
+        let mut inner = self.inner()?;
+        let subscribed = inner.subscriptions.unsubscribe(&subscriber_id, &keys);
+        if inner.subscribers.contains(&subscriber_id) {
+            inner.resync(&subscriber_id)?;
+        }
+        Ok(subscribed)
+    }
+
+    /// Acknowledge that `subscriber_id` applied state update `update_id`.
+    #[napi]
+    pub fn ack_state_update(&self, subscriber_id: String, update_id: String) -> Result<()> {
+        let inner = &mut *self.inner()?;
+        if let Some(broadcaster) = &mut inner.broadcaster {
+            broadcaster
+                .ack(&inner.subscriptions, &subscriber_id, &update_id)
+                .map_err(to_napi_error)?;
+        }
+        Ok(())
+    }
+
+    /// Forget everything about `subscriber_id` (e.g. when its renderer
+    /// closes): subscription, delta baseline, sequence, pending acks and its
+    /// thunks. Actions queued behind those thunks are applied and broadcast.
+    #[napi]
+    pub fn remove_subscriber(&self, subscriber_id: String) -> Result<()> {
+        let mut inner = self.inner()?;
+        inner.subscribers.remove(&subscriber_id);
+        inner.subscriptions.drop_label(&subscriber_id);
+        if let Some(broadcaster) = &mut inner.broadcaster {
+            broadcaster.forget(&subscriber_id);
+        }
+        let drained = inner
+            .queue
+            .drop_label(&subscriber_id)
+            .map_err(to_napi_error)?;
+        inner.publish_drained(drained);
+        Ok(())
+    }
+
+    /// Number of actions waiting behind a thunk.
+    #[napi]
+    pub fn queue_len(&self) -> Result<u32> {
+        Ok(self.inner()?.queue.queue_len() as u32)
+    }
+
+    fn inner(&self) -> Result<std::cell::RefMut<'_, Inner>> {
+        // Only a state manager callback calling back in can find it borrowed.
+        self.inner.try_borrow_mut().map_err(|_| {
+            Error::from_reason("ZubridgeCore cannot be called from its own state manager")
+        })
+    }
+}
+
+impl Inner {
+    fn state(&self) -> Result<JsonValue> {
+        crate::state::read_state(&self.state).map_err(to_napi_error)
+    }
+
+    /// Send `subscriber_id` its view of the current state.
+    fn resync(&mut self, subscriber_id: &str) -> Result<()> {
+        let state = self.state()?;
+        if let Some(broadcaster) = &mut self.broadcaster {
+            let targets = [subscriber_id.to_string()];
+            broadcaster
+                .broadcast(&self.subscriptions, &targets, state, None, None)
+                .map_err(to_napi_error)?;
+        }
+        Ok(())
+    }
+
+    /// Send every subscriber its update for `state`, if its view changed.
+    fn broadcast(
+        &mut self,
+        state: JsonValue,
+        changed_keys: Option<&[String]>,
+        action_id: Option<&str>,
+        thunk_id: Option<String>,
+    ) {
+        let Some(broadcaster) = &mut self.broadcaster else {
+            return;
+        };
+        let source = UpdateSource {
+            action_id: action_id.map(str::to_string),
+            thunk_id,
+            ..UpdateSource::default()
+        };
+        let targets: Vec<String> = self.subscribers.iter().cloned().collect();
+        let result = broadcaster.broadcast(
+            &self.subscriptions,
+            &targets,
+            state,
+            changed_keys,
+            Some(source),
+        );
+        // The action has been applied either way; failed subscribers catch
+        // up with their next update.
+        if let Err(err) = result {
+            log::warn!("zubridge: state update broadcast failed: {err}");
+        }
+    }
+
+    /// Broadcast the state of each drained action and settle its dispatch.
+    fn publish_drained(&mut self, drained: Vec<ExecutedAction>) {
+        for executed in drained {
+            let action_id = executed.action.id.clone().unwrap_or_default();
+            let result = executed.result.map(|state| {
+                let thunk_id = executed.action.thunk_parent_id.clone();
+                let changed_keys = executed.changed_keys.as_deref();
+                self.broadcast(state, changed_keys, Some(&action_id), thunk_id);
+            });
+            if let Some(deferred) = self.pending.remove(&action_id) {
+                resolve(deferred, result.map(|()| action_id));
+            }
+        }
+    }
+}
+
+fn resolve(deferred: DispatchDeferred, result: crate::Result<String>) {
+    match result {
+        Ok(action_id) => deferred.resolve(Box::new(move |_env| Ok(action_id))),
+        Err(err) => deferred.reject(to_napi_error(err)),
+    }
+}
+
+fn to_napi_error(err: ZubridgeError) -> Error {
+    Error::from_reason(err.to_string())
+}
@@ -1,6 +1,7 @@
 use tauri::{AppHandle, Emitter, Runtime};
 
 use crate::emit::EventEmitter;
+use crate::error::{Result, ZubridgeError};
 use crate::models::JsonValue;
 
 pub struct TauriEmitter<R: Runtime> {
@@ -14,9 +15,9 @@ impl<R: Runtime> TauriEmitter<R> {
 }
 
 impl<R: Runtime> EventEmitter for TauriEmitter<R> {
-    fn emit(&self, target: &str, event: &str, payload: &JsonValue) {
-        if let Err(err) = self.app.emit_to(target.to_string(), event, payload) {
-            log::warn!("zubridge: failed to emit {event} to {target}: {err}");
-        }
+    fn emit(&self, target: &str, event: &str, payload: &JsonValue) -> Result<()> {
+        self.app
+            .emit_to(target.to_string(), event, payload)
+            .map_err(|e| ZubridgeError::EmitError(e.to_string()))
     }
 }
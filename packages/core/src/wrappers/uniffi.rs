@@ -1,2 +1,587 @@
-// UniFFI proc-macro re-exports — P1 placeholder.
-// UniFFI bindings are added in P2 when the full interface stabilises.
+//! UniFFI bindings.
+//!
+//! Exports a `ZubridgeCore` object to Kotlin, Swift and Python through
+//! UniFFI's proc-macro flow. The foreign side supplies its state as a
+//! [`StateManager`] callback interface and receives per-subscriber state
+//! updates through an [`EventEmitter`] callback interface. Actions, states
+//! and update payloads cross the boundary as JSON strings; a malformed one
+//! fails with [`ZubridgeFfiError::InvalidJson`].
+//!
+//! Calls are synchronous and may come from any thread. Callbacks run on the
+//! calling thread with the core locked, so they must not call back into the
+//! same `ZubridgeCore`.
+//!
+//! The `cdylib` is the `zubridge-uniffi` crate (`packages/uniffi`), which
+//! also provides the `uniffi-bindgen` binary. CI generates Python bindings
+//! from it and runs `packages/uniffi/tests` against them.
+
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::broadcast::Broadcaster;
+use crate::error::ZubridgeError;
+use crate::models::{JsonValue, TryStateManager, UpdateSource, ZubridgeAction};
+use crate::orchestration::{ActionQueueManager, DispatchOutcome, ExecutedAction};
+use crate::state::StateManagerHandle;
+use crate::subscription::SubscriptionManager;
+
+/// Event name passed to [`EventEmitter::emit`] for state updates. The
+/// payload is a [`StateUpdatePayload`].
+pub const STATE_UPDATE_EVENT: &str = "state-update";
+
+/// Errors returned across the FFI boundary.
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum ZubridgeFfiError {
+    /// A JSON string from the foreign side did not parse, or didn't have the
+    /// expected shape.
+    #[error("invalid JSON: {message}")]
+    InvalidJson { message: String },
+    /// The state manager rejected the action. Also what a foreign
+    /// [`StateManager::dispatch_action`] should return to reject one.
+    #[error("action rejected: {message}")]
+    Rejected { message: String },
+    /// Any other core failure.
+    #[error("{message}")]
+    Core { message: String },
+}
+
+impl From<ZubridgeError> for ZubridgeFfiError {
+    fn from(err: ZubridgeError) -> Self {
+        match err {
+            ZubridgeError::Serialization(message) => Self::InvalidJson { message },
+            ZubridgeError::ActionProcessing(message) => Self::Rejected { message },
+            err @ (ZubridgeError::InvalidAction { .. }
+            | ZubridgeError::UnknownActionType { .. }) => Self::Rejected {
+                message: err.to_string(),
+            },
+            err => Self::Core {
+                message: err.to_string(),
+            },
+        }
+    }
+}
+
+impl From<uniffi::UnexpectedUniFFICallbackError> for ZubridgeFfiError {
+    fn from(err: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        Self::Core {
+            message: err.reason,
+        }
+    }
+}
+
+// ── Callback interfaces ───────────────────────────────────────────────────────
+
+/// Foreign state, as in [`TryStateManager`](crate::models::TryStateManager).
+#[uniffi::export(callback_interface)]
+pub trait StateManager: Send + Sync {
+    /// The current state as JSON.
+    fn get_state(&self) -> String;
+    /// Apply `action` (JSON `{ "type", "payload" }`) and return the new
+    /// state as JSON, or [`ZubridgeFfiError::Rejected`] leaving state
+    /// unchanged.
+    fn dispatch_action(&self, action: String) -> Result<String, ZubridgeFfiError>;
+    /// The action types it handles, or `None` to accept any.
+    fn action_types(&self) -> Option<Vec<String>>;
+}
+
+/// Receives events for foreign subscribers, as in
+/// [`crate::emit::EventEmitter`]. `payload` is JSON. Return an error if the
+/// event could not be delivered; the subscriber's next update is then
+/// diffed against the last one that was.
+#[uniffi::export(callback_interface)]
+pub trait EventEmitter: Send + Sync {
+    fn emit(&self, target: String, event: String, payload: String) -> Result<(), ZubridgeFfiError>;
+}
+
+struct ForeignStateManager(Box<dyn StateManager>);
+
+impl TryStateManager for ForeignStateManager {
+    fn get_initial_state(&self) -> JsonValue {
+        serde_json::from_str(&self.0.get_state()).unwrap_or_else(|err| {
+            log::warn!("zubridge: foreign state is not valid JSON: {err}");
+            JsonValue::Null
+        })
+    }
+
+    fn try_dispatch_action(&mut self, action: JsonValue) -> crate::Result<JsonValue> {
+        let state = self
+            .0
+            .dispatch_action(action.to_string())
+            .map_err(|err| ZubridgeError::ActionProcessing(err.to_string()))?;
+        serde_json::from_str(&state).map_err(|e| ZubridgeError::Serialization(e.to_string()))
+    }
+
+    fn action_types(&self) -> Option<Vec<String>> {
+        self.0.action_types()
+    }
+}
+
+struct ForeignEmitter(Box<dyn EventEmitter>);
+
+impl crate::emit::EventEmitter for ForeignEmitter {
+    fn emit(&self, target: &str, event: &str, payload: &JsonValue) -> crate::Result<()> {
+        self.0
+            .emit(target.to_string(), event.to_string(), payload.to_string())
+            .map_err(|err| ZubridgeError::EmitError(err.to_string()))
+    }
+}
+
+// ── ZubridgeCore ──────────────────────────────────────────────────────────────
+
+/// Outcome of [`ZubridgeCore::dispatch`].
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct DispatchResult {
+    pub action_id: String,
+    /// True if the action is waiting behind another source's thunk; it is
+    /// applied and broadcast when that thunk completes.
+    pub queued: bool,
+}
+
+/// Per-action outcome of [`ZubridgeCore::batch_dispatch`].
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct BatchItemResult {
+    /// `None` if the action didn't parse.
+    pub action_id: Option<String>,
+    pub queued: bool,
+    /// Why the action failed, if it did.
+    pub error: Option<String>,
+}
+
+struct Inner {
+    state: StateManagerHandle,
+    queue: ActionQueueManager,
+    subscriptions: SubscriptionManager,
+    subscribers: BTreeSet<String>,
+    /// Created once an event emitter is set.
+    broadcaster: Option<Broadcaster<ForeignEmitter>>,
+}
+
+/// The Zubridge core for UniFFI targets. See the module docs.
+#[derive(uniffi::Object)]
+pub struct ZubridgeCore {
+    inner: Mutex<Inner>,
+}
+
+#[uniffi::export]
+impl ZubridgeCore {
+    #[uniffi::constructor]
+    pub fn new(state_manager: Box<dyn StateManager>) -> Arc<Self> {
+        let state = crate::state::new_handle(ForeignStateManager(state_manager));
+        Arc::new(Self {
+            inner: Mutex::new(Inner {
+                queue: ActionQueueManager::with_state_handle(state.clone()),
+                state,
+                subscriptions: SubscriptionManager::new(),
+                subscribers: BTreeSet::new(),
+                broadcaster: None,
+            }),
+        })
+    }
+
+    /// Set the emitter state updates are delivered to.
+    pub fn set_event_emitter(
+        &self,
+        emitter: Box<dyn EventEmitter>,
+    ) -> Result<(), ZubridgeFfiError> {
+        let emitter = ForeignEmitter(emitter);
+        match &mut self.inner()?.broadcaster {
+            Some(broadcaster) => broadcaster.set_emitter(emitter),
+            broadcaster => *broadcaster = Some(Broadcaster::new(emitter, STATE_UPDATE_EVENT)),
+        }
+        Ok(())
+    }
+
+    /// The current state as JSON, filtered to `subscriber_id`'s
+    /// subscription if given.
+    pub fn get_state(&self, subscriber_id: Option<String>) -> Result<String, ZubridgeFfiError> {
+        let inner = self.inner()?;
+        let state = crate::state::read_state(&inner.state)?;
+        Ok(match subscriber_id {
+            Some(id) => inner.subscriptions.filter_for(&id, &state),
+            None => state,
+        }
+        .to_string())
+    }
+
+    /// The action types the state manager handles, if it lists them.
+    pub fn action_types(&self) -> Result<Option<Vec<String>>, ZubridgeFfiError> {
+        Ok(crate::state::action_types(&self.inner()?.state)?)
+    }
+
+    /// Dispatch `action`, a JSON [`ZubridgeAction`], from `source_id`.
+    pub fn dispatch(
+        &self,
+        action: String,
+        source_id: String,
+    ) -> Result<DispatchResult, ZubridgeFfiError> {
+        let action = parse_action(&action)?;
+        self.inner()?.dispatch(action, source_id)
+    }
+
+    /// Dispatch each of `actions` in order, as [`dispatch`](Self::dispatch)
+    /// would. A failed action doesn't stop the rest.
+    pub fn batch_dispatch(
+        &self,
+        actions: Vec<String>,
+        source_id: String,
+    ) -> Result<Vec<BatchItemResult>, ZubridgeFfiError> {
+        let mut inner = self.inner()?;
+        Ok(actions
+            .iter()
+            .map(|action| {
+                let action = match parse_action(action) {
+                    Ok(action) => action,
+                    Err(err) => return BatchItemResult::failed(None, &err),
+                };
+                let action_id = action.id.clone();
+                match inner.dispatch(action, source_id.clone()) {
+                    Ok(result) => BatchItemResult {
+                        action_id: Some(result.action_id),
+                        queued: result.queued,
+                        error: None,
+                    },
+                    Err(err) => BatchItemResult::failed(action_id, &err),
+                }
+            })
+            .collect())
+    }
+
+    /// Register a thunk from `source_id` and start it. A root thunk holds
+    /// back other sources' actions until it completes.
+    pub fn register_thunk(
+        &self,
+        thunk_id: String,
+        source_id: String,
+        parent_id: Option<String>,
+        keys: Option<Vec<String>>,
+        bypass_access_control: bool,
+        immediate: bool,
+    ) -> Result<(), ZubridgeFfiError> {
+        let mut inner = self.inner()?;
+        inner.queue.register_thunk(
+            thunk_id.clone(),
+            parent_id,
+            source_id,
+            keys,
+            bypass_access_control,
+            immediate,
+        )?;
+        inner.queue.execute_thunk(&thunk_id);
+        Ok(())
+    }
+
+    /// Complete (or, with `error`, fail) a thunk, then apply and broadcast
+    /// the actions queued behind it.
+    pub fn complete_thunk(
+        &self,
+        thunk_id: String,
+        error: Option<String>,
+    ) -> Result<(), ZubridgeFfiError> {
+        let mut inner = self.inner()?;
+        let (_events, drained) = inner.queue.on_thunk_complete(&thunk_id, error)?;
+        inner.publish_drained(drained);
+        Ok(())
+    }
+
+    /// Start sending state updates to `subscriber_id`, for every key until
+    /// it [`subscribe`](Self::subscribe)s to some. Sends the current state.
+    pub fn add_subscriber(&self, subscriber_id: String) -> Result<(), ZubridgeFfiError> {
+        let mut inner = self.inner()?;
+        inner.subscribers.insert(subscriber_id.clone());
+        inner.resync(&subscriber_id)
+    }
+
+    /// Limit `subscriber_id`'s updates to `keys` (adding it if needed).
+    /// Returns its subscribed keys.
+    pub fn subscribe(
+        &self,
+        subscriber_id: String,
+        keys: Vec<String>,
+    ) -> Result<Vec<String>, ZubridgeFfiError> {
+        let mut inner = self.inner()?;
+        let subscribed = inner.subscriptions.subscribe(&subscriber_id, &keys);
+        inner.subscribers.insert(subscriber_id.clone());
+        inner.resync(&subscriber_id)?;
+        Ok(subscribed)
+    }
+
+    /// Remove `keys` from `subscriber_id`'s subscription; an empty
+    /// subscription receives every key again. Returns its subscribed keys.
+    pub fn unsubscribe(
+        &self,
+        subscriber_id: String,
+        keys: Vec<String>,
+    ) -> Result<Vec<String>, ZubridgeFfiError> {
+        let mut inner = self.inner()?;
+        let subscribed = inner.subscriptions.unsubscribe(&subscriber_id, &keys);
+        if inner.subscribers.contains(&subscriber_id) {
+            inner.resync(&subscriber_id)?;
+        }
+        Ok(subscribed)
+    }
+
+    /// Acknowledge that `subscriber_id` applied state update `update_id`.
+    pub fn ack_state_update(
+        &self,
+        subscriber_id: String,
+        update_id: String,
+    ) -> Result<(), ZubridgeFfiError> {
+        let inner = &mut *self.inner()?;
+        if let Some(broadcaster) = &mut inner.broadcaster {
+            broadcaster.ack(&inner.subscriptions, &subscriber_id, &update_id)?;
+        }
+        Ok(())
+    }
+
+    /// Forget everything about `subscriber_id`: subscription, delta
+    /// baseline, sequence, pending acks and its thunks. Actions queued
+    /// behind those thunks are applied and broadcast.
+    pub fn remove_subscriber(&self, subscriber_id: String) -> Result<(), ZubridgeFfiError> {
+        let mut inner = self.inner()?;
+        inner.subscribers.remove(&subscriber_id);
+        inner.subscriptions.drop_label(&subscriber_id);
+        if let Some(broadcaster) = &mut inner.broadcaster {
+            broadcaster.forget(&subscriber_id);
+        }
+        let drained = inner.queue.drop_label(&subscriber_id)?;
+        inner.publish_drained(drained);
+        Ok(())
+    }
+
+    /// Number of actions waiting behind a thunk.
+    pub fn queue_len(&self) -> Result<u64, ZubridgeFfiError> {
+        Ok(self.inner()?.queue.queue_len() as u64)
+    }
+}
+
+impl ZubridgeCore {
+    fn inner(&self) -> Result<MutexGuard<'_, Inner>, ZubridgeFfiError> {
+        self.inner
+            .lock()
+            .map_err(|e| ZubridgeError::StateError(e.to_string()).into())
+    }
+}
+
+impl BatchItemResult {
+    fn failed(action_id: Option<String>, err: &ZubridgeFfiError) -> Self {
+        Self {
+            action_id,
+            queued: false,
+            error: Some(err.to_string()),
+        }
+    }
+}
+
+impl Inner {
+    fn dispatch(
+        &mut self,
+        action: ZubridgeAction,
+        source_id: String,
+    ) -> Result<DispatchResult, ZubridgeFfiError> {
+        let thunk_id = action.thunk_parent_id.clone();
+        match self.queue.dispatch(action, source_id)? {
+            DispatchOutcome::Executed {
+                action_id,
+                state,
+                changed_keys,
+                drained,
+            } => {
+                self.broadcast(state, changed_keys.as_deref(), Some(&action_id), thunk_id);
+                self.publish_drained(drained);
+                Ok(DispatchResult {
+                    action_id,
+                    queued: false,
+                })
+            }
+            DispatchOutcome::Queued { action_id, .. } => Ok(DispatchResult {
+                action_id,
+                queued: true,
+            }),
+        }
+    }
+
+    /// Send `subscriber_id` its view of the current state.
+    fn resync(&mut self, subscriber_id: &str) -> Result<(), ZubridgeFfiError> {
+        let state = crate::state::read_state(&self.state)?;
+        if let Some(broadcaster) = &mut self.broadcaster {
+            let targets = [subscriber_id.to_string()];
+            broadcaster.broadcast(&self.subscriptions, &targets, state, None, None)?;
+        }
+        Ok(())
+    }
+
+    /// Send every subscriber its update for `state`, if its view changed.
+    fn broadcast(
+        &mut self,
+        state: JsonValue,
+        changed_keys: Option<&[String]>,
+        action_id: Option<&str>,
+        thunk_id: Option<String>,
+    ) {
+        let Some(broadcaster) = &mut self.broadcaster else {
+            return;
+        };
+        let source = UpdateSource {
+            action_id: action_id.map(str::to_string),
+            thunk_id,
+            ..UpdateSource::default()
+        };
+        let targets: Vec<String> = self.subscribers.iter().cloned().collect();
+        let result = broadcaster.broadcast(
+            &self.subscriptions,
+            &targets,
+            state,
+            changed_keys,
+            Some(source),
+        );
+        // The action has been applied either way; failed subscribers catch
+        // up with their next update.
+        if let Err(err) = result {
+            log::warn!("zubridge: state update broadcast failed: {err}");
+        }
+    }
+
+    /// Broadcast the state of each drained action.
+    fn publish_drained(&mut self, drained: Vec<ExecutedAction>) {
+        for executed in drained {
+            let action_id = executed.action.id.clone().unwrap_or_default();
+            match executed.result {
+                Ok(state) => {
+                    let thunk_id = executed.action.thunk_parent_id.clone();
+                    let changed_keys = executed.changed_keys.as_deref();
+                    self.broadcast(state, changed_keys, Some(&action_id), thunk_id);
+                }
+                Err(err) => log::debug!("zubridge: queued action {action_id} rejected: {err}"),
+            }
+        }
+    }
+}
+
+fn parse_action(action: &str) -> Result<ZubridgeAction, ZubridgeFfiError> {
+    serde_json::from_str(action).map_err(|e| ZubridgeFfiError::InvalidJson {
+        message: e.to_string(),
+    })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A counter, as a foreign state manager would implement it.
+    struct Counter(Mutex<i64>);
+
+    impl StateManager for Counter {
+        fn get_state(&self) -> String {
+            json!({ "count": *self.0.lock().unwrap() }).to_string()
+        }
+
+        fn dispatch_action(&self, action: String) -> Result<String, ZubridgeFfiError> {
+            let action: JsonValue = serde_json::from_str(&action).unwrap();
+            match action["type"].as_str() {
+                Some("INC") => *self.0.lock().unwrap() += 1,
+                _ => {
+                    return Err(ZubridgeFfiError::Rejected {
+                        message: "unknown action".to_string(),
+                    })
+                }
+            }
+            Ok(self.get_state())
+        }
+
+        fn action_types(&self) -> Option<Vec<String>> {
+            Some(vec!["INC".to_string()])
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Recorder(Arc<Mutex<Vec<(String, JsonValue)>>>);
+
+    impl EventEmitter for Recorder {
+        fn emit(
+            &self,
+            target: String,
+            _event: String,
+            payload: String,
+        ) -> Result<(), ZubridgeFfiError> {
+            let payload = serde_json::from_str(&payload).unwrap();
+            self.0.lock().unwrap().push((target, payload));
+            Ok(())
+        }
+    }
+
+    fn core() -> (Arc<ZubridgeCore>, Recorder) {
+        let core = ZubridgeCore::new(Box::new(Counter(Mutex::new(0))));
+        let recorder = Recorder::default();
+        core.set_event_emitter(Box::new(recorder.clone())).unwrap();
+        (core, recorder)
+    }
+
+    #[test]
+    fn dispatch_updates_subscribers_with_deltas() {
+        let (core, recorder) = core();
+        core.add_subscriber("main".to_string()).unwrap();
+        let result = core
+            .dispatch(r#"{"action_type":"INC"}"#.to_string(), "main".to_string())
+            .unwrap();
+        assert!(!result.queued);
+        assert_eq!(core.get_state(None).unwrap(), r#"{"count":1}"#);
+
+        let updates = recorder.0.lock().unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].1["full_state"], json!({ "count": 0 }));
+        assert_eq!(updates[1].1["delta"]["changed"], json!({ "count": 1 }));
+        assert_eq!(updates[1].1["source"]["action_id"], json!(result.action_id));
+    }
+
+    #[test]
+    fn errors_cross_as_ffi_errors() {
+        let (core, _) = core();
+        assert!(matches!(
+            core.dispatch("not json".to_string(), "main".to_string()),
+            Err(ZubridgeFfiError::InvalidJson { .. })
+        ));
+        assert!(matches!(
+            core.dispatch(r#"{"action_type":"DEC"}"#.to_string(), "main".to_string()),
+            Err(ZubridgeFfiError::Rejected { .. })
+        ));
+        let results = core
+            .batch_dispatch(
+                vec![
+                    r#"{"action_type":"INC"}"#.to_string(),
+                    "{".to_string(),
+                    r#"{"action_type":"INC"}"#.to_string(),
+                ],
+                "main".to_string(),
+            )
+            .unwrap();
+        let failed: Vec<bool> = results.iter().map(|r| r.error.is_some()).collect();
+        assert_eq!(failed, vec![false, true, false]);
+        assert_eq!(core.get_state(None).unwrap(), r#"{"count":2}"#);
+    }
+
+    #[test]
+    fn actions_wait_behind_another_sources_thunk() {
+        let (core, _) = core();
+        core.register_thunk(
+            "t1".to_string(),
+            "main".to_string(),
+            None,
+            None,
+            false,
+            false,
+        )
+        .unwrap();
+        let result = core
+            .dispatch(r#"{"action_type":"INC"}"#.to_string(), "other".to_string())
+            .unwrap();
+        assert!(result.queued);
+        assert_eq!(core.queue_len().unwrap(), 1);
+
+        core.complete_thunk("t1".to_string(), None).unwrap();
+        assert_eq!(core.queue_len().unwrap(), 0);
+        assert_eq!(core.get_state(None).unwrap(), r#"{"count":1}"#);
+    }
+}
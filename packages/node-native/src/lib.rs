@@ -0,0 +1,7 @@
+//! The `@zubridge/node-native` addon: builds
+//! [`zubridge_core::wrappers::napi`] into a `.node` library Node can
+//! `require`. The bindings themselves live in `zubridge-core` behind its
+//! `napi` feature; this crate only provides the `cdylib` target, so crates
+//! depending on `zubridge-core` don't link a shared library they never load.
+
+pub use zubridge_core::wrappers::napi::*;
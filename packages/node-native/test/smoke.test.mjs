@@ -0,0 +1,79 @@
+// Smoke test for the Node-API addon: load it from plain Node, dispatch an
+// action through ZubridgeCore and receive the resulting state update.
+//
+//   cargo build -p zubridge-node-native
+//   cp target/debug/libzubridge_node_native.so packages/node-native/zubridge.node
+//   node --test packages/node-native/test/
+import assert from 'node:assert/strict';
+import { createRequire } from 'node:module';
+import { test } from 'node:test';
+
+const require = createRequire(import.meta.url);
+const addon = process.env.ZUBRIDGE_NODE_ADDON ?? new URL('../zubridge.node', import.meta.url).pathname;
+const { ZubridgeCore } = require(addon);
+
+function counterStore() {
+  let state = { counter: 0, theme: 'dark' };
+  return {
+    getState: () => state,
+    dispatchAction(action) {
+      if (action.type !== 'COUNTER:INCREMENT') {
+        throw new Error(`unknown action ${action.type}`);
+      }
+      state = { ...state, counter: state.counter + 1 };
+      return state;
+    },
+  };
+}
+
+// Collect emitted events; `next()` resolves with the next one.
+function recorder() {
+  const events = [];
+  const waiting = [];
+  return {
+    emit(target, event, payload) {
+      const entry = { target, event, payload };
+      const resolve = waiting.shift();
+      if (resolve) resolve(entry);
+      else events.push(entry);
+    },
+    next() {
+      if (events.length > 0) return Promise.resolve(events.shift());
+      return new Promise((resolve, reject) => {
+        // Also keeps the event loop alive: the emitter callback doesn't.
+        const timer = setTimeout(() => reject(new Error('no state update received')), 5000);
+        waiting.push((entry) => {
+          clearTimeout(timer);
+          resolve(entry);
+        });
+      });
+    },
+  };
+}
+
+test('dispatch delivers a state-update to subscribers', async () => {
+  const core = new ZubridgeCore(counterStore());
+  const events = recorder();
+  core.setEventEmitter((target, event, payload) => events.emit(target, event, payload));
+
+  core.addSubscriber('main');
+  const initial = await events.next();
+  assert.equal(initial.target, 'main');
+  assert.equal(initial.event, 'state-update');
+  assert.deepEqual(initial.payload.full_state, { counter: 0, theme: 'dark' });
+
+  const actionId = await core.dispatch({ id: 'a1', action_type: 'COUNTER:INCREMENT' });
+  assert.equal(actionId, 'a1');
+  const update = await events.next();
+  assert.equal(update.event, 'state-update');
+  assert.equal(update.payload.seq, initial.payload.seq + 1);
+  assert.deepEqual(update.payload.delta.changed, { counter: 1 });
+  assert.equal(update.payload.source.action_id, 'a1');
+  assert.deepEqual(core.getState(), { counter: 1, theme: 'dark' });
+});
+
+test('a throwing state manager rejects the dispatch', async () => {
+  const core = new ZubridgeCore(counterStore());
+  await assert.rejects(core.dispatch({ action_type: 'UNKNOWN' }), /unknown action UNKNOWN/);
+  assert.deepEqual(core.getState(), { counter: 0, theme: 'dark' });
+});
@@ -9,6 +9,10 @@ const COMMANDS: &[&str] = &[
     "subscribe",
     "unsubscribe",
     "get_window_subscriptions",
+    "get_action_types",
+    "devtools_entries",
+    "devtools_jump",
+    "devtools_resume",
 ];
 
 fn main() {
@@ -0,0 +1,42 @@
+//! Time-travel debugging commands. Only registered in debug builds, and only
+//! callable by webviews whose capability grants the `allow-devtools-*`
+//! permissions and whose permission policy allows
+//! [`PermissionRequest::Devtools`](zubridge_core::permission::PermissionRequest::Devtools).
+
+use tauri::{command, AppHandle, Runtime, Window};
+
+use zubridge_core::devtools::DevtoolsEntry;
+
+use crate::models::DevtoolsJumpArgs;
+use crate::Result;
+use crate::ZubridgeExt;
+
+/// The recorded entries, with each state filtered to the calling webview's
+/// subscriptions.
+#[command]
+pub(crate) async fn devtools_entries<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+) -> Result<Vec<DevtoolsEntry>> {
+    app.zubridge().check_devtools(window.label())?;
+    app.zubridge().devtools_entries(Some(window.label()))
+}
+
+#[command]
+pub(crate) async fn devtools_jump<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    args: DevtoolsJumpArgs,
+) -> Result<()> {
+    app.zubridge().check_devtools(window.label())?;
+    app.zubridge().devtools_jump(args.index)
+}
+
+#[command]
+pub(crate) async fn devtools_resume<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+) -> Result<()> {
+    app.zubridge().check_devtools(window.label())?;
+    app.zubridge().devtools_resume()
+}
@@ -2,10 +2,11 @@ use tauri::{command, AppHandle, Runtime, Window};
 use uuid::Uuid;
 
 use crate::models::{
-    BatchDispatchArgs, BatchDispatchResult, DispatchActionArgs, DispatchActionResult,
+    BatchDispatchArgs, BatchDispatchResult, BatchFailure, DispatchActionArgs,
+    DispatchActionResult,
 };
 use crate::Result;
-use crate::ZubridgeExt;
+use crate::{DispatchStatus, ZubridgeExt};
 
 #[command]
 pub(crate) async fn dispatch_action<R: Runtime>(
@@ -19,7 +20,12 @@ pub(crate) async fn dispatch_action<R: Runtime>(
     }
     // The dispatching webview's label is authoritative; ignore any client-supplied value.
     action.source_label = Some(window.label().to_string());
-    let action_id = app.zubridge().dispatch_action(action)?;
+    // An action held behind another window's root thunk resolves only once
+    // the thunk completes and the action has been applied and broadcast.
+    let action_id = match app.zubridge().submit_action(action)? {
+        DispatchStatus::Applied(action_id) => action_id,
+        DispatchStatus::Queued(pending) => pending.wait().await?,
+    };
     Ok(DispatchActionResult { action_id })
 }
 
@@ -41,5 +47,20 @@ pub(crate) async fn batch_dispatch<R: Runtime>(
             action
         })
         .collect();
-    app.zubridge().batch_dispatch(batch_id, actions)
+    let (mut result, queued) = app.zubridge().submit_batch(batch_id, actions)?;
+    for pending in queued {
+        let action_id = pending.action_id().to_string();
+        match pending.wait().await {
+            Ok(action_id) => result.acked_action_ids.push(action_id),
+            Err(err) => {
+                if result.failed.is_none() {
+                    result.failed = Some(BatchFailure {
+                        action_id,
+                        message: err.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    Ok(result)
 }
@@ -1,3 +1,5 @@
+#[cfg(debug_assertions)]
+pub mod devtools;
 pub mod dispatch;
 pub mod state;
 pub mod subscription;
@@ -1,12 +1,21 @@
 use tauri::{command, AppHandle, Runtime, Window};
 
-use crate::models::{GetStateArgs, GetStateResult, JsonValue};
+use zubridge_core::subscription::filter_paths;
+
+use crate::models::{GetActionTypesResult, GetStateArgs, GetStateResult, JsonValue};
 use crate::Result;
 use crate::ZubridgeExt;
 
+/// The calling webview's view of the state: checked against the permission
+/// policy and filtered by its subscriptions, like [`get_state`].
 #[command]
-pub(crate) async fn get_initial_state<R: Runtime>(app: AppHandle<R>) -> Result<JsonValue> {
-    app.zubridge().get_initial_state()
+pub(crate) async fn get_initial_state<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+) -> Result<JsonValue> {
+    let source_label = window.label();
+    app.zubridge().check_read(source_label, None)?;
+    app.zubridge().get_state(Some(source_label))
 }
 
 #[command]
@@ -16,8 +25,11 @@ pub(crate) async fn get_state<R: Runtime>(
     args: Option<GetStateArgs>,
 ) -> Result<GetStateResult> {
     // Filter via the SubscriptionManager using the runtime-supplied webview label,
-    // so a webview cannot read keys it isn't subscribed to.
+    // so a webview cannot read keys it isn't subscribed to. The permission
+    // policy, if any, may bar the webview from reading at all.
     let source_label = window.label().to_string();
+    app.zubridge()
+        .check_read(&source_label, args.as_ref().and_then(|a| a.keys.as_deref()))?;
 
     // If this is a renderer-driven resync, reset per-label tracking so the
     // state we're about to return becomes the new ground truth:
@@ -35,29 +47,27 @@ pub(crate) async fn get_state<R: Runtime>(
     //      keeps the key indefinitely. Clearing the baseline forces the next
     //      broadcast to emit a full-state payload, realigning both sides.
     if matches!(args.as_ref().and_then(|a| a.is_resync), Some(true)) {
-        if let Ok(mut tracker) = app.zubridge().update_tracker().write() {
-            tracker.drop_label(&source_label);
-        }
-        if let Ok(mut deltas) = app.zubridge().deltas().write() {
-            deltas.forget(&source_label);
+        if let Ok(mut broadcaster) = app.zubridge().broadcaster().lock() {
+            broadcaster.reset_label(&source_label);
         }
     }
 
     let mut value = app.zubridge().get_state(Some(&source_label))?;
 
     // The optional client-side key list narrows further but cannot widen.
-    if let Some(args) = args {
-        if let Some(keys) = args.keys {
-            if let JsonValue::Object(map) = &value {
-                let mut filtered = serde_json::Map::with_capacity(keys.len());
-                for key in keys {
-                    if let Some(v) = map.get(&key) {
-                        filtered.insert(key, v.clone());
-                    }
-                }
-                value = JsonValue::Object(filtered);
-            }
-        }
+    // Keys may be nested paths, like subscriptions.
+    if let Some(keys) = args.and_then(|a| a.keys) {
+        value = filter_paths(&value, keys.iter().map(String::as_str));
     }
     Ok(GetStateResult { value })
 }
+
+/// Action types the registered state manager declares, so the renderer can
+/// validate or autocomplete action types before dispatching.
+#[command]
+pub(crate) async fn get_action_types<R: Runtime>(
+    app: AppHandle<R>,
+) -> Result<GetActionTypesResult> {
+    let action_types = app.zubridge().action_types()?;
+    Ok(GetActionTypesResult { action_types })
+}
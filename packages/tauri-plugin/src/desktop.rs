@@ -1,32 +1,71 @@
 use serde::de::DeserializeOwned;
-use std::sync::{Arc, Mutex, RwLock};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
+use std::time::Duration;
 
 use serde_json::json;
-use tauri::{plugin::PluginApi, AppHandle, Emitter, Manager, Runtime};
+use tauri::async_runtime::{channel, Receiver, Sender};
+use tauri::{plugin::PluginApi, AppHandle, Emitter, EventTarget, Manager, Runtime};
 use uuid::Uuid;
+use zubridge_core::batching::{
+    BatchAckPayload, BatchActionResult, BatchPayload, BatchingConfig, BatchingDispatcher,
+};
+use zubridge_core::broadcast::{Broadcaster, EmitFailures};
+#[cfg(feature = "devtools")]
+use zubridge_core::devtools::remote::{DevtoolsHost, RemoteDevtools, RemoteDevtoolsConfig};
+use zubridge_core::devtools::{DevtoolsEntry, DevtoolsSession};
+use zubridge_core::history::{HistoryOp, UndoHistory};
+use zubridge_core::journal::{ActionJournal, JournalConfig};
+use zubridge_core::permission::{self, PermissionRequest};
+use zubridge_core::persistence::JsonFileBackend;
+use zubridge_core::wrappers::tauri::TauriEmitter;
 
-use crate::core::{DeltaCalculator, DeltaResult, StateUpdateTracker, SubscriptionManager, ThunkRegistry};
+use crate::core::{
+    ActionQueueManager, DispatchOutcome, ExecutedAction, QueuedAction, SubscriptionManager,
+    HOST_SOURCE_LABEL,
+};
 use crate::core::state_manager::{self, StateManagerHandle};
+use crate::core::thunk_manager::{AckLatencyHistogram, SlowConsumerEvent};
+use crate::{ZubridgeExt, SLOW_CONSUMER_EVENT};
 use crate::models::{
-    BatchDispatchResult, BatchFailure, JsonValue, StateManager, StateUpdatePayload, UpdateSource,
-    ZubridgeAction, ZubridgeOptions,
+    BatchDispatchResult, BatchFailure, JsonValue, TryStateManager, UpdateSource, ZubridgeAction,
+    ZubridgeOptions,
 };
 
-/// Per-webview monotonic sequence counter for state-update events.
-#[derive(Debug, Default)]
-pub struct SequenceTracker {
-    by_label: std::collections::HashMap<String, u64>,
+/// The plugin's [`Broadcaster`], emitting to webviews by label.
+pub type TauriBroadcaster<R> = Broadcaster<TauriEmitter<R>>;
+
+/// Outcome of [`Zubridge::submit_action`].
+pub enum DispatchStatus {
+    /// The action was applied and the resulting state broadcast.
+    Applied(String),
+    /// The action is held behind a running root thunk. It is applied and
+    /// broadcast when that thunk completes.
+    Queued(PendingDispatch),
+}
+
+/// Handle to an action waiting in the action queue.
+pub struct PendingDispatch {
+    action_id: String,
+    receiver: Receiver<crate::Result<()>>,
 }
 
-impl SequenceTracker {
-    pub fn next(&mut self, label: &str) -> u64 {
-        let entry = self.by_label.entry(label.to_string()).or_insert(0);
-        *entry += 1;
-        *entry
+impl PendingDispatch {
+    pub fn action_id(&self) -> &str {
+        &self.action_id
     }
 
-    pub fn forget(&mut self, label: &str) {
-        self.by_label.remove(label);
+    /// Resolve once the queued action has been applied and broadcast.
+    pub async fn wait(mut self) -> crate::Result<String> {
+        match self.receiver.recv().await {
+            Some(Ok(())) => Ok(self.action_id),
+            Some(Err(err)) => Err(err),
+            None => Err(crate::Error::ActionProcessing {
+                action_id: Some(self.action_id),
+                message: "queued action was discarded before it ran".to_string(),
+            }),
+        }
     }
 }
 
@@ -34,15 +73,21 @@ pub fn init<R: Runtime, C: DeserializeOwned>(
     app: &AppHandle<R>,
     _api: PluginApi<R, C>,
 ) -> crate::Result<Zubridge<R>> {
+    let options = ZubridgeOptions::default();
+    let mut broadcaster = Broadcaster::new(TauriEmitter::new(app.clone()), &options.event_name);
+    let reporter = app.clone();
+    broadcaster.on_slow_consumer(move |event| report_slow_consumer(&reporter, event));
     Ok(Zubridge {
         app: app.clone(),
-        options: ZubridgeOptions::default(),
+        options,
         subscriptions: Arc::new(RwLock::new(SubscriptionManager::new())),
-        deltas: Arc::new(RwLock::new(DeltaCalculator::new())),
-        thunks: Arc::new(RwLock::new(ThunkRegistry::new())),
-        update_tracker: Arc::new(RwLock::new(StateUpdateTracker::new())),
-        sequences: Arc::new(RwLock::new(SequenceTracker::default())),
-        broadcast_lock: Arc::new(Mutex::new(())),
+        queue: Arc::new(Mutex::new(ActionQueueManager::default())),
+        pending_dispatches: Arc::new(Mutex::new(HashMap::new())),
+        broadcaster: Arc::new(Mutex::new(broadcaster)),
+        throttle_timer: None,
+        ack_sweeper: None,
+        #[cfg(feature = "devtools")]
+        remote_devtools: None,
     })
 }
 
@@ -51,14 +96,24 @@ pub struct Zubridge<R: Runtime> {
     app: AppHandle<R>,
     options: ZubridgeOptions,
     subscriptions: Arc<RwLock<SubscriptionManager>>,
-    deltas: Arc<RwLock<DeltaCalculator>>,
-    thunks: Arc<RwLock<ThunkRegistry>>,
-    update_tracker: Arc<RwLock<StateUpdateTracker>>,
-    sequences: Arc<RwLock<SequenceTracker>>,
-    /// Serialises broadcast_state calls so concurrent dispatches can't interleave
+    /// Action scheduler + thunk lifecycle. Every dispatch goes through here so
+    /// actions from other windows wait while a root thunk is running.
+    queue: Arc<Mutex<ActionQueueManager>>,
+    /// Senders for `submit_action` callers whose action is still queued,
+    /// keyed by action id. Resolved when the action is drained.
+    pending_dispatches: Arc<Mutex<HashMap<String, Sender<crate::Result<()>>>>>,
+    /// Per-webview deltas, sequence numbers, throttling and acks. Locking it
+    /// also serialises broadcasts, so concurrent dispatches can't interleave
     /// the (read prev → compute delta → emit → record new prev) sequence and
     /// produce stale deltas computed against an outdated baseline.
-    broadcast_lock: Arc<Mutex<()>>,
+    broadcaster: Arc<Mutex<TauriBroadcaster<R>>>,
+    /// Stops the throttle timer thread when dropped.
+    throttle_timer: Option<mpsc::Sender<()>>,
+    /// Stops the ack-timeout sweeper thread when dropped.
+    ack_sweeper: Option<mpsc::Sender<()>>,
+    /// Redux DevTools bridge, when `options.remote_devtools` is set.
+    #[cfg(feature = "devtools")]
+    remote_devtools: Option<RemoteDevtools>,
 }
 
 impl<R: Runtime> Zubridge<R> {
@@ -67,6 +122,40 @@ impl<R: Runtime> Zubridge<R> {
     }
 
     pub fn set_options(&mut self, options: ZubridgeOptions) {
+        if let Ok(mut broadcaster) = self.broadcaster.lock() {
+            broadcaster.set_event_name(&options.event_name);
+            broadcaster.deltas_mut().set_mode(options.delta_mode);
+            broadcaster.set_throttle(options.update_throttle);
+            broadcaster
+                .updates_mut()
+                .set_max_in_flight(options.max_in_flight);
+        }
+        // Replacing a timer's sender stops the previous timer.
+        let app = &self.app;
+        self.throttle_timer = options
+            .update_throttle
+            .map(|window| spawn_timer(app.clone(), "throttle", window, Self::flush_throttled));
+        self.ack_sweeper = options.ack_timeout.map(|timeout| {
+            spawn_timer(
+                app.clone(),
+                "ack-sweeper",
+                timeout,
+                Self::sweep_expired_acks,
+            )
+        });
+        let journal = options.journal.clone().and_then(|config| {
+            self.open_journal(config)
+                .map_err(|err| log::warn!("zubridge: action journal disabled: {err}"))
+                .ok()
+        });
+        let devtools = options.devtools_capacity.map(DevtoolsSession::new);
+        #[cfg(feature = "devtools")]
+        let devtools = self.start_remote_devtools(options.remote_devtools.clone(), devtools);
+        if let Ok(mut queue) = self.queue.lock() {
+            queue.set_journal(journal);
+            queue.set_history(options.history.clone().map(UndoHistory::new));
+            queue.set_devtools(devtools);
+        }
         self.options = options;
     }
 
@@ -74,16 +163,12 @@ impl<R: Runtime> Zubridge<R> {
         &self.subscriptions
     }
 
-    pub fn deltas(&self) -> &Arc<RwLock<DeltaCalculator>> {
-        &self.deltas
+    pub fn queue(&self) -> &Arc<Mutex<ActionQueueManager>> {
+        &self.queue
     }
 
-    pub fn thunks(&self) -> &Arc<RwLock<ThunkRegistry>> {
-        &self.thunks
-    }
-
-    pub fn update_tracker(&self) -> &Arc<RwLock<StateUpdateTracker>> {
-        &self.update_tracker
+    pub fn broadcaster(&self) -> &Arc<Mutex<TauriBroadcaster<R>>> {
+        &self.broadcaster
     }
 
     /// Look up the registered state manager handle, returning an error if none.
@@ -94,15 +179,90 @@ impl<R: Runtime> Zubridge<R> {
             .ok_or(crate::Error::StateManagerMissing)
     }
 
+    /// Point the action queue at `handle`. Called by the plugin builder when
+    /// a state manager is supplied up front.
+    pub(crate) fn set_state_handle(&self, handle: StateManagerHandle) -> crate::Result<()> {
+        self.lock_queue()?.set_state_handle(handle);
+        Ok(())
+    }
+
+    fn lock_queue(&self) -> crate::Result<MutexGuard<'_, ActionQueueManager>> {
+        self.queue
+            .lock()
+            .map_err(|e| crate::Error::StateError(e.to_string()))
+    }
+
+    fn lock_broadcaster(&self) -> crate::Result<MutexGuard<'_, TauriBroadcaster<R>>> {
+        self.broadcaster
+            .lock()
+            .map_err(|e| crate::Error::StateError(e.to_string()))
+    }
+
+    /// Check `action` against the configured
+    /// [`PermissionPolicy`](zubridge_core::permission::PermissionPolicy), then
+    /// against the keys the configured
+    /// [`AccessControl`](zubridge_core::access::AccessControl) grants
+    /// `source_label`. `queue`
+    /// supplies the record of the thunk the action belongs to, which must
+    /// have been registered by `source_label`. With a history attached, an
+    /// undo or redo is checked against the keys it would restore instead.
+    fn check_access(
+        &self,
+        queue: &ActionQueueManager,
+        action: &ZubridgeAction,
+        source_label: &str,
+    ) -> zubridge_core::Result<()> {
+        let thunk = action
+            .thunk_parent_id
+            .as_deref()
+            .and_then(|thunk_id| queue.thunk_manager().get(thunk_id));
+        self.check_permission(source_label, &PermissionRequest::Dispatch { action, thunk })?;
+        let access_control = &self.options.access_control;
+        let history_step = HistoryOp::from_action_type(&action.action_type)
+            .zip(queue.history())
+            .map(|(op, history)| history.affected_keys(op));
+        match history_step {
+            Some(keys) => access_control.check_history(source_label, thunk, &keys),
+            None => access_control.check_action(source_label, action, thunk),
+        }
+    }
+
+    /// Consult the configured permission policy, if any. Host-dispatched
+    /// operations are never checked.
+    fn check_permission(
+        &self,
+        source_label: &str,
+        request: &PermissionRequest<'_>,
+    ) -> zubridge_core::Result<()> {
+        match &self.options.permission_policy {
+            Some(policy) if source_label != HOST_SOURCE_LABEL => {
+                permission::enforce(policy.as_ref(), source_label, request)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check that `source_label` may read state, optionally narrowed to
+    /// `keys`, under the configured permission policy.
+    pub fn check_read(&self, source_label: &str, keys: Option<&[String]>) -> crate::Result<()> {
+        Ok(self.check_permission(source_label, &PermissionRequest::ReadState { keys })?)
+    }
+
+    /// Check that `source_label` may use the devtools commands under the
+    /// configured permission policy.
+    pub fn check_devtools(&self, source_label: &str) -> crate::Result<()> {
+        Ok(self.check_permission(source_label, &PermissionRequest::Devtools)?)
+    }
+
     /// Read the current state from the state manager.
     pub fn get_initial_state(&self) -> crate::Result<JsonValue> {
-        Ok(state_manager::read_state(&self.state_handle()?)?)
+        self.current_state()
     }
 
     /// Read state filtered to the keys subscribed by `source_label`. If the
     /// label has no explicit subscription, the full state is returned.
     pub fn get_state(&self, source_label: Option<&str>) -> crate::Result<JsonValue> {
-        let full = state_manager::read_state(&self.state_handle()?)?;
+        let full = self.current_state()?;
         match source_label {
             Some(label) => {
                 let subs = self
@@ -115,43 +275,132 @@ impl<R: Runtime> Zubridge<R> {
         }
     }
 
-    /// Dispatch a single action and broadcast the resulting state. The
-    /// broadcast lock is acquired *before* the state-manager dispatch so the
-    /// state we read is always the state we broadcast — without that, two
+    /// The state webviews see: the state manager's, or the devtools
+    /// session's past state while it is paused.
+    fn current_state(&self) -> crate::Result<JsonValue> {
+        let jumped = self
+            .lock_queue()?
+            .devtools()
+            .and_then(DevtoolsSession::jumped_state)
+            .cloned();
+        match jumped {
+            Some(state) => Ok(state),
+            None => Ok(state_manager::read_state(&self.state_handle()?)?),
+        }
+    }
+
+    /// Action types declared by the registered state manager, or `None` if
+    /// it accepts arbitrary types (see `TryStateManager::action_types`).
+    pub fn action_types(&self) -> crate::Result<Option<Vec<String>>> {
+        Ok(state_manager::action_types(&self.state_handle()?)?)
+    }
+
+    /// Dispatch a single action and broadcast the resulting state.
+    ///
+    /// Returns as soon as the action has been applied, or — if another
+    /// window's root thunk is running — as soon as it has been queued; a
+    /// queued action is applied and broadcast when that thunk completes. Use
+    /// [`submit_action`](Self::submit_action) to await queued actions.
+    ///
+    /// An action rejected by the state manager returns
+    /// [`Error::ActionProcessing`](crate::Error::ActionProcessing); state is
+    /// left unchanged and nothing is broadcast. An action affecting keys
+    /// outside those granted to the source webview is rejected with
+    /// [`Error::AccessDenied`](crate::Error::AccessDenied) before it reaches
+    /// the queue. Once the state manager has applied the action it returns
+    /// Ok, even if the update couldn't be emitted to some webview; such
+    /// failures are logged and counted in
+    /// [`emit_failures`](Self::emit_failures).
+    pub fn dispatch_action(&self, action: ZubridgeAction) -> crate::Result<String> {
+        match self.submit_action(action)? {
+            DispatchStatus::Applied(action_id) => Ok(action_id),
+            DispatchStatus::Queued(pending) => Ok(pending.action_id),
+        }
+    }
+
+    /// Submit a single action through the action queue.
+    ///
+    /// The broadcast lock is acquired *before* the state-manager dispatch so
+    /// the state we read is always the state we broadcast — without that, two
     /// concurrent dispatches A → state_A and (A → A→B) → state_AB could
     /// interleave (B's broadcast wins the lock first, records state_AB; A's
     /// broadcast then emits state_A as a delta against state_AB and records
     /// state_A as the new baseline, silently undoing B's changes).
-    pub fn dispatch_action(&self, action: ZubridgeAction) -> crate::Result<String> {
+    pub fn submit_action(&self, mut action: ZubridgeAction) -> crate::Result<DispatchStatus> {
         let action_id = action
             .id
+            .get_or_insert_with(|| Uuid::new_v4().to_string())
+            .clone();
+        let source_label = action
+            .source_label
             .clone()
-            .unwrap_or_else(|| Uuid::new_v4().to_string());
-
-        let _broadcast_guard = self
-            .broadcast_lock
-            .lock()
-            .map_err(|e| crate::Error::StateError(e.to_string()))?;
+            .unwrap_or_else(|| HOST_SOURCE_LABEL.to_string());
+        let thunk_id = action.thunk_parent_id.clone();
 
-        let new_state = state_manager::dispatch(&self.state_handle()?, action.to_legacy_json())
-            .map_err(|e| crate::Error::ActionProcessing {
-                action_id: Some(action_id.clone()),
-                message: e.to_string(),
-            })?;
+        let mut broadcaster = self.lock_broadcaster()?;
 
-        let source = UpdateSource {
-            action_id: Some(action_id.clone()),
-            thunk_id: action.thunk_parent_id.clone(),
+        let outcome = {
+            let mut queue = self.lock_queue()?;
+            self.check_access(&queue, &action, &source_label)
+                .map_err(|e| dispatch_error(&action_id, e))?;
+            queue
+                .dispatch(action, source_label)
+                .map_err(|e| dispatch_error(&action_id, e))?
         };
 
-        self.broadcast_state_locked(new_state, Some(source))?;
-        Ok(action_id)
+        match outcome {
+            DispatchOutcome::Executed {
+                action_id,
+                state,
+                changed_keys,
+                drained,
+            } => {
+                let source = UpdateSource {
+                    action_id: Some(action_id.clone()),
+                    thunk_id,
+                    ..UpdateSource::default()
+                };
+                self.broadcast_state_locked(
+                    &mut broadcaster,
+                    state,
+                    changed_keys.as_deref(),
+                    Some(source),
+                );
+                self.publish_drained_locked(&mut broadcaster, drained);
+                Ok(DispatchStatus::Applied(action_id))
+            }
+            DispatchOutcome::Queued { action_id, evicted } => {
+                if let Some(evicted) = evicted {
+                    self.reject_evicted(evicted);
+                }
+                Ok(DispatchStatus::Queued(self.register_pending(action_id)))
+            }
+        }
     }
 
     /// Sequentially apply a batch of actions and emit a single coalesced
-    /// state-update event after the last action has been processed. Per-action
-    /// broadcasts are skipped — emitting N events for N actions defeats the
-    /// purpose of batching.
+    /// state-update event after the last action has been processed.
+    ///
+    /// Actions held behind a running root thunk are applied when it completes
+    /// and are not listed in `acked_action_ids`; use
+    /// [`submit_batch`](Self::submit_batch) to await them.
+    pub fn batch_dispatch(
+        &self,
+        batch_id: String,
+        actions: Vec<ZubridgeAction>,
+    ) -> crate::Result<BatchDispatchResult> {
+        let (result, _queued) = self.submit_batch(batch_id, actions)?;
+        Ok(result)
+    }
+
+    /// Sequentially submit a batch of actions through the action queue and
+    /// emit a single coalesced state-update event after the last action has
+    /// been processed. Per-action broadcasts are skipped — emitting N events
+    /// for N actions defeats the purpose of batching.
+    ///
+    /// Returns the batch result for actions that ran now, plus a
+    /// [`PendingDispatch`] for each action that was queued behind a running
+    /// root thunk.
     ///
     /// On a mid-batch dispatch error the actions that succeeded are NOT rolled
     /// back (the state manager has no transaction model), so the function
@@ -162,78 +411,259 @@ impl<R: Runtime> Zubridge<R> {
     /// were aborted because the loop bailed out — without it, the renderer
     /// would have to reject every action in the batch and a caller retrying
     /// on rejection would double-apply already-committed actions.
+    /// For the same reason a failed emit is logged rather than returned, and
+    /// an error is only returned when no action was applied.
     ///
     /// The broadcast lock is held across both the per-action dispatch loop and
     /// the coalesced broadcast, so a concurrent dispatch_action can't insert
     /// its broadcast in between and stale-base our delta.
-    pub fn batch_dispatch(
+    pub fn submit_batch(
         &self,
         batch_id: String,
         actions: Vec<ZubridgeAction>,
-    ) -> crate::Result<BatchDispatchResult> {
+    ) -> crate::Result<(BatchDispatchResult, Vec<PendingDispatch>)> {
         if actions.is_empty() {
-            return Ok(BatchDispatchResult {
-                batch_id,
-                acked_action_ids: Vec::new(),
-                failed: None,
-            });
+            return Ok((
+                BatchDispatchResult {
+                    batch_id,
+                    acked_action_ids: Vec::new(),
+                    failed: None,
+                },
+                Vec::new(),
+            ));
         }
 
-        let _broadcast_guard = self
-            .broadcast_lock
-            .lock()
-            .map_err(|e| crate::Error::StateError(e.to_string()))?;
+        let mut broadcaster = self.lock_broadcaster()?;
 
-        let mut acked = Vec::with_capacity(actions.len());
         let handle = self.state_handle()?;
+        let mut acked = Vec::with_capacity(actions.len());
+        let mut queued = Vec::new();
+        let mut drained_actions = Vec::new();
+        let mut evicted_actions = Vec::new();
         let mut last_action_id: Option<String> = None;
         let mut last_thunk_id: Option<String> = None;
         let mut failed: Option<BatchFailure> = None;
+        // Union of the keys changed by every applied action; `None` once any
+        // action's changes are unknown.
+        let mut changed_keys = Some(Vec::new());
 
-        for action in actions {
-            let action_id = action
-                .id
-                .clone()
-                .unwrap_or_else(|| Uuid::new_v4().to_string());
-            match state_manager::dispatch(&handle, action.to_legacy_json()) {
-                Ok(_) => {
-                    last_action_id = Some(action_id.clone());
-                    last_thunk_id = action.thunk_parent_id.clone();
-                    acked.push(action_id);
-                }
-                Err(e) => {
-                    failed = Some(BatchFailure {
-                        action_id,
-                        message: e.to_string(),
-                    });
-                    break;
+        {
+            let mut queue = self.lock_queue()?;
+            for mut action in actions {
+                let action_id = action
+                    .id
+                    .get_or_insert_with(|| Uuid::new_v4().to_string())
+                    .clone();
+                let source_label = action
+                    .source_label
+                    .clone()
+                    .unwrap_or_else(|| HOST_SOURCE_LABEL.to_string());
+                let thunk_id = action.thunk_parent_id.clone();
+                let dispatched = self
+                    .check_access(&queue, &action, &source_label)
+                    .and_then(|()| queue.dispatch(action, source_label));
+                match dispatched {
+                    Ok(DispatchOutcome::Executed {
+                        changed_keys: keys,
+                        drained,
+                        ..
+                    }) => {
+                        merge_changed_keys(&mut changed_keys, keys);
+                        last_action_id = Some(action_id.clone());
+                        last_thunk_id = thunk_id;
+                        acked.push(action_id);
+                        drained_actions.extend(drained);
+                    }
+                    Ok(DispatchOutcome::Queued { action_id, evicted }) => {
+                        evicted_actions.extend(evicted);
+                        queued.push(self.register_pending(action_id));
+                    }
+                    Err(e) => {
+                        failed = Some(BatchFailure {
+                            action_id,
+                            message: e.to_string(),
+                        });
+                        break;
+                    }
                 }
             }
         }
+        for evicted in evicted_actions {
+            self.reject_evicted(evicted);
+        }
 
         // Broadcast even when the loop bailed early so the renderer's replica
         // catches up to whatever actions did commit before the error. Skipped
-        // only when nothing committed (acked is empty).
+        // only when nothing committed (acked is empty). Previously-queued
+        // actions drained along the way are covered by the same coalesced
+        // broadcast — emitting their intermediate states afterwards would
+        // regress the renderer to an older state. Drained actions the state
+        // manager rejected are resolved with their own error.
+        let mut drained_ok = Vec::new();
+        for executed in drained_actions {
+            let action_id = executed.action.id.unwrap_or_default();
+            match executed.result {
+                Ok(_) => {
+                    merge_changed_keys(&mut changed_keys, executed.changed_keys);
+                    drained_ok.push(action_id);
+                }
+                Err(err) => self.resolve_pending(&action_id, Err(dispatch_error(&action_id, err))),
+            }
+        }
         if !acked.is_empty() {
-            let new_state = state_manager::read_state(&handle)?;
+            match state_manager::read_state(&handle) {
+                Ok(new_state) => {
+                    let source = UpdateSource {
+                        action_id: last_action_id,
+                        thunk_id: last_thunk_id,
+                        ..UpdateSource::default()
+                    };
+                    self.broadcast_state_locked(
+                        &mut broadcaster,
+                        new_state,
+                        changed_keys.as_deref(),
+                        Some(source),
+                    );
+                }
+                Err(err) => {
+                    log::warn!("zubridge: reading state after batch {batch_id} failed: {err}")
+                }
+            }
+            for action_id in drained_ok {
+                self.resolve_pending(&action_id, Ok(()));
+            }
+        }
+
+        Ok((
+            BatchDispatchResult {
+                batch_id,
+                acked_action_ids: acked,
+                failed,
+            },
+            queued,
+        ))
+    }
+
+    /// Start a [`BatchingDispatcher`] for host-side dispatches.
+    ///
+    /// Actions dispatched through it are applied together via
+    /// [`submit_batch`](Self::submit_batch) once per `config.window_ms`, so a
+    /// host dispatching in a loop emits one state update per window instead
+    /// of one per action. An action at or above
+    /// `config.priority_flush_threshold` (e.g. `immediate`) flushes the
+    /// window straight away. Failed actions are logged. Keep the dispatcher
+    /// alive for as long as it is used; dropping it stops its timer and
+    /// flushes anything still queued.
+    pub fn batching_dispatcher(&self, config: BatchingConfig) -> BatchingDispatcher {
+        let app = self.app.clone();
+        BatchingDispatcher::spawn(config, move |batch: BatchPayload| {
+            let ids: Vec<String> = batch.actions.iter().map(|entry| entry.id.clone()).collect();
+            let actions = batch
+                .actions
+                .into_iter()
+                .map(|entry| ZubridgeAction {
+                    id: Some(entry.id),
+                    ..entry.action
+                })
+                .collect();
+            let outcome = app.zubridge().submit_batch(batch.batch_id.clone(), actions);
+            batch_ack(batch.batch_id, &ids, outcome)
+        })
+    }
+
+    /// Broadcast the state produced by each drained action, in execution
+    /// order, and resolve the matching pending dispatch. Rejected actions
+    /// left state unchanged, so they are resolved with their error and not
+    /// broadcast.
+    fn publish_drained_locked(
+        &self,
+        broadcaster: &mut TauriBroadcaster<R>,
+        drained: Vec<ExecutedAction>,
+    ) {
+        for executed in drained {
+            let action_id = executed.action.id.clone().unwrap_or_default();
+            let state = match executed.result {
+                Ok(state) => state,
+                Err(err) => {
+                    log::debug!("zubridge: queued action {action_id} rejected: {err}");
+                    self.resolve_pending(&action_id, Err(dispatch_error(&action_id, err)));
+                    continue;
+                }
+            };
             let source = UpdateSource {
-                action_id: last_action_id,
-                thunk_id: last_thunk_id,
+                action_id: Some(action_id.clone()),
+                thunk_id: executed.action.thunk_parent_id.clone(),
+                ..UpdateSource::default()
             };
-            self.broadcast_state_locked(new_state, Some(source))?;
+            self.broadcast_state_locked(
+                broadcaster,
+                state,
+                executed.changed_keys.as_deref(),
+                Some(source),
+            );
+            self.resolve_pending(&action_id, Ok(()));
         }
+    }
 
-        Ok(BatchDispatchResult {
-            batch_id,
-            acked_action_ids: acked,
-            failed,
-        })
+    /// Park a sender for a queued action so the drain that eventually runs it
+    /// can resolve the caller.
+    ///
+    /// Infallible because the action is already queued: an error here would
+    /// report it as failed although it will still run. The map only holds
+    /// senders, so a poisoned lock leaves nothing inconsistent.
+    fn register_pending(&self, action_id: String) -> PendingDispatch {
+        let (sender, receiver) = channel(1);
+        self.pending_dispatches
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(action_id.clone(), sender);
+        PendingDispatch {
+            action_id,
+            receiver,
+        }
+    }
+
+    fn resolve_pending(&self, action_id: &str, result: crate::Result<()>) {
+        let sender = self
+            .pending_dispatches
+            .lock()
+            .ok()
+            .and_then(|mut pending| pending.remove(action_id));
+        if let Some(sender) = sender {
+            // The receiver is gone when the caller used the fire-and-forget
+            // `dispatch_action`; nothing to resolve in that case.
+            let _ = sender.try_send(result);
+        }
+    }
+
+    /// Fail the pending dispatch for an action the scheduler dropped to make
+    /// room on overflow — it will never run.
+    fn reject_evicted(&self, evicted: QueuedAction) {
+        let Some(action_id) = evicted.action.id else {
+            return;
+        };
+        let (queue_size, max_size) = self
+            .queue
+            .lock()
+            .map(|q| {
+                let stats = q.scheduler().stats();
+                (stats.queue_len, stats.max_queue_size)
+            })
+            .unwrap_or_default();
+        log::warn!("zubridge: queued action {action_id} dropped on queue overflow");
+        self.resolve_pending(
+            &action_id,
+            Err(crate::Error::QueueOverflow {
+                queue_size,
+                max_size,
+            }),
+        );
     }
 
     /// Compute and emit a state update for every active webview. Acquires
-    /// `broadcast_lock` for the duration, then delegates to
+    /// the broadcaster for the duration, then delegates to
     /// `broadcast_state_locked`. Use `broadcast_state_locked` directly if the
-    /// caller already holds the lock (e.g. dispatch_action, batch_dispatch,
+    /// caller already holds it (e.g. dispatch_action, batch_dispatch,
     /// subscribe/unsubscribe).
     #[allow(dead_code)]
     fn broadcast_state(
@@ -241,124 +671,127 @@ impl<R: Runtime> Zubridge<R> {
         new_state: JsonValue,
         source: Option<UpdateSource>,
     ) -> crate::Result<()> {
-        let _broadcast_guard = self
-            .broadcast_lock
-            .lock()
-            .map_err(|e| crate::Error::StateError(e.to_string()))?;
-        self.broadcast_state_locked(new_state, source)
+        let mut broadcaster = self.lock_broadcaster()?;
+        self.broadcast_state_locked(&mut broadcaster, new_state, None, source);
+        Ok(())
     }
 
-    /// Inner broadcast that assumes `broadcast_lock` is already held by the
-    /// caller. Two concurrent dispatches must not be able to interleave the
-    /// (compute delta → emit → record baseline) sequence — see
-    /// `dispatch_action` for the lock-acquisition path.
+    /// Broadcast `new_state` to every webview the permission policy lets
+    /// read state, through the already-locked `broadcaster`. Two concurrent
+    /// dispatches must not be able to interleave the (compute delta → emit →
+    /// record baseline) sequence — see `dispatch_action` for the
+    /// lock-acquisition path.
+    ///
+    /// A webview the update can't be emitted to doesn't stop the others, and
+    /// doesn't fail the caller: the state manager has already applied the
+    /// change, so a caller retrying on error would apply it twice. Failures
+    /// are logged and counted per webview (see
+    /// [`emit_failures`](Self::emit_failures)).
+    /// `changed_keys` are as in [`Broadcaster::broadcast`]. With
+    /// [`ZubridgeOptions::update_throttle`] set, webviews updated within the
+    /// throttle window are skipped here and caught up by
+    /// [`flush_throttled`](Self::flush_throttled).
     fn broadcast_state_locked(
         &self,
+        broadcaster: &mut TauriBroadcaster<R>,
         new_state: JsonValue,
+        changed_keys: Option<&[String]>,
         source: Option<UpdateSource>,
-    ) -> crate::Result<()> {
-        let event_name = self.options.event_name.clone();
-
-        let webviews = self.app.webview_windows();
-        for label in webviews.keys() {
-            let scoped = {
-                let subs = self
-                    .subscriptions
-                    .read()
-                    .map_err(|e| crate::Error::StateError(e.to_string()))?;
-                subs.filter_for(label, &new_state)
-            };
-
-            // Compute the delta under a read lock. `DeltaResult` distinguishes
-            // three cases:
-            //   FullState  → no baseline / shape change → emit full state
-            //   Unchanged  → state identical to baseline → skip emit
-            //   Delta(d)   → emit incremental update
-            //
-            // The baseline is recorded in a *separate* write-lock acquisition
-            // AFTER emit_to succeeds. Recording before the emit would advance
-            // the baseline past a state the renderer never received: if emit_to
-            // then fails, subsequent deltas would be computed against that
-            // phantom state and silently diverge.
-            let outcome = {
-                let calc = self
-                    .deltas
-                    .read()
-                    .map_err(|e| crate::Error::StateError(e.to_string()))?;
-                calc.compute(label, &scoped)
-            };
-
-            let (delta, full_state) = match outcome {
-                DeltaResult::Unchanged => continue,
-                DeltaResult::FullState => (None, Some(scoped.clone())),
-                DeltaResult::Delta(d) => (Some(d), None),
-            };
-
-            let seq = {
-                let mut sequences = self
-                    .sequences
-                    .write()
-                    .map_err(|e| crate::Error::StateError(e.to_string()))?;
-                sequences.next(label)
-            };
-            let update_id = Uuid::new_v4().to_string();
-
-            let payload = StateUpdatePayload {
-                seq,
-                update_id: update_id.clone(),
-                delta,
-                full_state,
-                source: source.clone(),
-            };
+    ) {
+        let targets = self.readable_labels();
+        let result = self
+            .subscriptions
+            .read()
+            .map_err(|e| zubridge_core::ZubridgeError::StateError(e.to_string()))
+            .and_then(|subs| {
+                broadcaster.broadcast(&subs, &targets, new_state, changed_keys, source)
+            });
+        if let Err(err) = result {
+            log::warn!("zubridge: state update broadcast failed: {err}");
+        }
+    }
 
-            self.app
-                .emit_to(label.clone(), &event_name, payload)
-                .map_err(|e| crate::Error::EmitError(e.to_string()))?;
-
-            // Record the new delta baseline only after the emit succeeded.
-            // If emit_to failed above, we propagate the error without updating
-            // the baseline, so the next broadcast recomputes against the last
-            // successfully-delivered state.
-            {
-                let mut calc = self
-                    .deltas
-                    .write()
-                    .map_err(|e| crate::Error::StateError(e.to_string()))?;
-                calc.record(label, scoped);
-            }
+    /// Labels of the webviews the permission policy lets read state.
+    fn readable_labels(&self) -> Vec<String> {
+        self.app
+            .webview_windows()
+            .into_keys()
+            .filter(|label| self.check_read(label, None).is_ok())
+            .collect()
+    }
 
-            {
-                let mut tracker = self
-                    .update_tracker
-                    .write()
-                    .map_err(|e| crate::Error::StateError(e.to_string()))?;
-                tracker.record_pending(label, &update_id);
-            }
+    /// Emit the updates the throttle deferred for every webview whose window
+    /// has elapsed, each diffed against the latest broadcast state. Called
+    /// periodically by the throttle timer; a no-op when throttling is off.
+    pub fn flush_throttled(&self) -> crate::Result<()> {
+        let mut broadcaster = self.lock_broadcaster()?;
+        if !broadcaster.has_throttled() {
+            return Ok(());
         }
+        let subs = self
+            .subscriptions
+            .read()
+            .map_err(|e| crate::Error::StateError(e.to_string()))?;
+        broadcaster.flush_throttled(&subs, &self.readable_labels())?;
         Ok(())
     }
 
     /// Triggered by the renderer to acknowledge it has applied a state update.
     pub fn state_update_ack(&self, source_label: &str, update_id: &str) -> crate::Result<()> {
-        let mut tracker = self
-            .update_tracker
-            .write()
+        let mut broadcaster = self.lock_broadcaster()?;
+        let subs = self
+            .subscriptions
+            .read()
+            .map_err(|e| crate::Error::StateError(e.to_string()))?;
+        broadcaster.ack(&subs, source_label, update_id)?;
+        Ok(())
+    }
+
+    /// Treat state updates unacked for longer than
+    /// [`ZubridgeOptions::ack_timeout`] as lost: each affected webview's
+    /// delta baseline is dropped and it is sent the full state. Called
+    /// periodically by the ack sweeper; a no-op without a timeout.
+    pub fn sweep_expired_acks(&self) -> crate::Result<()> {
+        let Some(timeout) = self.options.ack_timeout else {
+            return Ok(());
+        };
+        let mut broadcaster = self.lock_broadcaster()?;
+        let subs = self
+            .subscriptions
+            .read()
             .map_err(|e| crate::Error::StateError(e.to_string()))?;
-        tracker.ack(source_label, update_id);
+        broadcaster.sweep_expired_acks(&subs, timeout)?;
         Ok(())
     }
 
+    /// Per-webview state-update ack latencies, including updates lost to
+    /// [`ZubridgeOptions::ack_timeout`].
+    pub fn ack_latencies(&self) -> crate::Result<HashMap<String, AckLatencyHistogram>> {
+        Ok(self.lock_broadcaster()?.updates().ack_latencies().clone())
+    }
+
+    /// Labels stalled on unacknowledged state updates (see
+    /// [`ZubridgeOptions::max_in_flight`]).
+    pub fn slow_consumers(&self) -> crate::Result<Vec<String>> {
+        Ok(self.lock_broadcaster()?.updates().stalled_labels())
+    }
+
+    /// Failed state-update emits per webview. A webview whose
+    /// [`consecutive`](EmitFailures::consecutive) count keeps growing is
+    /// persistently unreachable; it is sent its full state once an emit
+    /// succeeds again.
+    pub fn emit_failures(&self) -> crate::Result<HashMap<String, EmitFailures>> {
+        Ok(self.lock_broadcaster()?.emit_failures().clone())
+    }
+
     /// Subscribe a webview to a set of top-level state keys.
     pub fn subscribe(&self, source_label: &str, keys: &[String]) -> crate::Result<Vec<String>> {
-        // Hold the broadcast lock across the subscription mutation, the
+        // Hold the broadcaster across the subscription mutation, the
         // delta-baseline reset, and the immediate state push. Without this a
         // concurrent dispatch_action's broadcast could observe an inconsistent
         // (new subscriptions, old baseline) snapshot and emit a delta computed
         // against the wrong base.
-        let _broadcast_guard = self
-            .broadcast_lock
-            .lock()
-            .map_err(|e| crate::Error::StateError(e.to_string()))?;
+        let mut broadcaster = self.lock_broadcaster()?;
 
         let resulting = {
             let mut subs = self
@@ -372,26 +805,17 @@ impl<R: Runtime> Zubridge<R> {
         };
         // Force a full-state resync for this label so the renderer's local
         // replica matches the new key set.
-        {
-            let mut deltas = self
-                .deltas
-                .write()
-                .map_err(|e| crate::Error::StateError(e.to_string()))?;
-            deltas.forget(source_label);
-        }
+        broadcaster.deltas_mut().forget(source_label);
         // Push the current state immediately so the subscriber sees its
         // newly-included keys without waiting for the next dispatch. Best
         // effort: if no state manager is registered yet, skip the broadcast
         // (the next dispatch will catch up).
-        self.broadcast_current_state_locked();
+        self.broadcast_current_state_locked(&mut broadcaster);
         Ok(resulting)
     }
 
     pub fn unsubscribe(&self, source_label: &str, keys: &[String]) -> crate::Result<Vec<String>> {
-        let _broadcast_guard = self
-            .broadcast_lock
-            .lock()
-            .map_err(|e| crate::Error::StateError(e.to_string()))?;
+        let mut broadcaster = self.lock_broadcaster()?;
 
         let resulting = {
             let mut subs = self
@@ -403,17 +827,11 @@ impl<R: Runtime> Zubridge<R> {
                 })?;
             subs.unsubscribe(source_label, keys)
         };
-        {
-            let mut deltas = self
-                .deltas
-                .write()
-                .map_err(|e| crate::Error::StateError(e.to_string()))?;
-            deltas.forget(source_label);
-        }
+        broadcaster.deltas_mut().forget(source_label);
         // Push the current state so the renderer's replica drops the
         // now-unsubscribed keys instead of leaving them stale until the next
         // dispatch.
-        self.broadcast_current_state_locked();
+        self.broadcast_current_state_locked(&mut broadcaster);
         Ok(resulting)
     }
 
@@ -422,15 +840,11 @@ impl<R: Runtime> Zubridge<R> {
     /// swallowed because the caller's subscription change has already
     /// succeeded — the next real dispatch will reconcile.
     ///
-    /// **Caller must hold `broadcast_lock`** (this function calls
-    /// `broadcast_state_locked` directly to avoid a drop+reacquire window).
-    fn broadcast_current_state_locked(&self) {
-        if let Ok(handle) = self.state_handle() {
-            if let Ok(state) = state_manager::read_state(&handle) {
-                if let Err(err) = self.broadcast_state_locked(state, None) {
-                    log::warn!("zubridge: post-subscription broadcast failed: {err}");
-                }
-            }
+    /// Takes the caller's locked `broadcaster` to avoid a drop+reacquire
+    /// window.
+    fn broadcast_current_state_locked(&self, broadcaster: &mut TauriBroadcaster<R>) {
+        if let Ok(state) = self.current_state() {
+            self.broadcast_state_locked(broadcaster, state, None, None);
         }
     }
 
@@ -454,81 +868,221 @@ impl<R: Runtime> Zubridge<R> {
         bypass_access_control: bool,
         immediate: bool,
     ) -> crate::Result<()> {
-        let mut registry = self
-            .thunks
-            .write()
+        let mut queue = self
+            .queue
+            .lock()
             .map_err(|e| crate::Error::ThunkRegistration {
                 thunk_id: thunk_id.clone(),
                 message: e.to_string(),
             })?;
-        registry
-            .register(
-                thunk_id.clone(),
-                parent_id,
-                source_label,
-                keys,
+        {
+            let parent = parent_id
+                .as_deref()
+                .and_then(|pid| queue.thunk_manager().get(pid));
+            self.check_permission(
+                &source_label,
+                &PermissionRequest::RegisterThunk {
+                    thunk_id: &thunk_id,
+                    keys: keys.as_deref(),
+                    parent,
+                },
+            )?;
+            self.options.access_control.check_thunk(
+                &source_label,
+                keys.as_deref(),
                 bypass_access_control,
-                immediate,
-            )
-            .map_err(|message| crate::Error::ThunkRegistration {
-                thunk_id: thunk_id.clone(),
-                message,
-            })?;
-        let _ = registry.execute_thunk(&thunk_id);
+            )?;
+        }
+        queue.register_thunk(
+            thunk_id.clone(),
+            parent_id,
+            source_label,
+            keys,
+            bypass_access_control,
+            immediate,
+        )?;
+        // Start the thunk straight away: a root-level thunk becomes the root
+        // thunk and holds back actions from other windows until it completes.
+        queue.execute_thunk(&thunk_id);
         Ok(())
     }
 
+    /// Complete (or fail) a thunk, then apply and broadcast any actions that
+    /// were queued behind it.
     pub fn complete_thunk(
         &self,
         thunk_id: &str,
         source_label: &str,
         error: Option<String>,
     ) -> crate::Result<()> {
-        let mut registry = self.thunks.write().map_err(|e| crate::Error::ThunkRegistration {
-            thunk_id: thunk_id.to_string(),
-            message: e.to_string(),
-        })?;
+        // Drained actions are broadcast below; hold the broadcaster across
+        // the drain so no other dispatch can broadcast in between.
+        let mut broadcaster = self.lock_broadcaster()?;
 
-        // Verify the caller owns this thunk. Without this, any webview could
-        // complete another window's in-flight thunk by id.
-        let owner_label = registry
-            .get(thunk_id)
-            .ok_or_else(|| crate::Error::ThunkNotFound {
-                thunk_id: thunk_id.to_string(),
-            })?
-            .source_label
-            .clone();
-        if owner_label != source_label {
-            return Err(crate::Error::ThunkRegistration {
+        let drained = {
+            let mut queue = self.queue.lock().map_err(|e| crate::Error::ThunkRegistration {
                 thunk_id: thunk_id.to_string(),
-                message: format!(
-                    "thunk {thunk_id} is owned by {owner_label}, not {source_label}"
-                ),
-            });
+                message: e.to_string(),
+            })?;
+
+            // Verify the caller owns this thunk. Without this, any webview could
+            // complete another window's in-flight thunk by id.
+            let owner_label = queue
+                .thunk_manager()
+                .get(thunk_id)
+                .ok_or_else(|| crate::Error::ThunkNotFound {
+                    thunk_id: thunk_id.to_string(),
+                })?
+                .source_label
+                .clone();
+            if owner_label != source_label {
+                return Err(crate::Error::ThunkRegistration {
+                    thunk_id: thunk_id.to_string(),
+                    message: format!(
+                        "thunk {thunk_id} is owned by {owner_label}, not {source_label}"
+                    ),
+                });
+            }
+
+            let (_events, drained) = queue.on_thunk_complete(thunk_id, error)?;
+            drained
+        };
+
+        self.publish_drained_locked(&mut broadcaster, drained);
+        Ok(())
+    }
+
+    /// A [`JsonFileBackend`] storing `file_name` in the app data directory,
+    /// for use with a
+    /// [`PersistedStateManager`](zubridge_core::persistence::PersistedStateManager).
+    pub fn persistence_backend(&self, file_name: &str) -> crate::Result<JsonFileBackend> {
+        let dir = self
+            .app
+            .path()
+            .app_data_dir()
+            .map_err(|e| crate::Error::Persistence(e.to_string()))?;
+        Ok(JsonFileBackend::new(dir.join(file_name)))
+    }
+
+    /// Open the journal described by `config`, resolving a relative path
+    /// against the app data directory.
+    fn open_journal(&self, mut config: JournalConfig) -> crate::Result<ActionJournal> {
+        if config.path.is_relative() {
+            let dir = self
+                .app
+                .path()
+                .app_data_dir()
+                .map_err(|e| crate::Error::Persistence(e.to_string()))?;
+            config.path = dir.join(&config.path);
         }
+        Ok(ActionJournal::open(config)?)
+    }
 
-        registry
-            .complete(thunk_id, error)
-            .map_err(|_| crate::Error::ThunkNotFound {
-                thunk_id: thunk_id.to_string(),
-            })?;
+    /// Replace the Redux DevTools bridge and attach it to `devtools`, which
+    /// defaults to a session of [`DEFAULT_REMOTE_DEVTOOLS_CAPACITY`] entries
+    /// when [`ZubridgeOptions::devtools_capacity`] is unset.
+    #[cfg(feature = "devtools")]
+    fn start_remote_devtools(
+        &mut self,
+        config: Option<RemoteDevtoolsConfig>,
+        devtools: Option<DevtoolsSession>,
+    ) -> Option<DevtoolsSession> {
+        // Disconnect the previous bridge before the new one connects.
+        self.remote_devtools = None;
+        let Some(config) = config else {
+            return devtools;
+        };
+        let host = Arc::new(PluginDevtoolsHost {
+            app: self.app.clone(),
+        });
+        match RemoteDevtools::start(config, host) {
+            Ok(remote) => {
+                let mut devtools = devtools
+                    .unwrap_or_else(|| DevtoolsSession::new(DEFAULT_REMOTE_DEVTOOLS_CAPACITY));
+                devtools.add_observer(remote.observer());
+                self.remote_devtools = Some(remote);
+                Some(devtools)
+            }
+            Err(err) => {
+                log::warn!("zubridge: Redux DevTools bridge disabled: {err}");
+                devtools
+            }
+        }
+    }
+
+    /// The files of the active action journal, oldest first, ready for
+    /// [`replay_files`](zubridge_core::journal::replay_files). Empty when
+    /// [`ZubridgeOptions::journal`] is unset.
+    pub fn journal_files(&self) -> crate::Result<Vec<std::path::PathBuf>> {
+        Ok(self
+            .lock_queue()?
+            .journal()
+            .map(ActionJournal::files)
+            .unwrap_or_default())
+    }
+
+    /// The actions recorded by the devtools session, oldest first. Empty when
+    /// [`ZubridgeOptions::devtools_capacity`] is unset. With `source_label`,
+    /// each entry's state is filtered to that webview's subscriptions, as in
+    /// [`get_state`](Self::get_state).
+    pub fn devtools_entries(
+        &self,
+        source_label: Option<&str>,
+    ) -> crate::Result<Vec<DevtoolsEntry>> {
+        let mut entries: Vec<DevtoolsEntry> = self
+            .lock_queue()?
+            .devtools()
+            .map(|devtools| devtools.entries().cloned().collect())
+            .unwrap_or_default();
+        if let Some(label) = source_label {
+            let subs = self
+                .subscriptions
+                .read()
+                .map_err(|e| crate::Error::StateError(e.to_string()))?;
+            for entry in &mut entries {
+                entry.state = subs.filter_for(label, &entry.state);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Show every webview the state after devtools entry `index`. New
+    /// dispatches fail, and queued actions wait, until
+    /// [`devtools_resume`](Self::devtools_resume).
+    pub fn devtools_jump(&self, index: u64) -> crate::Result<()> {
+        let mut broadcaster = self.lock_broadcaster()?;
+        let state = self.lock_queue()?.devtools_jump(index)?;
+        self.broadcast_state_locked(&mut broadcaster, state, None, None);
+        Ok(())
+    }
+
+    /// Return every webview to the live state and run the actions queued
+    /// while paused.
+    pub fn devtools_resume(&self) -> crate::Result<()> {
+        let mut broadcaster = self.lock_broadcaster()?;
+        let drained = self.lock_queue()?.devtools_resume();
+        let live = state_manager::read_state(&self.state_handle()?)?;
+        self.broadcast_state_locked(&mut broadcaster, live, None, None);
+        self.publish_drained_locked(&mut broadcaster, drained);
         Ok(())
     }
 
     /// Register a state manager at runtime (used when the plugin is initialised
     /// without one).
-    pub fn register_state_manager<S: StateManager>(
+    pub fn register_state_manager<S: TryStateManager>(
         &self,
         state_manager: S,
     ) -> crate::Result<()> {
         let handle = state_manager::new_handle(state_manager);
+        self.set_state_handle(handle.clone())?;
         self.app.manage(handle);
         Ok(())
     }
 
     /// Drop all per-label state for a webview that's been closed: subscription
     /// keys, delta baseline, sequence counter, pending state-update acks, and
-    /// any thunks owned by the webview.
+    /// any thunks owned by the webview. Actions queued behind those thunks are
+    /// released and broadcast.
     ///
     /// Wired automatically to `RunEvent::WindowEvent { event: Destroyed, .. }`
     /// in `lib.rs::forget_on_destroy`. Also exposed publicly so hosts that
@@ -538,17 +1092,16 @@ impl<R: Runtime> Zubridge<R> {
         if let Ok(mut subs) = self.subscriptions.write() {
             subs.drop_label(label);
         }
-        if let Ok(mut deltas) = self.deltas.write() {
-            deltas.forget(label);
-        }
-        if let Ok(mut tracker) = self.update_tracker.write() {
-            tracker.drop_label(label);
-        }
-        if let Ok(mut sequences) = self.sequences.write() {
-            sequences.forget(label);
-        }
-        if let Ok(mut thunks) = self.thunks.write() {
-            thunks.drop_label(label);
+        if let Ok(mut broadcaster) = self.broadcaster.lock() {
+            broadcaster.forget(label);
+            let drained = match self.queue.lock() {
+                Ok(mut queue) => queue.drop_label(label).unwrap_or_else(|err| {
+                    log::warn!("zubridge: draining queue after closing {label} failed: {err}");
+                    Vec::new()
+                }),
+                Err(_) => Vec::new(),
+            };
+            self.publish_drained_locked(&mut broadcaster, drained);
         }
     }
 
@@ -571,3 +1124,183 @@ impl<R: Runtime> Zubridge<R> {
         }
     }
 }
+
+/// Session size used for the Redux DevTools bridge when
+/// [`ZubridgeOptions::devtools_capacity`] is unset.
+#[cfg(feature = "devtools")]
+pub const DEFAULT_REMOTE_DEVTOOLS_CAPACITY: usize = 100;
+
+/// Applies Redux DevTools requests to the managed [`Zubridge`].
+#[cfg(feature = "devtools")]
+struct PluginDevtoolsHost<R: Runtime> {
+    app: AppHandle<R>,
+}
+
+#[cfg(feature = "devtools")]
+impl<R: Runtime> PluginDevtoolsHost<R> {
+    fn with_zubridge<T>(
+        &self,
+        f: impl FnOnce(&Zubridge<R>) -> crate::Result<T>,
+    ) -> zubridge_core::Result<T> {
+        let zubridge = self.app.try_state::<Zubridge<R>>().ok_or_else(|| {
+            zubridge_core::ZubridgeError::Devtools("plugin is not initialised".to_string())
+        })?;
+        f(&zubridge).map_err(|err| zubridge_core::ZubridgeError::Devtools(err.to_string()))
+    }
+}
+
+#[cfg(feature = "devtools")]
+impl<R: Runtime> DevtoolsHost for PluginDevtoolsHost<R> {
+    fn dispatch(&self, action: ZubridgeAction) -> zubridge_core::Result<()> {
+        self.with_zubridge(|zubridge| zubridge.dispatch_action(action).map(|_| ()))
+    }
+
+    fn jump_to(&self, index: u64) -> zubridge_core::Result<()> {
+        self.with_zubridge(|zubridge| zubridge.devtools_jump(index))
+    }
+
+    fn resume(&self) -> zubridge_core::Result<()> {
+        self.with_zubridge(Zubridge::devtools_resume)
+    }
+
+    fn snapshot(&self) -> zubridge_core::Result<(JsonValue, Option<u64>)> {
+        self.with_zubridge(|zubridge| {
+            // Under the broadcaster's lock no action commits between the two
+            // reads.
+            let _broadcaster = zubridge.lock_broadcaster()?;
+            let state = state_manager::read_state(&zubridge.state_handle()?)?;
+            let latest = zubridge
+                .lock_queue()?
+                .devtools()
+                .and_then(DevtoolsSession::latest_index);
+            Ok((state, latest))
+        })
+    }
+}
+
+/// Notify host listeners of [`SLOW_CONSUMER_EVENT`] of a backpressure
+/// transition.
+fn report_slow_consumer<R: Runtime>(app: &AppHandle<R>, event: &SlowConsumerEvent) {
+    if let Err(err) = app.emit_to(EventTarget::app(), SLOW_CONSUMER_EVENT, event) {
+        log::warn!("zubridge: failed to report slow consumer: {err}");
+    }
+}
+
+/// Run `task` on the managed [`Zubridge`] every half `period` on a thread
+/// named `zubridge-<name>`, until the returned sender is dropped.
+fn spawn_timer<R: Runtime>(
+    app: AppHandle<R>,
+    name: &'static str,
+    period: Duration,
+    task: fn(&Zubridge<R>) -> crate::Result<()>,
+) -> mpsc::Sender<()> {
+    let (stop, stopped) = mpsc::channel::<()>();
+    let tick = (period / 2).max(Duration::from_millis(1));
+    let spawned = std::thread::Builder::new()
+        .name(format!("zubridge-{name}"))
+        .spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(tick) {
+                if let Some(zubridge) = app.try_state::<Zubridge<R>>() {
+                    if let Err(err) = task(&zubridge) {
+                        log::warn!("zubridge: {name} timer failed: {err}");
+                    }
+                }
+            }
+        });
+    if let Err(err) = spawned {
+        log::warn!("zubridge: failed to start the {name} timer: {err}");
+    }
+    stop
+}
+
+/// Translate a `submit_batch` outcome into the per-action results a
+/// [`BatchingDispatcher`] expects. Actions applied or queued behind a thunk
+/// count as accepted; only the failing action and those after it are
+/// reported as failed. `submit_batch` returns an error only when it applied
+/// nothing, so every action is failed then. Results are reported per action
+/// rather than as a batch-level error, which would retry the batch.
+fn batch_ack(
+    batch_id: String,
+    ids: &[String],
+    outcome: crate::Result<(BatchDispatchResult, Vec<PendingDispatch>)>,
+) -> BatchAckPayload {
+    let results = match outcome {
+        Ok((result, queued)) => ids
+            .iter()
+            .map(|id| {
+                let accepted = result.acked_action_ids.contains(id)
+                    || queued.iter().any(|pending| pending.action_id() == id);
+                let error = match &result.failed {
+                    _ if accepted => None,
+                    Some(failed) if failed.action_id == *id => Some(failed.message.clone()),
+                    _ => Some("not applied: an earlier action in the batch failed".to_string()),
+                };
+                BatchActionResult {
+                    action_id: id.clone(),
+                    success: error.is_none(),
+                    error,
+                }
+            })
+            .collect(),
+        Err(err) => ids
+            .iter()
+            .map(|id| BatchActionResult {
+                action_id: id.clone(),
+                success: false,
+                error: Some(err.to_string()),
+            })
+            .collect(),
+    };
+    BatchAckPayload {
+        batch_id,
+        results,
+        error: None,
+    }
+}
+
+/// Add the keys changed by one more applied action to `acc`. Once any action
+/// reports unknown changes (`None`), so does the union.
+fn merge_changed_keys(acc: &mut Option<Vec<String>>, keys: Option<Vec<String>>) {
+    match (acc.as_mut(), keys) {
+        (Some(acc), Some(keys)) => {
+            for key in keys {
+                if !acc.contains(&key) {
+                    acc.push(key);
+                }
+            }
+        }
+        _ => *acc = None,
+    }
+}
+
+/// Map a queue error to the plugin error surfaced to the caller, attributing
+/// processing failures to `action_id`.
+fn dispatch_error(action_id: &str, err: zubridge_core::ZubridgeError) -> crate::Error {
+    match err {
+        zubridge_core::ZubridgeError::StateManagerMissing => crate::Error::StateManagerMissing,
+        zubridge_core::ZubridgeError::AccessDenied {
+            source_label,
+            keys,
+            message,
+        } => crate::Error::AccessDenied {
+            source_label,
+            keys,
+            message,
+        },
+        zubridge_core::ZubridgeError::PermissionDenied {
+            source_label,
+            message,
+        } => crate::Error::PermissionDenied {
+            source_label,
+            message,
+        },
+        zubridge_core::ZubridgeError::ActionProcessing(message) => crate::Error::ActionProcessing {
+            action_id: Some(action_id.to_string()),
+            message,
+        },
+        other => crate::Error::ActionProcessing {
+            action_id: Some(action_id.to_string()),
+            message: other.to_string(),
+        },
+    }
+}
@@ -20,6 +20,16 @@ pub enum Error {
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
+    #[error("Persistence error: {0}")]
+    Persistence(String),
+
+    #[error("State migration from schema v{from} to v{to} failed: {message}")]
+    Migration {
+        from: u32,
+        to: u32,
+        message: String,
+    },
+
     #[error("Action processing failed for action {action_id:?}: {message}")]
     ActionProcessing {
         action_id: Option<String>,
@@ -49,8 +59,24 @@ pub enum Error {
         thunk_id: String,
     },
 
+    #[error("Access denied for {source_label}: {message}")]
+    AccessDenied {
+        source_label: String,
+        keys: Vec<String>,
+        message: String,
+    },
+
+    #[error("Permission denied for {source_label}: {message}")]
+    PermissionDenied {
+        source_label: String,
+        message: String,
+    },
+
     #[error("State manager not registered")]
     StateManagerMissing,
+
+    #[error("Devtools error: {0}")]
+    Devtools(String),
 }
 
 impl From<zubridge_core::ZubridgeError> for Error {
@@ -62,7 +88,35 @@ impl From<zubridge_core::ZubridgeError> for Error {
                 action_id: None,
                 message: msg,
             },
+            Z::InvalidAction { .. } | Z::UnknownActionType { .. } => Error::ActionProcessing {
+                action_id: None,
+                message: err.to_string(),
+            },
+            Z::AccessDenied {
+                source_label,
+                keys,
+                message,
+            } => Error::AccessDenied {
+                source_label,
+                keys,
+                message,
+            },
+            Z::PermissionDenied {
+                source_label,
+                message,
+            } => Error::PermissionDenied {
+                source_label,
+                message,
+            },
+            Z::QueueOverflow {
+                queue_size,
+                max_size,
+            } => Error::QueueOverflow {
+                queue_size,
+                max_size,
+            },
             Z::StateManagerMissing => Error::StateManagerMissing,
+            err @ Z::HydrateUnsupported => Error::StateError(err.to_string()),
             Z::ThunkNotFound { thunk_id } => Error::ThunkNotFound { thunk_id },
             Z::ThunkRegistration { thunk_id, message } => {
                 Error::ThunkRegistration { thunk_id, message }
@@ -72,6 +126,9 @@ impl From<zubridge_core::ZubridgeError> for Error {
             }
             Z::EmitError(msg) => Error::EmitError(msg),
             Z::Serialization(msg) => Error::SerializationError(msg),
+            Z::Persistence(msg) => Error::Persistence(msg),
+            Z::Migration { from, to, message } => Error::Migration { from, to, message },
+            Z::Devtools(msg) => Error::Devtools(msg),
         }
     }
 }
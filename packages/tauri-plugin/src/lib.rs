@@ -16,9 +16,25 @@ mod error;
 mod models;
 
 pub use error::{Error, Result};
+pub use zubridge_core::access;
+pub use zubridge_core::batching::{BatchingConfig, BatchingDispatcher};
+pub use zubridge_core::broadcast::EmitFailures;
+pub use zubridge_core::devtools::{self, DevtoolsEntry, DevtoolsSession};
+pub use zubridge_core::handlers::HandlerRegistry;
+pub use zubridge_core::history;
+pub use zubridge_core::journal;
+pub use zubridge_core::migration;
+pub use zubridge_core::permission;
+pub use zubridge_core::persistence;
+pub use zubridge_core::slice::{
+    SliceStateManager, SliceStateManagerBuilder, SLICE_INIT_ACTION_TYPE,
+};
+pub use zubridge_core::thunk::{AckLatencyHistogram, SlowConsumerEvent, ACK_LATENCY_BUCKETS_MS};
+pub use zubridge_core::typed::{TypedAdapter, TypedStateManager};
+pub use zubridge_core::ZubridgeError;
 
 #[cfg(desktop)]
-pub use desktop::Zubridge;
+pub use desktop::{DispatchStatus, PendingDispatch, TauriBroadcaster, Zubridge};
 #[cfg(mobile)]
 pub use mobile::Zubridge;
 
@@ -37,8 +53,13 @@ impl<R: Runtime, T: Manager<R>> crate::ZubridgeExt<R> for T {
 /// Default Tauri event name for state-update payloads.
 pub const STATE_UPDATE_EVENT: &str = "zubridge://state-update";
 
+/// Tauri event emitted to app-level listeners (`AppHandle::listen`) when a
+/// webview stalls on, or resumes from, unacknowledged state updates. The
+/// payload is a [`SlowConsumerEvent`].
+pub const SLOW_CONSUMER_EVENT: &str = "zubridge://slow-consumer";
+
 /// Build the plugin with the given state manager and options.
-pub fn plugin<R: Runtime, S: StateManager>(
+pub fn plugin<R: Runtime, S: TryStateManager>(
     state_manager: S,
     options: ZubridgeOptions,
 ) -> TauriPlugin<R> {
@@ -56,6 +77,13 @@ pub fn plugin<R: Runtime, S: StateManager>(
             commands::subscription::subscribe,
             commands::subscription::unsubscribe,
             commands::subscription::get_window_subscriptions,
+            commands::state::get_action_types,
+            #[cfg(debug_assertions)]
+            commands::devtools::devtools_entries,
+            #[cfg(debug_assertions)]
+            commands::devtools::devtools_jump,
+            #[cfg(debug_assertions)]
+            commands::devtools::devtools_resume,
         ])
         .setup(move |app, api| {
             #[cfg(mobile)]
@@ -63,6 +91,8 @@ pub fn plugin<R: Runtime, S: StateManager>(
             #[cfg(desktop)]
             let mut zubridge = desktop::init(app, api)?;
             zubridge.set_options(options.clone());
+            #[cfg(desktop)]
+            zubridge.set_state_handle(handle.clone())?;
 
             app.manage(handle.clone());
             app.manage(zubridge);
@@ -73,7 +103,7 @@ pub fn plugin<R: Runtime, S: StateManager>(
 }
 
 /// Build the plugin with the given state manager and default options.
-pub fn plugin_default<R: Runtime, S: StateManager>(state_manager: S) -> TauriPlugin<R> {
+pub fn plugin_default<R: Runtime, S: TryStateManager>(state_manager: S) -> TauriPlugin<R> {
     plugin::<R, S>(state_manager, ZubridgeOptions::default())
 }
 
@@ -92,6 +122,13 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
             commands::subscription::subscribe,
             commands::subscription::unsubscribe,
             commands::subscription::get_window_subscriptions,
+            commands::state::get_action_types,
+            #[cfg(debug_assertions)]
+            commands::devtools::devtools_entries,
+            #[cfg(debug_assertions)]
+            commands::devtools::devtools_jump,
+            #[cfg(debug_assertions)]
+            commands::devtools::devtools_resume,
         ])
         .setup(|app, api| {
             #[cfg(mobile)]
@@ -71,6 +71,8 @@ export interface StateUpdatePayload {
   source?: {
     action_id?: string;
     thunk_id?: string;
+    /** Every action covered by a throttled update, oldest first. */
+    action_ids?: string[];
   };
 }
 
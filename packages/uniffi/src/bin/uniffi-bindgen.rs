@@ -0,0 +1,6 @@
+//! `cargo run -p zubridge-uniffi --bin uniffi-bindgen -- generate --library
+//! target/debug/libzubridge_uniffi.so --language python --out-dir <dir>`
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
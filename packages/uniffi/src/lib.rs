@@ -0,0 +1,7 @@
+//! The UniFFI library for [`zubridge_core::wrappers::uniffi`]. The exports
+//! live in `zubridge-core` behind its `uniffi` feature; this crate only
+//! provides the `cdylib` that foreign bindings load and are generated from,
+//! so crates depending on `zubridge-core` don't link a shared library they
+//! never load.
+
+zubridge_core::uniffi_reexport_scaffolding!();
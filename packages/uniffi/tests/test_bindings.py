@@ -0,0 +1,118 @@
+"""Exercise the generated Python bindings across the FFI boundary.
+
+    cargo build -p zubridge-uniffi
+    cargo run -p zubridge-uniffi --bin uniffi-bindgen -- generate \\
+        --library target/debug/libzubridge_uniffi.so --language python \\
+        --out-dir packages/uniffi/bindings
+    cp target/debug/libzubridge_uniffi.so packages/uniffi/bindings/
+    python -m unittest discover -s packages/uniffi/tests
+"""
+
+import json
+import os
+import sys
+import unittest
+
+sys.path.insert(
+    0,
+    os.environ.get(
+        "ZUBRIDGE_BINDINGS_DIR",
+        os.path.join(os.path.dirname(__file__), "..", "bindings"),
+    ),
+)
+
+from zubridge_core import (  # noqa: E402
+    EventEmitter,
+    StateManager,
+    ZubridgeCore,
+    ZubridgeFfiError,
+)
+
+
+class CounterStore(StateManager):
+    def __init__(self):
+        self.state = {"counter": 0, "theme": "dark"}
+
+    def get_state(self):
+        return json.dumps(self.state)
+
+    def dispatch_action(self, action):
+        action = json.loads(action)
+        if action["type"] == "COUNTER:INCREMENT":
+            self.state = {**self.state, "counter": self.state["counter"] + 1}
+        elif action["type"] == "THEME:SET":
+            self.state = {**self.state, "theme": action["payload"]}
+        else:
+            raise ZubridgeFfiError.Rejected(f"unknown action {action['type']}")
+        return json.dumps(self.state)
+
+    def action_types(self):
+        return ["COUNTER:INCREMENT", "THEME:SET"]
+
+
+class Recorder(EventEmitter):
+    def __init__(self):
+        self.events = []
+
+    def emit(self, target, event, payload):
+        self.events.append((target, event, json.loads(payload)))
+
+
+def action(action_type, payload=None, action_id=None):
+    return json.dumps({"id": action_id, "action_type": action_type, "payload": payload})
+
+
+class ZubridgeCoreTest(unittest.TestCase):
+    def setUp(self):
+        self.store = CounterStore()
+        self.core = ZubridgeCore(self.store)
+        self.events = Recorder()
+        self.core.set_event_emitter(self.events)
+
+    def test_dispatch_updates_state_and_emits_a_delta(self):
+        self.core.add_subscriber("main")
+        target, event, initial = self.events.events.pop()
+        self.assertEqual((target, event), ("main", "state-update"))
+        self.assertEqual(initial["full_state"], {"counter": 0, "theme": "dark"})
+
+        result = self.core.dispatch(action("COUNTER:INCREMENT", action_id="a1"), "main")
+        self.assertEqual(result.action_id, "a1")
+        self.assertFalse(result.queued)
+        _, _, update = self.events.events.pop()
+        self.assertEqual(update["seq"], initial["seq"] + 1)
+        self.assertEqual(update["delta"]["changed"], {"counter": 1})
+        self.assertEqual(json.loads(self.core.get_state(None)), {"counter": 1, "theme": "dark"})
+
+    def test_subscriptions_filter_state(self):
+        self.assertEqual(self.core.subscribe("overlay", ["theme"]), ["theme"])
+        self.assertEqual(json.loads(self.core.get_state("overlay")), {"theme": "dark"})
+        self.core.dispatch(action("COUNTER:INCREMENT"), "main")
+        self.assertEqual(len(self.events.events), 1, "counter changes skip the overlay")
+
+    def test_rejected_and_malformed_actions_raise(self):
+        with self.assertRaises(ZubridgeFfiError.Rejected):
+            self.core.dispatch(action("UNKNOWN"), "main")
+        with self.assertRaises(ZubridgeFfiError.InvalidJson):
+            self.core.dispatch("not json", "main")
+        self.assertEqual(self.store.state["counter"], 0)
+
+    def test_thunks_hold_back_other_sources(self):
+        self.core.register_thunk("t1", "main", None, None, False, False)
+        queued = self.core.dispatch(action("THEME:SET", "light"), "overlay")
+        self.assertTrue(queued.queued)
+        self.assertEqual(self.store.state["theme"], "dark")
+        self.core.complete_thunk("t1", None)
+        self.assertEqual(self.store.state["theme"], "light")
+
+    def test_batch_dispatch_reports_each_action(self):
+        results = self.core.batch_dispatch(
+            [action("COUNTER:INCREMENT"), action("UNKNOWN"), "not json"], "main"
+        )
+        self.assertIsNone(results[0].error)
+        self.assertIn("unknown action UNKNOWN", results[1].error)
+        self.assertIsNone(results[2].action_id)
+        self.assertEqual(self.store.state["counter"], 1)
+
+
+if __name__ == "__main__":
+    unittest.main()